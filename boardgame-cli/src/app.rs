@@ -2,75 +2,224 @@ use std::{
     cell::{Ref, RefCell},
     collections::{HashMap, VecDeque},
     io::{self, Stdout},
-    time::{Duration, Instant},
+    rc::Rc,
+    sync::mpsc::Receiver,
+    time::Instant,
 };
 
-use boardgame_core::{db::{Boardgame, BoardgameDb}, strings::*};
-use crossterm::event::{self, Event, KeyCode};
+use boardgame_core::{db::{
+    Boardgame, BoardgameDb, BoardgameFilter, BoardgameSort, Component, Credit, CreditRole, CustomField, CustomFieldType,
+    CustomValue, DbEvent, GameCopy, HistoryEntry, HouseRule, ImageInfo, ImageKind, Mechanic, PlayerCountPoll,
+    PlayerCountVote,
+}, strings::*};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{
     layout::{Position, Rect},
-    prelude::CrosstermBackend,
+    prelude::{Backend, CrosstermBackend},
+    widgets::ListState,
     Terminal,
 };
 
-use crate::ui;
+use crate::{config::{Config, Theme}, keymap::KeyCommand, terminal::{self, Signals}, ui::{self, View}, widgets::{image::{self, ImageProtocol}, form::{FieldKind, Values}, text_input::TextInputState, textarea::TextAreaState}};
+
+mod action;
+mod focus;
+mod forms;
+mod model;
+mod undo;
+
+pub use action::Action;
+pub use focus::{Focus, FOCUS_KEYS, FOCUS_ONLY_KEYS};
+use model::{Model, Query, ViewedBoardgame};
+use undo::{credits_by_role, Command, Credits, Purged, UNDO_LIMIT};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Mode {
     Main,
     Adding,
-    // Editing,
-    // Deleting,
+    Details,
+    Filtering,
+    Voting,
+    CustomFields,
+    AddingImage,
+    Copies,
+    Audit,
+    HouseRules,
+    RuleSearch,
+    Trash,
+    History,
+    Editing,
     Quitting,
 }
 
+impl Mode {
+    pub const ALL: [Mode; 15] = [
+        Mode::Main,
+        Mode::Adding,
+        Mode::Details,
+        Mode::Filtering,
+        Mode::Voting,
+        Mode::CustomFields,
+        Mode::AddingImage,
+        Mode::Copies,
+        Mode::Audit,
+        Mode::HouseRules,
+        Mode::RuleSearch,
+        Mode::Trash,
+        Mode::History,
+        Mode::Editing,
+        Mode::Quitting,
+    ];
+
+    /// The name the screen goes by in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Main => "main",
+            Mode::Adding => "adding",
+            Mode::Details => "details",
+            Mode::Filtering => "filtering",
+            Mode::Voting => "voting",
+            Mode::CustomFields => "custom-fields",
+            Mode::AddingImage => "adding-image",
+            Mode::Copies => "copies",
+            Mode::Audit => "audit",
+            Mode::HouseRules => "house-rules",
+            Mode::RuleSearch => "rule-search",
+            Mode::Trash => "trash",
+            Mode::History => "history",
+            Mode::Editing => "editing",
+            Mode::Quitting => "quitting",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     pub modes: Vec<Mode>,
     pub state: AppState,
-    pub buttons: HashMap<Rect, fn(&mut App) -> ()>,
+    /// What the last frame drew: where its buttons and inputs are, and what the buttons do.
+    pub view: View,
     pub messages: RefCell<MessageQueue>,
     pub cursor: Option<Position>,
-    config: AppConfig,
+    pub image_protocol: ImageProtocol,
+    /// What is currently drawn on the terminal by the kitty protocol.
+    placed_image: Option<(i64, Rect)>,
+    thumbnails: HashMap<i64, Option<Rc<Thumbnail>>>,
+    model: Model,
+    /// Set when the model no longer matches the database or the state, to reload it before drawing.
+    model_stale: bool,
+    /// Why the model couldn't be loaded, until a retry works.
+    load_error: Option<String>,
+    db_events: Receiver<DbEvent>,
+    /// Commands reversing the actions taken, with a description of each action.
+    undo_stack: Vec<(String, Command)>,
+    redo_stack: Vec<(String, Command)>,
+    config: Config,
     db: BoardgameDb,
-    debug: bool,
 }
 
 #[derive(Debug)]
 pub struct AppState {
     pub should_quit: bool,
-    pub inputs: HashMap<Rect, String>,
+    /// Set to stop the process, as Ctrl-Z does in a shell, once the current event is handled.
+    pub should_suspend: bool,
     pub input_state: HashMap<String, String>,
+    /// Cursor and selection of each input that has been focused, by label.
+    pub text_inputs: HashMap<String, TextInputState>,
+    /// The same for inputs of several lines.
+    pub textareas: HashMap<String, TextAreaState>,
     pub selected_input: Option<String>,
+    /// The button with keyboard focus, by where it is drawn.
+    pub focused_button: Option<Rect>,
+    /// How far the help is scrolled while it is shown over the screen.
+    pub help: Option<u16>,
+    pub boardgame_list: ListState,
+    pub custom_field_list: ListState,
+    pub copy_list: ListState,
+    pub component_list: ListState,
+    pub rule_list: ListState,
+    pub trash_list: ListState,
+    pub history_list: ListState,
+    /// The copy being audited.
+    pub auditing: Option<i64>,
+    /// The boardgame being edited.
+    pub editing: Option<i64>,
+    pub viewing: Option<i64>,
+    /// Which of the viewed boardgame's images is shown.
+    pub image_index: usize,
+    pub filter: BoardgameFilter,
+    pub sort: BoardgameSort,
 }
 
+type MessageQueue = VecDeque<(String, Instant)>;
+
+/// A decoded thumbnail, kept with its PNG for protocols that take the file itself.
 #[derive(Debug)]
-struct AppConfig {
-    message_timeout: Duration,
+pub struct Thumbnail {
+    pub png: Vec<u8>,
+    pub image: ::image::DynamicImage,
 }
 
-type MessageQueue = VecDeque<(String, Instant)>;
-
 impl App {
-    pub fn new(db_path: &str) -> App {
+    /// Opens the configured database, failing if it can't be opened or migrated.
+    pub fn new(config: Config) -> Result<App, boardgame_core::errors::Error> {
         let state = AppState {
             should_quit: false,
-            inputs: HashMap::new(),
+            should_suspend: false,
             input_state: HashMap::new(),
+            text_inputs: HashMap::new(),
+            textareas: HashMap::new(),
             selected_input: None,
+            focused_button: None,
+            help: None,
+            boardgame_list: ListState::default(),
+            custom_field_list: ListState::default(),
+            copy_list: ListState::default(),
+            component_list: ListState::default(),
+            rule_list: ListState::default(),
+            trash_list: ListState::default(),
+            history_list: ListState::default(),
+            auditing: None,
+            editing: None,
+            viewing: None,
+            image_index: 0,
+            filter: BoardgameFilter::default(),
+            sort: config.sort,
         };
-        let config = AppConfig {
-            message_timeout: Duration::from_secs(3),
-        };
-        App {
+        let db = BoardgameDb::new(&config.database)?;
+        let app = App {
             state,
             config,
-            buttons: HashMap::new(),
-            db: BoardgameDb::new(db_path).expect("failed to create database"),
+            view: View::default(),
+            db_events: db.subscribe(),
+            db,
             modes: Vec::from([Mode::Main]),
-            debug: true,
             messages: RefCell::new(VecDeque::new()),
             cursor: None,
+            image_protocol: ImageProtocol::detect(),
+            placed_image: None,
+            thumbnails: HashMap::new(),
+            model: Model::default(),
+            model_stale: true,
+            load_error: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        };
+        // Changes are recorded in the history under the login name
+        if let Ok(actor) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+            if let Err(e) = app.db.set_actor(&actor) {
+                app.send_message(format!("Error setting history actor: {}", e));
+            }
+        }
+        app.purge_expired_trash();
+        Ok(app)
+    }
+
+    fn purge_expired_trash(&self) {
+        match self.db.purge_expired_trash(self.config.trash_retention_days) {
+            Ok(0) => {}
+            Ok(purged) => self.send_message(format!("Purged {} boardgames from the trash", purged)),
+            Err(e) => self.send_message(format!("Error purging trash: {}", e)),
         }
     }
 
@@ -78,44 +227,175 @@ impl App {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     ) -> Result<(), io::Error> {
+        let signals = Signals::register()?;
         while !self.state.should_quit {
-            terminal.draw(|frame| ui::render(frame, self))?;
-            self.check_message_timeout();
+            self.draw(terminal)?;
+            self.sync_image_placement(terminal)?;
             if event::poll(std::time::Duration::from_millis(30))? {
-                match event::read()? {
-                    Event::Key(key) if key.kind == event::KeyEventKind::Press => {
-                        self.on_key(key.code)
-                    }
-                    Event::Mouse(event) => {
-                        if event::MouseEventKind::Down(event::MouseButton::Left) == event.kind {
-                            self.on_mouse_click(event.column, event.row);
-                        } else if event.kind == event::MouseEventKind::Moved {
-                            self.update_cursor((event.column, event.row));
-                        }
+                self.handle_event(event::read()?);
+            }
+            if signals.take_quit() {
+                self.update(Action::Quit);
+            }
+            if signals.take_suspend() {
+                self.update(Action::Suspend);
+            }
+            if std::mem::take(&mut self.state.should_suspend) {
+                self.suspend(terminal)?;
+            }
+        }
+        if self.placed_image.is_some() {
+            image::kitty_clear(terminal.backend_mut())?;
+        }
+        Ok(())
+    }
+
+    /// Hands the terminal back to the shell until it resumes the app.
+    fn suspend(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<(), io::Error> {
+        if self.placed_image.take().is_some() {
+            image::kitty_clear(terminal.backend_mut())?;
+        }
+        terminal::suspend(terminal)
+    }
+
+    /// Catches up with the database and draws a frame of the current screen.
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<(), io::Error> {
+        self.check_db_events();
+        self.refresh();
+        let mut view = View::default();
+        terminal.draw(|frame| view = ui::render(frame, self))?;
+        self.apply_view(view);
+        self.check_message_timeout();
+        Ok(())
+    }
+
+    /// Turns a terminal event into what it does.
+    pub fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == event::KeyEventKind::Press => self.on_key(key),
+            Event::Paste(text) => self.update(Action::Paste(text)),
+            Event::Mouse(event) => {
+                let position = Position::new(event.column, event.row);
+                if event::MouseEventKind::Down(event::MouseButton::Left) == event.kind {
+                    self.on_mouse_click(position);
+                } else if event.kind == event::MouseEventKind::Moved {
+                    self.update(Action::Hover(position));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks the model stale if this or another process changed the database.
+    fn check_db_events(&mut self) {
+        if let Err(e) = self.db.check_external_changes() {
+            self.send_message(format!("Error checking for changes: {}", e));
+        }
+        // Nearly every table shows up somewhere, so any change means reloading
+        if self.db_events.try_iter().count() > 0 {
+            self.model_stale = true;
+        }
+    }
+
+    /// Reloads the model if it is stale. A failure is reported once and the last loaded data
+    /// stays on screen until `retry_load`.
+    fn refresh(&mut self) {
+        if !self.model_stale {
+            return;
+        }
+        self.model_stale = false;
+        let query = Query {
+            filter: &self.state.filter,
+            sort: self.state.sort,
+            viewing: self.state.viewing,
+            rule_search: self.state.input_state.get(RULE_SEARCH).map_or("", String::as_str),
+        };
+        match Model::load(&self.db, &query) {
+            Ok(model) => {
+                self.model = model;
+                self.load_error = None;
+                self.load_thumbnails();
+            }
+            Err(e) => {
+                let error = format!("Couldn't load data: {}", e);
+                match self.get_curr_mode().and_then(|mode| self.key_for(mode, KeyCommand::Reload)) {
+                    Some(key) => self.send_message(format!("{} ({} to retry)", error, key)),
+                    None => self.send_message(error.clone()),
+                }
+                self.load_error = Some(error);
+            }
+        }
+    }
+
+    fn retry_load(&mut self) {
+        self.model_stale = true;
+    }
+
+    pub fn get_load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Decodes the viewed boardgame's thumbnails that aren't in memory yet.
+    fn load_thumbnails(&mut self) {
+        let images = self.model.viewed.iter().flat_map(|viewed| &viewed.images);
+        let missing: Vec<i64> = images.map(|image| image.id).filter(|id| !self.thumbnails.contains_key(id)).collect();
+        for image_id in missing {
+            let thumbnail = match self.db.get_thumbnail(image_id) {
+                Ok(Some(png)) => match ::image::load_from_memory(&png) {
+                    Ok(image) => Some(Rc::new(Thumbnail { png, image })),
+                    Err(e) => {
+                        self.send_message(format!("Error decoding thumbnail: {}", e));
+                        None
                     }
-                    _ => {}
+                },
+                Ok(None) => None,
+                Err(e) => {
+                    self.send_message(format!("Error getting thumbnail: {}", e));
+                    None
                 }
+            };
+            self.thumbnails.insert(image_id, thumbnail);
+        }
+    }
+
+    /// Draws, moves or removes the kitty image to match what the last frame asked for.
+    fn sync_image_placement(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<(), io::Error> {
+        if self.view.image_placement == self.placed_image {
+            return Ok(());
+        }
+        if self.placed_image.is_some() {
+            image::kitty_clear(terminal.backend_mut())?;
+        }
+        if let Some((image_id, area)) = self.view.image_placement {
+            if let Some(thumbnail) = self.get_thumbnail(image_id) {
+                image::kitty_display(terminal.backend_mut(), image_id, &thumbnail.png, area)?;
             }
         }
+        self.placed_image = self.view.image_placement;
         Ok(())
     }
 
     fn clear_state(&mut self) {
-        self.buttons.clear();
-        self.state.inputs.clear();
+        self.view = View::default();
         self.state.input_state.clear();
+        self.state.text_inputs.clear();
+        self.state.textareas.clear();
         self.state.selected_input = None;
+        self.state.focused_button = None;
     }
 
+    /// Switching screens can change what is viewed or searched, so the model is reloaded.
     pub fn switch_mode(&mut self, mode: Mode) {
         self.modes.push(mode);
         self.clear_state();
+        self.model_stale = true;
     }
 
     pub fn prev_mode(&mut self) {
         if self.modes.len() > 1 {
             self.modes.pop();
             self.clear_state();
+            self.model_stale = true;
         }
     }
 
@@ -131,70 +411,96 @@ impl App {
         self.modes.last().copied()
     }
 
-    pub fn on_key(&mut self, key: KeyCode) {
-        if let Some(input) = &self.state.selected_input {
-            if !self.state.input_state.contains_key(input) {
-                self.state.input_state.insert(input.clone(), String::new());
+    /// Carries out what the key does on the current screen.
+    pub fn on_key(&mut self, key: KeyEvent) {
+        match self.key_action(key) {
+            Ok(action) => self.update(action),
+            Err(message) => self.send_message(message),
+        }
+    }
+
+    /// Applies a key to the focused input with the widget for its kind of field.
+    fn edit_input(&mut self, key: KeyEvent) {
+        let Some(input) = self.state.selected_input.clone() else {
+            return;
+        };
+        let kind = self.form().kind(&input).cloned().unwrap_or(FieldKind::Text);
+        let value = self.state.input_state.entry(input.clone()).or_default();
+        let handled = match kind {
+            _ if key.code == KeyCode::Enter && !kind.uses_enter() => {
+                self.state.selected_input = None;
+                true
             }
-            let mut input_state = self.state.input_state.get_mut(input).expect("how is this not present?");
-            match key {
-                KeyCode::Enter => self.state.selected_input = None,
-                KeyCode::Tab => {},
-                KeyCode::Backspace => input_state.pop().map_or((), |_| ()),
-                KeyCode::Char(ch) => input_state.push(ch),
-                key => self.send_message(format!("Unhandled key: {:?}", key))
+            FieldKind::Text => {
+                let text_input = self.state.text_inputs.entry(input).or_insert_with(|| TextInputState::at_end(value));
+                text_input.handle_key(value, key)
             }
-        } else {
-            match key {
-                KeyCode::Char('q') => self.go_to_quit(),
-                KeyCode::Backspace => self.prev_mode(),
-                KeyCode::Char('d') if self.debug => self.send_debug_message(),
-                key => {
-                    self.send_message(format!("Unhandled key: {:?}", key));
-                }
+            FieldKind::Multiline => {
+                let textarea = self.state.textareas.entry(input).or_insert_with(|| TextAreaState::at_end(value));
+                textarea.handle_key(value, key)
             }
+            kind => kind.handle_key(value, key),
+        };
+        if !handled {
+            self.send_message(format!("Unhandled key: {:?}", key.code));
+        }
+        if self.get_curr_mode() == Some(Mode::RuleSearch) {
+            self.model_stale = true;
         }
     }
 
-    pub fn on_mouse_click(&mut self, x: u16, y: u16) {
-        let mut key = None;
-        for (area, k) in &self.state.inputs {
-            if area.contains((x, y).into()) {
-                key = Some(k);
+    /// Pastes into the selected input, the terminal sends pastes whole rather than as keys.
+    fn paste(&mut self, text: &str) {
+        if let Some(input) = self.state.selected_input.clone() {
+            let kind = self.form().kind(&input).cloned().unwrap_or(FieldKind::Text);
+            let value = self.state.input_state.entry(input.clone()).or_default();
+            match kind {
+                FieldKind::Text => {
+                    let text_input = self.state.text_inputs.entry(input).or_insert_with(|| TextInputState::at_end(value));
+                    text_input.paste(value, text);
+                }
+                FieldKind::Multiline => {
+                    let textarea = self.state.textareas.entry(input).or_insert_with(|| TextAreaState::at_end(value));
+                    textarea.paste(value, text);
+                }
+                kind => kind.paste(value, text),
             }
-        }
-        if let Some(key) = key {
-            self.state.selected_input = Some(key.to_owned());
-            return;
-        } else {
-            self.state.selected_input = None;
-        }
-        let mut func: Option<fn(&mut App) -> ()> = None;
-        for (area, f) in &self.buttons {
-            if area.contains((x, y).into()) {
-                func = Some(*f);
-                break;
+            if self.get_curr_mode() == Some(Mode::RuleSearch) {
+                self.model_stale = true;
             }
         }
-        if let Some(f) = func {
-            f(self);
-        }
     }
 
-    pub fn add_button(&mut self, area: Rect, func: fn(&mut App) -> ()) {
-        self.buttons.insert(area, func);
+    /// Clicking an input focuses it. Clicking anywhere else drops focus, then presses the
+    /// button there if there is one.
+    pub fn on_mouse_click(&mut self, position: Position) {
+        let action = self.view.hit_test(position);
+        if !matches!(action, Some(Action::SetFocus(_))) {
+            self.set_focus(None);
+        }
+        if let Some(action) = action {
+            self.update(action);
+        }
     }
 
-    pub fn add_input(&mut self, area: Rect, key: &str) {
-        self.state.inputs.insert(area, key.to_string());
-        // self.state.input_state.insert(key.to_string(), String::new());
+    /// Keeps what the last frame drew, and where its widgets scrolled to for the next one.
+    fn apply_view(&mut self, mut view: View) {
+        self.state.text_inputs.extend(view.text_inputs.drain());
+        self.state.textareas.extend(view.textareas.drain());
+        if let (Some(list), Some(current)) = (view.list.take(), self.current_list_mut()) {
+            *current = list;
+        }
+        if let (Some(scroll), Some(current)) = (view.help.take(), &mut self.state.help) {
+            *current = scroll;
+        }
+        self.view = view;
     }
 
     fn send_message(&self, msg: String) {
         self.messages.borrow_mut().push_back((msg, Instant::now()));
     }
 
-    pub fn get_messages(&self) -> Ref<MessageQueue> {
+    pub fn get_messages(&self) -> Ref<'_, MessageQueue> {
         self.messages.borrow()
     }
 
@@ -214,37 +520,22 @@ impl App {
         }
     }
 
-    fn update_cursor(&mut self, (x, y): (u16, u16)) {
-        self.cursor = Some(Position::new(x, y))
-    }
-
     fn send_debug_message(&mut self) {
         self.send_message(format!("previous_mode: {:?}", self.get_prev_mode()));
         self.send_message(format!("input state: {:?}", self.state.input_state));
-        self.send_message(format!("inputs: {:?}", self.state.inputs));
+        self.send_message(format!("inputs: {:?}", self.view.inputs));
     }
 
-    pub fn add_new_boardgame(&mut self) {
-        let name = self.state.input_state.get(BG_NAME).expect(&format!("'{}' not in input_state", BG_NAME)).to_owned();
-        let description = self.state.input_state.get(BG_DESCRIPTION).expect(&format!("'{}' not in input_state", BG_DESCRIPTION)).to_owned();
-        let mut numbers = [0, 0, 0];
-        for (field, pos) in [(BG_MIN_PLAYERS, 0), (BG_MAX_PLAYERS, 1), (BG_PLAY_TIME, 2)] {
-            match self.state.input_state.get(field).expect(&format!("'{}' not in input_state", field)).parse::<i32>() {
-                Err(e) => {
-                    self.send_message(format!("Bad value for '{}': {}", field, e));
-                    return;
-                },
-                Ok(v) => numbers[pos] = v
+    fn add_new_boardgame(&mut self) {
+        let (boardgame, credits) = match self.boardgame_input() {
+            Ok(input) => input,
+            Err(e) => {
+                self.send_message(e);
+                return;
             }
-        }
-        match self.db.create_boardgame(&Boardgame {
-            id: None,
-            name,
-            min_players: numbers[0],
-            max_players: numbers[1],
-            play_time_minutes: numbers[2],
-            description
-        }) {
+        };
+        let action = format!("add {}", boardgame.name);
+        match self.execute(action, Command::CreateBoardgame { boardgame, credits }) {
             Ok(_) => {
                 self.switch_mode(Mode::Main);
                 self.send_message("Successfully added new boardgame!".to_string())
@@ -253,26 +544,746 @@ impl App {
         }
     }
 
-    pub fn go_to_quit(&mut self) {
-        self.switch_mode(Mode::Quitting);
+    /// Opens the boardgame form filled in with a boardgame's details, to change them.
+    fn edit_boardgame(&mut self, id: i64) {
+        let Some(boardgame) = self.boardgame(id) else {
+            return;
+        };
+        let credits = match self.db.get_credits(id) {
+            Ok(credits) => credits_by_role(&credits),
+            Err(e) => {
+                self.send_message(format!("Error getting credits: {}", e));
+                return;
+            }
+        };
+        self.state.editing = Some(id);
+        self.switch_mode(Mode::Editing);
+
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mechanics: Vec<&str> = boardgame.mechanics.iter().map(Mechanic::label).collect();
+        let mut inputs = vec![
+            (BG_NAME.to_string(), boardgame.name.clone()),
+            (BG_MIN_PLAYERS.to_string(), boardgame.min_players.to_string()),
+            (BG_MAX_PLAYERS.to_string(), boardgame.max_players.to_string()),
+            (BG_PLAY_TIME.to_string(), boardgame.play_time_minutes.to_string()),
+            (BG_WEIGHT.to_string(), optional(boardgame.weight.map(|weight| weight.to_string()))),
+            (BG_MIN_AGE.to_string(), optional(boardgame.min_age.map(|age| age.to_string()))),
+            (BG_YEAR_PUBLISHED.to_string(), optional(boardgame.year_published.map(|year| year.to_string()))),
+            (BG_MECHANICS.to_string(), mechanics.join(", ")),
+            (BG_DESCRIPTION.to_string(), boardgame.description.clone()),
+        ];
+        for ((_, names), field) in credits.iter().zip(BG_CREDIT_FIELDS) {
+            inputs.push((field.to_string(), names.join(", ")));
+        }
+        for field in self.get_custom_fields() {
+            if let Some(value) = boardgame.custom_values.get(&field.id) {
+                inputs.push((field.name, value.to_string()));
+            }
+        }
+        self.state.input_state.extend(inputs.into_iter().filter(|(_, value)| !value.is_empty()));
     }
 
-    pub fn go_to_add_new(&mut self) {
-        self.switch_mode(Mode::Adding);
+    fn save_boardgame(&mut self, id: i64) {
+        let (boardgame, credits) = match self.boardgame_input() {
+            Ok((boardgame, credits)) => (Boardgame { id: Some(id), ..boardgame }, credits),
+            Err(e) => {
+                self.send_message(e);
+                return;
+            }
+        };
+        let name = boardgame.name.clone();
+        match self.execute(format!("edit {}", name), Command::UpdateBoardgame { boardgame, credits }) {
+            Ok(_) => {
+                self.prev_mode();
+                self.send_message(format!("Saved {}", name));
+            }
+            Err(e) => self.send_message(format!("Error saving boardgame: {}", e)),
+        }
     }
 
-    pub fn quit(&mut self) {
-        self.state.should_quit = true;
+    /// The boardgame and its credits as the boardgame form holds them.
+    fn boardgame_input(&self) -> Result<(Boardgame, Credits), String> {
+        let form = self.form();
+        let values = form.values(&self.state.input_state);
+        let boardgame = Boardgame {
+            id: None,
+            name: values.text(BG_NAME),
+            min_players: values.required(BG_MIN_PLAYERS, Values::number)?,
+            max_players: values.required(BG_MAX_PLAYERS, Values::number)?,
+            play_time_minutes: values.required(BG_PLAY_TIME, Values::number)?,
+            description: values.text(BG_DESCRIPTION),
+            weight: values.decimal(BG_WEIGHT)?,
+            min_age: values.number(BG_MIN_AGE)?,
+            year_published: values.number(BG_YEAR_PUBLISHED)?,
+            mechanics: mechanics_input(&values, BG_MECHANICS)?,
+            custom_values: self.custom_values_input(&values)?.into_iter().collect(),
+            deleted_at: None,
+        };
+        let credits = CreditRole::ALL
+            .into_iter()
+            .zip(BG_CREDIT_FIELDS)
+            .map(|(role, field)| (role, split_names(&values.text(field))))
+            .collect();
+        Ok((boardgame, credits))
     }
 
-    pub fn get_boardgames(&self) -> Vec<Boardgame> {
-        let result = self.db.get_all_boardgames();
-        match result {
-            Ok(boardgames) => boardgames,
+    /// Reads the fields for every custom field that has been filled in.
+    fn custom_values_input(&self, values: &Values) -> Result<Vec<(i64, CustomValue)>, String> {
+        let mut custom_values = Vec::new();
+        for field in self.get_custom_fields() {
+            let value = match field.field_type {
+                CustomFieldType::Number => values.decimal(&field.name)?.map(CustomValue::Number),
+                _ => values.parse::<String>(&field.name)?.map(|text| field.parse_value(&text)).transpose().map_err(|e| e.to_string())?,
+            };
+            custom_values.extend(value.map(|value| (field.id, value)));
+        }
+        Ok(custom_values)
+    }
+
+    /// Applies the command and remembers how to undo it.
+    fn execute(&mut self, description: String, command: Command) -> Result<(), boardgame_core::errors::Error> {
+        let undo = command.apply(&self.db)?;
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push((description, undo));
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    pub fn undo(&mut self) {
+        let Some((description, command)) = self.undo_stack.pop() else {
+            self.send_message("Nothing to undo".to_string());
+            return;
+        };
+        match command.apply(&self.db) {
+            Ok(redo) => {
+                self.send_message(format!("Undid: {}", description));
+                self.redo_stack.push((description, redo));
+            }
+            Err(e) => self.send_message(format!("Couldn't undo {}: {}", description, e)),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        let Some((description, command)) = self.redo_stack.pop() else {
+            self.send_message("Nothing to redo".to_string());
+            return;
+        };
+        match command.apply(&self.db) {
+            Ok(undo) => {
+                self.send_message(format!("Redid: {}", description));
+                self.undo_stack.push((description, undo));
+            }
+            Err(e) => self.send_message(format!("Couldn't redo {}: {}", description, e)),
+        }
+    }
+
+    /// Switches to a screen, with nothing selected in its list.
+    fn go_to(&mut self, mode: Mode) {
+        self.switch_mode(mode);
+        if let Some(list) = self.current_list_mut() {
+            list.select(None);
+        }
+        match mode {
+            Mode::Filtering => self.fill_filter_inputs(),
+            Mode::RuleSearch => self.state.selected_input = Some(RULE_SEARCH.to_string()),
+            _ => {}
+        }
+    }
+
+    /// The list on the current screen, which Up and Down move through.
+    fn current_list(&self) -> Option<&ListState> {
+        let state = &self.state;
+        match self.get_curr_mode()? {
+            Mode::Main => Some(&state.boardgame_list),
+            Mode::CustomFields => Some(&state.custom_field_list),
+            Mode::Copies => Some(&state.copy_list),
+            Mode::Audit => Some(&state.component_list),
+            Mode::HouseRules | Mode::RuleSearch => Some(&state.rule_list),
+            Mode::Trash => Some(&state.trash_list),
+            Mode::History => Some(&state.history_list),
+            _ => None,
+        }
+    }
+
+    fn current_list_mut(&mut self) -> Option<&mut ListState> {
+        let state = &mut self.state;
+        match self.modes.last()? {
+            Mode::Main => Some(&mut state.boardgame_list),
+            Mode::CustomFields => Some(&mut state.custom_field_list),
+            Mode::Copies => Some(&mut state.copy_list),
+            Mode::Audit => Some(&mut state.component_list),
+            Mode::HouseRules | Mode::RuleSearch => Some(&mut state.rule_list),
+            Mode::Trash => Some(&mut state.trash_list),
+            Mode::History => Some(&mut state.history_list),
+            _ => None,
+        }
+    }
+
+    fn move_selection(&mut self, steps: isize) {
+        if self.get_curr_mode() == Some(Mode::Audit) {
+            return self.select_component(steps);
+        }
+        if let Some(list) = self.current_list_mut() {
+            move_list_selection(list, steps);
+        }
+    }
+
+    fn cycle_sort(&mut self) {
+        let next = BoardgameSort::ALL.iter().position(|sort| *sort == self.state.sort).map_or(0, |i| i + 1);
+        self.state.sort = BoardgameSort::ALL[next % BoardgameSort::ALL.len()];
+        self.state.boardgame_list.select(None);
+        self.model_stale = true;
+    }
+
+    fn view_boardgame(&mut self, id: i64) {
+        self.state.viewing = Some(id);
+        self.state.image_index = 0;
+        self.switch_mode(Mode::Details);
+    }
+
+    /// A boardgame in the list or the one viewed.
+    fn boardgame(&self, id: i64) -> Option<Boardgame> {
+        let viewed = self.viewed().map(|viewed| &viewed.boardgame);
+        self.model.boardgames.iter().chain(viewed).find(|boardgame| boardgame.id == Some(id)).cloned()
+    }
+
+    pub fn selected_boardgame(&self) -> Option<Boardgame> {
+        self.state.boardgame_list.selected()
+            .and_then(|i| self.get_boardgames().into_iter().nth(i))
+    }
+
+    /// Moves a boardgame to the trash, leaving its details if they are open.
+    fn trash_boardgame(&mut self, id: i64) {
+        let Some(boardgame) = self.boardgame(id) else {
+            return;
+        };
+        match self.execute(format!("delete {}", boardgame.name), Command::DeleteBoardgame(id)) {
+            Ok(_) => {
+                self.send_message(format!("Moved {} to the trash", boardgame.name));
+                if self.get_curr_mode() == Some(Mode::Details) && self.state.viewing == Some(id) {
+                    self.prev_mode();
+                }
+            }
+            Err(e) => self.send_message(format!("Error deleting boardgame: {}", e)),
+        }
+    }
+
+    pub fn selected_trashed_boardgame(&self) -> Option<Boardgame> {
+        self.state.trash_list.selected()
+            .and_then(|i| self.get_trashed_boardgames().into_iter().nth(i))
+    }
+
+    fn trashed_boardgame(&self, id: i64) -> Option<Boardgame> {
+        self.model.trash.iter().find(|boardgame| boardgame.id == Some(id)).cloned()
+    }
+
+    fn restore_boardgame(&mut self, id: i64) {
+        if let Some(boardgame) = self.trashed_boardgame(id) {
+            let command = Command::RestoreBoardgame(id);
+            match self.execute(format!("restore {}", boardgame.name), command) {
+                Ok(_) => self.send_message(format!("Restored {}", boardgame.name)),
+                Err(e) => self.send_message(format!("Error restoring boardgame: {}", e)),
+            }
+        }
+    }
+
+    fn purge_boardgame(&mut self, id: i64) {
+        if let Some(boardgame) = self.trashed_boardgame(id) {
+            let result = Purged::load(&self.db, id).and_then(|purged| Ok((self.db.purge_boardgame(id)?, purged)));
+            match result {
+                Ok((_, purged)) => {
+                    self.forget(&purged);
+                    self.send_message(format!("Permanently deleted {}, this can't be undone", boardgame.name));
+                }
+                Err(e) => self.send_message(format!("Error purging boardgame: {}", e)),
+            }
+        }
+    }
+
+    /// Drops what can no longer be undone or redone now that a boardgame is gone for good.
+    fn forget(&mut self, purged: &Purged) {
+        for stack in [&mut self.undo_stack, &mut self.redo_stack] {
+            *stack = std::mem::take(stack)
+                .into_iter()
+                .filter_map(|(description, command)| Some((description, command.without(purged)?)))
+                .collect();
+        }
+    }
+
+    /// Fills the filter form in from the filter in use.
+    fn fill_filter_inputs(&mut self) {
+        let filter = &self.state.filter;
+        let mechanics = filter.mechanics.iter().map(Mechanic::label).collect::<Vec<_>>().join(", ");
+        for (field, value) in [
+            (FILTER_NAME, filter.name.clone()),
+            (FILTER_CREDIT, filter.credit.clone()),
+            (FILTER_MIN_WEIGHT, filter.min_weight.map(|w| w.to_string())),
+            (FILTER_MAX_WEIGHT, filter.max_weight.map(|w| w.to_string())),
+            (FILTER_AGE, filter.age.map(|a| a.to_string())),
+            (FILTER_MECHANICS, Some(mechanics).filter(|m| !m.is_empty())),
+            (FILTER_PLAYERS, filter.players.map(|p| p.to_string())),
+            (FILTER_PLAYER_COUNT_MATCH, Some(filter.player_count_match.to_string())),
+        ] {
+            if let Some(value) = value {
+                self.state.input_state.insert(field.to_string(), value);
+            }
+        }
+        let custom_fields = self.get_custom_fields();
+        for (field_id, value) in self.state.filter.custom_values.clone() {
+            if let Some(field) = custom_fields.iter().find(|field| field.id == field_id) {
+                self.state.input_state.insert(field.name.clone(), value.to_string());
+            }
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        match self.filter_input() {
+            Ok(filter) => {
+                self.state.filter = filter;
+                self.model_stale = true;
+                self.state.boardgame_list.select(None);
+                self.prev_mode();
+            }
+            Err(e) => self.send_message(e),
+        }
+    }
+
+    /// The filter as the filter form holds it.
+    fn filter_input(&self) -> Result<BoardgameFilter, String> {
+        let form = self.form();
+        let values = form.values(&self.state.input_state);
+        Ok(BoardgameFilter {
+            name: values.parse(FILTER_NAME)?,
+            credit: values.parse(FILTER_CREDIT)?,
+            min_weight: values.decimal(FILTER_MIN_WEIGHT)?,
+            max_weight: values.decimal(FILTER_MAX_WEIGHT)?,
+            age: values.number(FILTER_AGE)?,
+            mechanics: mechanics_input(&values, FILTER_MECHANICS)?,
+            players: values.number(FILTER_PLAYERS)?,
+            player_count_match: values.parse(FILTER_PLAYER_COUNT_MATCH)?.unwrap_or_default(),
+            custom_values: self.custom_values_input(&values)?,
+        })
+    }
+
+    fn clear_filter(&mut self) {
+        self.state.filter = BoardgameFilter::default();
+        self.model_stale = true;
+        self.state.boardgame_list.select(None);
+        self.prev_mode();
+    }
+
+    fn cast_vote(&mut self, vote: PlayerCountVote) {
+        let Some(id) = self.state.viewing else {
+            return;
+        };
+        let form = self.form();
+        let values = form.values(&self.state.input_state);
+        let voter = values.text(VOTE_VOTER);
+        let player_count = match values.required(VOTE_PLAYER_COUNT, Values::number) {
+            Ok(player_count) => player_count,
+            Err(e) => {
+                self.send_message(e);
+                return;
+            }
+        };
+        let command = Command::Vote { boardgame_id: id, player_count, voter, vote: Some(vote) };
+        match self.execute(format!("vote at {} players", player_count), command) {
+            Ok(()) => {
+                self.prev_mode();
+                self.send_message(format!("Voted {} at {} players", vote.label().to_lowercase(), player_count));
+            }
+            Err(e) => self.send_message(format!("Error voting: {}", e)),
+        }
+    }
+
+    fn add_custom_field(&mut self) {
+        let name = self.state.input_state.get(CUSTOM_FIELD_NAME).cloned().unwrap_or_default();
+        let field_type = self.state.input_state.get(CUSTOM_FIELD_TYPE)
+            .map_or(Ok(CustomFieldType::Text), |kind| kind.parse());
+        let command = field_type.map(|field_type| Command::CreateCustomField { name: name.clone(), field_type });
+        match command.and_then(|command| self.execute(format!("add field '{}'", name.trim()), command)) {
+            Ok(_) => {
+                self.state.input_state.clear();
+                self.state.selected_input = None;
+                self.send_message(format!("Added field '{}'", name.trim()));
+            }
+            Err(e) => self.send_message(format!("Error adding field: {}", e)),
+        }
+    }
+
+    pub fn selected_custom_field(&self) -> Option<CustomField> {
+        self.state.custom_field_list.selected()
+            .and_then(|i| self.get_custom_fields().into_iter().nth(i))
+    }
+
+    fn delete_custom_field(&mut self, id: i64) {
+        let field = self.model.custom_fields.iter().find(|field| field.id == id).cloned();
+        if let Some(field) = field {
+            match self.execute(format!("delete field '{}'", field.name), Command::DeleteCustomField(field.id)) {
+                Ok(_) => {
+                    self.state.filter.custom_values.retain(|(field_id, _)| *field_id != field.id);
+                    self.send_message(format!("Deleted field '{}'", field.name));
+                }
+                Err(e) => self.send_message(format!("Error deleting field: {}", e)),
+            }
+        }
+    }
+
+    /// Moves through the viewed boardgame's images, stopping at either end.
+    fn show_image(&mut self, steps: isize) {
+        let last = self.viewed().map_or(0, |viewed| viewed.images.len().saturating_sub(1));
+        self.state.image_index = self.state.image_index.saturating_add_signed(steps).min(last);
+    }
+
+    fn add_image(&mut self, kind: ImageKind) {
+        let Some(id) = self.state.viewing else {
+            return;
+        };
+        let path = self.state.input_state.get(IMAGE_PATH).map(|p| p.trim().to_string()).unwrap_or_default();
+        if path.is_empty() {
+            self.send_message(format!("'{}' is required", IMAGE_PATH));
+            return;
+        }
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
             Err(e) => {
-                self.send_message(format!("Error getting boardgames: {}", e));
-                Vec::new()
+                self.send_message(format!("Error adding image: couldn't read {}: {}", path, e));
+                return;
+            }
+        };
+        match self.execute(format!("add {}", kind), Command::AddImage { boardgame_id: id, kind, data }) {
+            Ok(_) => {
+                self.prev_mode();
+                self.send_message(format!("Added {}", kind));
+            }
+            Err(e) => self.send_message(format!("Error adding image: {}", e)),
+        }
+    }
+
+    fn add_copy(&mut self) {
+        let Some(boardgame_id) = self.state.viewing else {
+            return;
+        };
+        let form = self.form();
+        let values = form.values(&self.state.input_state);
+        let publisher_year = match values.number(COPY_PUBLISHER_YEAR) {
+            Ok(year) => year,
+            Err(e) => {
+                self.send_message(e);
+                return;
+            }
+        };
+        let copy = GameCopy {
+            boardgame_id,
+            edition: values.text(COPY_EDITION),
+            language: values.text(COPY_LANGUAGE),
+            publisher_year,
+            ..Default::default()
+        };
+        match self.execute(format!("add copy {}", copy.label()), Command::CreateCopy(copy.clone())) {
+            Ok(_) => {
+                self.state.input_state.clear();
+                self.state.selected_input = None;
+                self.send_message(format!("Added copy: {}", copy.label()));
+            }
+            Err(e) => self.send_message(format!("Error adding copy: {}", e)),
+        }
+    }
+
+    pub fn selected_copy(&self) -> Option<GameCopy> {
+        self.state.copy_list.selected()
+            .and_then(|i| self.get_copies().into_iter().nth(i))
+    }
+
+    fn copy(&self, id: i64) -> Option<GameCopy> {
+        self.get_copies().into_iter().find(|copy| copy.id == Some(id))
+    }
+
+    fn delete_copy(&mut self, id: i64) {
+        if let Some(copy) = self.copy(id) {
+            let command = Command::DeleteCopy(id);
+            match self.execute(format!("delete copy {}", copy.label()), command) {
+                Ok(_) => self.send_message(format!("Deleted copy: {}", copy.label())),
+                Err(e) => self.send_message(format!("Error deleting copy: {}", e)),
+            }
+        }
+    }
+
+    fn audit_copy(&mut self, id: i64) {
+        self.state.auditing = Some(id);
+        self.state.component_list.select(None);
+        self.switch_mode(Mode::Audit);
+    }
+
+    pub fn selected_component(&self) -> Option<Component> {
+        let copy_id = self.state.auditing?;
+        self.state.component_list.selected()
+            .and_then(|i| self.get_components(copy_id).into_iter().nth(i))
+    }
+
+    /// One of the audited copy's components.
+    fn component(&self, id: i64) -> Option<Component> {
+        let copy_id = self.state.auditing?;
+        self.get_components(copy_id).into_iter().find(|component| component.id == Some(id))
+    }
+
+    /// Moves the selection and loads the newly selected component's notes for editing.
+    fn select_component(&mut self, steps: isize) {
+        move_list_selection(&mut self.state.component_list, steps);
+        let notes = self.selected_component().map(|c| c.notes).unwrap_or_default();
+        self.state.text_inputs.insert(COMPONENT_NOTES.to_string(), TextInputState::at_end(&notes));
+        self.state.input_state.insert(COMPONENT_NOTES.to_string(), notes);
+    }
+
+    /// Changes how many of the component are in the box.
+    fn adjust_component(&mut self, id: i64, delta: i32) {
+        if let Some(mut component) = self.component(id) {
+            component.present = (component.present + delta).max(0);
+            let description = format!("count {} {}/{}", component.name, component.present, component.expected);
+            if let Err(e) = self.execute(description, Command::UpdateComponent(component)) {
+                self.send_message(format!("Error updating component: {}", e));
+            }
+        }
+    }
+
+    fn add_component(&mut self) {
+        let Some(copy_id) = self.state.auditing else {
+            return;
+        };
+        let form = self.form();
+        let values = form.values(&self.state.input_state);
+        let expected = match values.number(COMPONENT_EXPECTED) {
+            Ok(expected) => expected.unwrap_or(1),
+            Err(e) => {
+                self.send_message(e);
+                return;
+            }
+        };
+        let component = Component {
+            copy_id,
+            name: values.text(COMPONENT_NAME),
+            expected,
+            present: expected,
+            notes: values.text(COMPONENT_NOTES),
+            ..Default::default()
+        };
+        match self.execute(format!("add {}", component.name.trim()), Command::AddComponent(component.clone())) {
+            Ok(_) => {
+                self.state.input_state.clear();
+                self.state.selected_input = None;
+                self.send_message(format!("Added {}", component.name.trim()));
+            }
+            Err(e) => self.send_message(format!("Error adding component: {}", e)),
+        }
+    }
+
+    fn save_component_notes(&mut self, id: i64) {
+        let Some(mut component) = self.component(id) else {
+            return;
+        };
+        component.notes = self.state.input_state.get(COMPONENT_NOTES).cloned().unwrap_or_default();
+        let description = format!("save notes for {}", component.name);
+        match self.execute(description, Command::UpdateComponent(component.clone())) {
+            Ok(_) => self.send_message(format!("Saved notes for {}", component.name)),
+            Err(e) => self.send_message(format!("Error saving notes: {}", e)),
+        }
+    }
+
+    fn delete_component(&mut self, id: i64) {
+        if let Some(component) = self.component(id) {
+            let command = Command::DeleteComponent(id);
+            match self.execute(format!("delete {}", component.name), command) {
+                Ok(_) => self.send_message(format!("Deleted {}", component.name)),
+                Err(e) => self.send_message(format!("Error deleting component: {}", e)),
             }
         }
     }
+
+    fn finish_audit(&mut self) {
+        let Some(copy_id) = self.state.auditing else {
+            return;
+        };
+        let missing = self
+            .execute("finish audit".to_string(), Command::CompleteAudit(copy_id))
+            .and_then(|_| self.db.get_components(copy_id))
+            .map(|components| components.into_iter().filter(|c| !c.is_complete()).collect::<Vec<_>>());
+        match missing {
+            Ok(missing) if missing.is_empty() => {
+                self.prev_mode();
+                self.send_message("Audit complete, nothing missing!".to_string());
+            }
+            Ok(missing) => {
+                self.prev_mode();
+                let summary: Vec<String> = missing.iter().map(|c| format!("{} x{}", c.name, c.missing())).collect();
+                self.send_message(format!("Audit complete, missing: {}", summary.join(", ")));
+            }
+            Err(e) => self.send_message(format!("Error completing audit: {}", e)),
+        }
+    }
+
+    fn add_house_rule(&mut self) {
+        let Some(boardgame_id) = self.state.viewing else {
+            return;
+        };
+        let input = |field: &str| self.state.input_state.get(field).cloned().unwrap_or_default();
+        let rule = HouseRule {
+            boardgame_id,
+            title: input(RULE_TITLE),
+            author: input(RULE_AUTHOR),
+            body: input(RULE_BODY),
+            active: true,
+            ..Default::default()
+        };
+        match self.execute(format!("add house rule '{}'", rule.title.trim()), Command::CreateHouseRule(rule.clone())) {
+            Ok(_) => {
+                self.state.input_state.clear();
+                self.state.selected_input = None;
+                self.send_message(format!("Added house rule '{}'", rule.title.trim()));
+            }
+            Err(e) => self.send_message(format!("Error adding house rule: {}", e)),
+        }
+    }
+
+    pub fn selected_house_rule(&self) -> Option<HouseRule> {
+        self.state.rule_list.selected()
+            .and_then(|i| self.get_house_rules().into_iter().nth(i))
+    }
+
+    fn house_rule(&self, id: i64) -> Option<HouseRule> {
+        self.get_house_rules().into_iter().find(|rule| rule.id == Some(id))
+    }
+
+    /// The house rule search result selected, with the name of its game.
+    pub fn selected_rule_search_result(&self) -> Option<(HouseRule, String)> {
+        self.state.rule_list.selected()
+            .and_then(|i| self.search_house_rules().into_iter().nth(i))
+    }
+
+    fn toggle_house_rule(&mut self, id: i64) {
+        if let Some(mut rule) = self.house_rule(id) {
+            rule.active = !rule.active;
+            let description = format!("{} '{}'", if rule.active { "use" } else { "stop using" }, rule.title);
+            if let Err(e) = self.execute(description, Command::UpdateHouseRule(rule)) {
+                self.send_message(format!("Error updating house rule: {}", e));
+            }
+        }
+    }
+
+    fn delete_house_rule(&mut self, id: i64) {
+        if let Some(rule) = self.house_rule(id) {
+            let command = Command::DeleteHouseRule(id);
+            match self.execute(format!("delete house rule '{}'", rule.title), command) {
+                Ok(_) => self.send_message(format!("Deleted house rule '{}'", rule.title)),
+                Err(e) => self.send_message(format!("Error deleting house rule: {}", e)),
+            }
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.config.theme
+    }
+
+    pub fn get_boardgames(&self) -> Vec<Boardgame> {
+        self.model.boardgames.clone()
+    }
+
+    fn viewed(&self) -> Option<&ViewedBoardgame> {
+        self.model.viewed.as_ref()
+    }
+
+    pub fn get_viewed_boardgame(&self) -> Option<Boardgame> {
+        self.viewed().map(|viewed| viewed.boardgame.clone())
+    }
+
+    pub fn get_credits(&self) -> Vec<Credit> {
+        self.viewed().map(|viewed| viewed.credits.clone()).unwrap_or_default()
+    }
+
+    pub fn get_player_count_poll(&self) -> PlayerCountPoll {
+        self.viewed().map(|viewed| viewed.poll.clone()).unwrap_or_default()
+    }
+
+    pub fn get_custom_fields(&self) -> Vec<CustomField> {
+        self.model.custom_fields.clone()
+    }
+
+    pub fn get_copies(&self) -> Vec<GameCopy> {
+        self.viewed().map(|viewed| viewed.copies.clone()).unwrap_or_default()
+    }
+
+    pub fn get_edited_boardgame(&self) -> Option<Boardgame> {
+        self.boardgame(self.state.editing?)
+    }
+
+    pub fn get_audited_copy(&self) -> Option<GameCopy> {
+        let copy_id = self.state.auditing?;
+        self.get_copies().into_iter().find(|copy| copy.id == Some(copy_id))
+    }
+
+    /// Components of one of the viewed boardgame's copies.
+    pub fn get_components(&self, copy_id: i64) -> Vec<Component> {
+        self.viewed().and_then(|viewed| viewed.components.get(&copy_id).cloned()).unwrap_or_default()
+    }
+
+    pub fn get_history(&self) -> Vec<HistoryEntry> {
+        self.viewed().map(|viewed| viewed.history.clone()).unwrap_or_default()
+    }
+
+    pub fn get_trashed_boardgames(&self) -> Vec<Boardgame> {
+        self.model.trash.clone()
+    }
+
+    pub fn get_house_rules(&self) -> Vec<HouseRule> {
+        self.viewed().map(|viewed| viewed.house_rules.clone()).unwrap_or_default()
+    }
+
+    /// Rules on any game matching the search input, with the name of the game.
+    pub fn search_house_rules(&self) -> Vec<(HouseRule, String)> {
+        self.model.rule_search.clone()
+    }
+
+    pub fn get_images(&self) -> Vec<ImageInfo> {
+        self.viewed().map(|viewed| viewed.images.clone()).unwrap_or_default()
+    }
+
+    /// A thumbnail of one of the viewed boardgame's images, decoded when the model was loaded.
+    pub fn get_thumbnail(&self, image_id: i64) -> Option<Rc<Thumbnail>> {
+        self.thumbnails.get(&image_id).cloned().flatten()
+    }
+
+    /// Each credit on the viewed boardgame, with the other boardgames it is on.
+    pub fn get_credited_boardgames(&self) -> Vec<(Credit, Vec<Boardgame>)> {
+        self.viewed().map(|viewed| viewed.credited.clone()).unwrap_or_default()
+    }
+}
+
+/// Moves a list's selection item by item, so that moving down from nothing selects the first.
+fn move_list_selection(list: &mut ListState, steps: isize) {
+    for _ in 0..steps.unsigned_abs() {
+        if steps < 0 {
+            list.select_previous();
+        } else {
+            list.select_next();
+        }
+    }
+}
+
+/// Reads a comma-separated list of mechanics such as "Worker placement, drafting".
+fn mechanics_input(values: &Values, field: &str) -> Result<Vec<Mechanic>, String> {
+    split_names(&values.text(field))
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<Vec<Mechanic>, _>>()
+        .map_err(|e| format!("Bad value for '{}': {}", field, e))
+}
+
+/// Splits a comma-separated input such as "Uwe Rosenberg, Klemens Franz" into names.
+fn split_names(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
 }
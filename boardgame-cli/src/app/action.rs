@@ -0,0 +1,258 @@
+use boardgame_core::db::{ImageKind, PlayerCountVote};
+use crossterm::event::KeyEvent;
+use ratatui::layout::Position;
+
+use super::{focus::Focus, App, Mode};
+use crate::keymap::{Binding, KeyCommand};
+
+/// Something the user asked for. Keys, clicks and focused buttons are all turned into actions,
+/// which `App::update` carries out. Actions on one item carry its id, so they mean the same
+/// whichever screen or selection they come from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Quit,
+    /// Stops the process until the shell resumes it.
+    Suspend,
+    /// Goes to a screen that only needs the viewed boardgame, if any.
+    GoTo(Mode),
+    Back,
+    Undo,
+    Redo,
+    RetryLoad,
+    ShowDebug,
+    /// Shows the keys and mouse actions for the screen, or hides them again.
+    ToggleHelp,
+    /// Scrolls the help this many lines.
+    ScrollHelp(isize),
+    /// Moves keyboard focus this many places through the screen.
+    MoveFocus(isize),
+    SetFocus(Option<Focus>),
+    /// A key for the focused input.
+    EditInput(KeyEvent),
+    /// Pasted text for the focused input.
+    Paste(String),
+    /// The mouse moved here.
+    Hover(Position),
+    /// Moves the selection in the screen's list this many items.
+    MoveSelection(isize),
+    /// Sorts the boardgame list the next way round.
+    CycleSort,
+    ViewBoardgame(i64),
+    AddBoardgame,
+    /// Opens the form to change a boardgame's details.
+    EditBoardgame(i64),
+    SaveBoardgame(i64),
+    TrashBoardgame(i64),
+    RestoreBoardgame(i64),
+    PurgeBoardgame(i64),
+    ApplyFilter,
+    ClearFilter,
+    Vote(PlayerCountVote),
+    AddCustomField,
+    DeleteCustomField(i64),
+    /// Shows the viewed boardgame's image this many places on.
+    ShowImage(isize),
+    AddImage(ImageKind),
+    AddCopy,
+    DeleteCopy(i64),
+    AuditCopy(i64),
+    AddComponent,
+    /// Changes how many of a component are in the box by this much.
+    AdjustComponent(i64, i32),
+    SaveComponentNotes(i64),
+    DeleteComponent(i64),
+    FinishAudit,
+    AddHouseRule,
+    ToggleHouseRule(i64),
+    DeleteHouseRule(i64),
+}
+
+impl App {
+    /// Carries out an action.
+    pub fn update(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.state.should_quit = true,
+            Action::Suspend => self.state.should_suspend = true,
+            Action::GoTo(mode) => self.go_to(mode),
+            Action::Back => self.prev_mode(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::RetryLoad => self.retry_load(),
+            Action::ShowDebug => self.send_debug_message(),
+            Action::ToggleHelp => self.state.help = if self.state.help.is_some() { None } else { Some(0) },
+            Action::ScrollHelp(lines) => {
+                if let Some(scroll) = &mut self.state.help {
+                    *scroll = scroll.saturating_add_signed(lines.clamp(i16::MIN as isize, i16::MAX as isize) as i16);
+                }
+            }
+            Action::MoveFocus(steps) => self.move_focus(steps),
+            Action::SetFocus(focus) => self.set_focus(focus),
+            Action::EditInput(key) => self.edit_input(key),
+            Action::Paste(text) => self.paste(&text),
+            Action::Hover(position) => self.cursor = Some(position),
+            Action::MoveSelection(steps) => self.move_selection(steps),
+            Action::CycleSort => self.cycle_sort(),
+            Action::ViewBoardgame(id) => self.view_boardgame(id),
+            Action::AddBoardgame => self.add_new_boardgame(),
+            Action::EditBoardgame(id) => self.edit_boardgame(id),
+            Action::SaveBoardgame(id) => self.save_boardgame(id),
+            Action::TrashBoardgame(id) => self.trash_boardgame(id),
+            Action::RestoreBoardgame(id) => self.restore_boardgame(id),
+            Action::PurgeBoardgame(id) => self.purge_boardgame(id),
+            Action::ApplyFilter => self.apply_filter(),
+            Action::ClearFilter => self.clear_filter(),
+            Action::Vote(vote) => self.cast_vote(vote),
+            Action::AddCustomField => self.add_custom_field(),
+            Action::DeleteCustomField(id) => self.delete_custom_field(id),
+            Action::ShowImage(steps) => self.show_image(steps),
+            Action::AddImage(kind) => self.add_image(kind),
+            Action::AddCopy => self.add_copy(),
+            Action::DeleteCopy(id) => self.delete_copy(id),
+            Action::AuditCopy(id) => self.audit_copy(id),
+            Action::AddComponent => self.add_component(),
+            Action::AdjustComponent(id, delta) => self.adjust_component(id, delta),
+            Action::SaveComponentNotes(id) => self.save_component_notes(id),
+            Action::DeleteComponent(id) => self.delete_component(id),
+            Action::FinishAudit => self.finish_audit(),
+            Action::AddHouseRule => self.add_house_rule(),
+            Action::ToggleHouseRule(id) => self.toggle_house_rule(id),
+            Action::DeleteHouseRule(id) => self.delete_house_rule(id),
+        }
+    }
+
+    /// What a key does on the current screen, or the message to show if it does nothing.
+    pub(super) fn key_action(&self, key: KeyEvent) -> Result<Action, String> {
+        let keymap = &self.config.keys;
+        let unhandled = || format!("Unhandled key: {:?}", key.code);
+        // Raw mode delivers Ctrl-C and Ctrl-Z as keys instead of signals, so they work even in
+        // an input
+        if let Some(command) = keymap.lookup(None, &key).filter(|command| command.works_in_inputs()) {
+            return self.command_action(command, None)?.ok_or_else(unhandled);
+        }
+        // The help covers the screen, so keys only scroll or close it
+        if self.state.help.is_some() {
+            return Ok(match keymap.lookup(None, &key) {
+                Some(KeyCommand::Up) => Action::ScrollHelp(-1),
+                Some(KeyCommand::Down) => Action::ScrollHelp(1),
+                _ => Action::ToggleHelp,
+            });
+        }
+        if let Some(action) = self.focus_action(key) {
+            return Ok(action);
+        }
+        if self.state.selected_input.is_some() {
+            return Ok(Action::EditInput(key));
+        }
+        let Some(mode) = self.get_curr_mode() else {
+            return Err(unhandled());
+        };
+        match keymap.lookup(Some(mode), &key) {
+            Some(command) => self.command_action(command, Some(mode))?.ok_or_else(unhandled),
+            None => Err(unhandled()),
+        }
+    }
+
+    /// The bound keys that can do something on a screen, the screen's own first, as the help
+    /// lists them.
+    pub fn bindings(&self, mode: Mode) -> Vec<&Binding> {
+        let keymap = &self.config.keys;
+        keymap
+            .bindings(Some(mode))
+            .chain(keymap.bindings(None))
+            .filter(|binding| !binding.chords.is_empty())
+            // Keys that only say "Nothing selected" still do something once something is
+            .filter(|binding| self.command_action(binding.command, Some(mode)) != Ok(None))
+            .collect()
+    }
+
+    /// The first keys that scroll the help, which takes them from the keys bound everywhere.
+    pub fn help_scroll_keys(&self) -> Vec<String> {
+        let keymap = &self.config.keys;
+        keymap
+            .bindings(None)
+            .filter(|binding| matches!(binding.command, KeyCommand::Up | KeyCommand::Down))
+            .filter_map(|binding| binding.chords.first().map(ToString::to_string))
+            .collect()
+    }
+
+    /// The first key for a command on a screen, to hint at it with, or `None` if it has no key there.
+    pub fn key_for(&self, mode: Mode, command: KeyCommand) -> Option<String> {
+        self.bindings(mode).into_iter().find(|binding| binding.command == command).map(|binding| binding.chords[0].to_string())
+    }
+
+    /// What a bound command does on a screen, if anything, or the message to show if it
+    /// can't.
+    fn command_action(&self, command: KeyCommand, mode: Option<Mode>) -> Result<Option<Action>, String> {
+        Ok(Some(match (command, mode) {
+            (KeyCommand::ForceQuit, _) => Action::Quit,
+            (KeyCommand::Suspend, _) => Action::Suspend,
+            (KeyCommand::Quit, _) => Action::GoTo(Mode::Quitting),
+            (KeyCommand::Back, _) if self.get_prev_mode().is_some() => Action::Back,
+            (KeyCommand::Undo, _) => Action::Undo,
+            (KeyCommand::Redo, _) => Action::Redo,
+            (KeyCommand::Reload, _) => Action::RetryLoad,
+            (KeyCommand::Debug, _) if self.config.debug => Action::ShowDebug,
+            (KeyCommand::Help, _) => Action::ToggleHelp,
+            (KeyCommand::Up, _) if self.current_list().is_some() => Action::MoveSelection(-1),
+            (KeyCommand::Down, _) if self.current_list().is_some() => Action::MoveSelection(1),
+            (KeyCommand::Filter, _) => Action::GoTo(Mode::Filtering),
+            (KeyCommand::ShowTrash, _) => Action::GoTo(Mode::Trash),
+            (KeyCommand::CustomFields, _) => Action::GoTo(Mode::CustomFields),
+            (KeyCommand::RuleSearch, _) => Action::GoTo(Mode::RuleSearch),
+            (KeyCommand::Sort, _) => Action::CycleSort,
+            (KeyCommand::Open, Some(Mode::Main)) => {
+                Action::ViewBoardgame(selected(self.selected_boardgame().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Open, Some(Mode::RuleSearch)) => {
+                Action::ViewBoardgame(selected(self.selected_rule_search_result().map(|(rule, _)| rule.boardgame_id))?)
+            }
+            (KeyCommand::Delete, Some(Mode::Main)) => {
+                Action::TrashBoardgame(selected(self.selected_boardgame().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Delete, Some(Mode::Details)) => Action::TrashBoardgame(selected(self.state.viewing)?),
+            (KeyCommand::Edit, Some(Mode::Main)) => {
+                Action::EditBoardgame(selected(self.selected_boardgame().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Edit, Some(Mode::Details)) => Action::EditBoardgame(selected(self.state.viewing)?),
+            (KeyCommand::Delete, Some(Mode::CustomFields)) => {
+                Action::DeleteCustomField(selected(self.selected_custom_field().map(|field| field.id))?)
+            }
+            (KeyCommand::Delete, Some(Mode::Copies)) => Action::DeleteCopy(selected(self.selected_copy().and_then(|item| item.id))?),
+            (KeyCommand::Delete, Some(Mode::Audit)) => {
+                Action::DeleteComponent(selected(self.selected_component().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Delete, Some(Mode::HouseRules)) => {
+                Action::DeleteHouseRule(selected(self.selected_house_rule().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Vote, _) => Action::GoTo(Mode::Voting),
+            (KeyCommand::AddImage, _) => Action::GoTo(Mode::AddingImage),
+            (KeyCommand::Copies, _) => Action::GoTo(Mode::Copies),
+            (KeyCommand::HouseRules, _) => Action::GoTo(Mode::HouseRules),
+            (KeyCommand::History, _) => Action::GoTo(Mode::History),
+            (KeyCommand::PreviousImage, _) => Action::ShowImage(-1),
+            (KeyCommand::NextImage, _) => Action::ShowImage(1),
+            (KeyCommand::Restore, _) => {
+                Action::RestoreBoardgame(selected(self.selected_trashed_boardgame().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Purge, _) => {
+                Action::PurgeBoardgame(selected(self.selected_trashed_boardgame().and_then(|item| item.id))?)
+            }
+            (KeyCommand::Audit, _) => Action::AuditCopy(selected(self.selected_copy().and_then(|item| item.id))?),
+            (KeyCommand::AddOne, _) => {
+                Action::AdjustComponent(selected(self.selected_component().and_then(|item| item.id))?, 1)
+            }
+            (KeyCommand::RemoveOne, _) => {
+                Action::AdjustComponent(selected(self.selected_component().and_then(|item| item.id))?, -1)
+            }
+            (KeyCommand::Toggle, _) => {
+                Action::ToggleHouseRule(selected(self.selected_house_rule().and_then(|item| item.id))?)
+            }
+            _ => return Ok(None),
+        }))
+    }
+}
+
+/// The item a key acts on, or the message to show when nothing is selected.
+fn selected<T>(item: Option<T>) -> Result<T, String> {
+    item.ok_or_else(|| "Nothing selected".to_string())
+}
@@ -0,0 +1,95 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+
+use super::{Action, App};
+use crate::widgets::form::FieldKind;
+
+/// Something on the current screen that can have keyboard focus.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Focus {
+    /// An input, by its label. A focused input is the one being typed into.
+    Input(String),
+    /// A button, by where it was drawn.
+    Button(Rect),
+}
+
+/// The keys `focus_action` handles, as the help lists them. They move between and press the
+/// buttons and inputs a screen draws, so unlike the keymap's they can't be rebound.
+pub const FOCUS_KEYS: [(&str, &str); 4] = [
+    ("tab, backtab", "Focus the next or previous button or input"),
+    ("arrows", "Move between buttons while one is focused"),
+    ("enter, space", "Press the focused button"),
+    ("esc", "Leave the focused button or input"),
+];
+
+/// The keys that move focus whatever is focused, so no binding could ever get them. The others
+/// in `FOCUS_KEYS` only act while something is focused, and bindings share them otherwise.
+pub const FOCUS_ONLY_KEYS: [KeyCode; 2] = [KeyCode::Tab, KeyCode::BackTab];
+
+impl App {
+    /// Every input and button drawn in the last frame, in reading order.
+    fn focus_order(&self) -> Vec<Focus> {
+        let inputs = self.view.inputs.iter().map(|(area, name)| (*area, Focus::Input(name.clone())));
+        let buttons = self.view.buttons.iter().map(|(area, _, _)| (*area, Focus::Button(*area)));
+        let mut order: Vec<(Rect, Focus)> = inputs.chain(buttons).collect();
+        order.sort_by_key(|(area, _)| (area.y, area.x));
+        order.into_iter().map(|(_, focus)| focus).collect()
+    }
+
+    pub fn get_focus(&self) -> Option<Focus> {
+        match (&self.state.selected_input, self.state.focused_button) {
+            (Some(name), _) => Some(Focus::Input(name.clone())),
+            (None, Some(area)) => Some(Focus::Button(area)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn set_focus(&mut self, focus: Option<Focus>) {
+        self.state.selected_input = None;
+        self.state.focused_button = None;
+        match focus {
+            Some(Focus::Input(name)) => self.state.selected_input = Some(name),
+            Some(Focus::Button(area)) => self.state.focused_button = Some(area),
+            None => {}
+        }
+    }
+
+    /// Moves focus `steps` places through the screen, wrapping around at either end.
+    pub(super) fn move_focus(&mut self, steps: isize) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self.get_focus().and_then(|focus| order.iter().position(|f| *f == focus));
+        let next = match current {
+            Some(i) => (i as isize + steps).rem_euclid(order.len() as isize) as usize,
+            None if steps < 0 => order.len() - 1,
+            None => 0,
+        };
+        self.set_focus(order.into_iter().nth(next));
+    }
+
+    /// What a key does to the focus, if it is one that moves or uses it.
+    ///
+    /// Tab and Shift-Tab always move focus. The arrow keys only do while a button is focused,
+    /// and Up and Down while an input that doesn't use them is, so lists keep them otherwise.
+    /// Enter and Space press the focused button, and Esc drops focus.
+    pub(super) fn focus_action(&self, key: KeyEvent) -> Option<Action> {
+        let focus = self.get_focus();
+        let field_uses_up_down = match &focus {
+            Some(Focus::Input(name)) => self.form().kind(name).is_some_and(FieldKind::uses_up_down),
+            _ => false,
+        };
+        Some(match (key.code, &focus) {
+            (KeyCode::Tab, _) => Action::MoveFocus(1),
+            (KeyCode::BackTab, _) => Action::MoveFocus(-1),
+            (KeyCode::Esc, Some(_)) => Action::SetFocus(None),
+            (KeyCode::Down | KeyCode::Right, Some(Focus::Button(_))) => Action::MoveFocus(1),
+            (KeyCode::Down, Some(Focus::Input(_))) if !field_uses_up_down => Action::MoveFocus(1),
+            (KeyCode::Up | KeyCode::Left, Some(Focus::Button(_))) => Action::MoveFocus(-1),
+            (KeyCode::Up, Some(Focus::Input(_))) if !field_uses_up_down => Action::MoveFocus(-1),
+            (KeyCode::Enter | KeyCode::Char(' '), Some(Focus::Button(area))) => self.view.button_action(*area)?,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use boardgame_core::{
+    db::{CustomField, CustomFieldType, PlayerCountMatch, MAX_WEIGHT, MIN_WEIGHT},
+    strings::*,
+};
+use chrono::{Datelike, Local};
+
+use super::{App, Mode};
+use crate::widgets::form::{FieldKind, Form};
+
+const MAX_PLAYERS: i64 = 99;
+const MAX_PLAY_TIME: i64 = 24 * 60;
+const MAX_AGE: i64 = 99;
+const MAX_COMPONENT_COUNT: i64 = 9999;
+const WEIGHT_STEP: f64 = 0.1;
+
+/// A year, which stepping starts at the current one.
+fn year() -> FieldKind {
+    FieldKind::Number { min: 1, max: 9999, start: Local::now().year().into() }
+}
+
+/// The field for a custom field's value. Filters take any value of a yes-or-no field, so it
+/// needs a way to be left unset there.
+fn custom_field_kind(field: &CustomField, filtering: bool) -> FieldKind {
+    match &field.field_type {
+        CustomFieldType::Text => FieldKind::Text,
+        CustomFieldType::Number => FieldKind::Decimal { min: f64::MIN, max: f64::MAX, step: 1.0 },
+        CustomFieldType::Bool if filtering => FieldKind::Select(["", "yes", "no"].map(String::from).to_vec()),
+        CustomFieldType::Bool => FieldKind::Checkbox,
+        CustomFieldType::Date => FieldKind::Date,
+        CustomFieldType::Enum(options) => {
+            FieldKind::Select(std::iter::once(String::new()).chain(options.iter().cloned()).collect())
+        }
+    }
+}
+
+impl App {
+    /// The form on the current screen, empty on screens without one.
+    pub fn form(&self) -> Form {
+        match self.get_curr_mode() {
+            Some(Mode::Adding | Mode::Editing) => self.boardgame_form(),
+            Some(Mode::Filtering) => self.filter_form(),
+            Some(Mode::Voting) => Form::new().text(VOTE_VOTER).number(VOTE_PLAYER_COUNT, 1, MAX_PLAYERS),
+            Some(Mode::CustomFields) => Form::new().text(CUSTOM_FIELD_NAME).text(CUSTOM_FIELD_TYPE),
+            Some(Mode::AddingImage) => Form::new().text(IMAGE_PATH),
+            Some(Mode::Copies) => Form::new().text(COPY_EDITION).text(COPY_LANGUAGE).field(COPY_PUBLISHER_YEAR, year()),
+            Some(Mode::Audit) => Form::new()
+                .text(COMPONENT_NAME)
+                .number(COMPONENT_EXPECTED, 1, MAX_COMPONENT_COUNT)
+                .text(COMPONENT_NOTES),
+            Some(Mode::HouseRules) => Form::new().text(RULE_TITLE).text(RULE_AUTHOR).multiline(RULE_BODY),
+            Some(Mode::RuleSearch) => Form::new().text(RULE_SEARCH),
+            _ => Form::new(),
+        }
+    }
+
+    fn boardgame_form(&self) -> Form {
+        let form = Form::new()
+            .text(BG_NAME)
+            .number(BG_MIN_PLAYERS, 1, MAX_PLAYERS)
+            .number(BG_MAX_PLAYERS, 1, MAX_PLAYERS)
+            .number(BG_PLAY_TIME, 1, MAX_PLAY_TIME)
+            .decimal(BG_WEIGHT, MIN_WEIGHT, MAX_WEIGHT, WEIGHT_STEP)
+            .number(BG_MIN_AGE, 0, MAX_AGE)
+            .field(BG_YEAR_PUBLISHED, year())
+            .text(BG_MECHANICS)
+            .text(BG_DESIGNERS)
+            .text(BG_ARTISTS)
+            .text(BG_PUBLISHERS);
+        self.get_custom_fields()
+            .iter()
+            .fold(form, |form, field| form.field(&field.name, custom_field_kind(field, false)))
+            .multiline(BG_DESCRIPTION)
+    }
+
+    fn filter_form(&self) -> Form {
+        let form = Form::new()
+            .text(FILTER_NAME)
+            .text(FILTER_CREDIT)
+            .decimal(FILTER_MIN_WEIGHT, MIN_WEIGHT, MAX_WEIGHT, WEIGHT_STEP)
+            .decimal(FILTER_MAX_WEIGHT, MIN_WEIGHT, MAX_WEIGHT, WEIGHT_STEP)
+            .number(FILTER_AGE, 0, MAX_AGE)
+            .text(FILTER_MECHANICS)
+            .number(FILTER_PLAYERS, 1, MAX_PLAYERS)
+            .select(FILTER_PLAYER_COUNT_MATCH, PlayerCountMatch::ALL.map(|m| m.as_str()));
+        self.get_custom_fields()
+            .iter()
+            .fold(form, |form, field| form.field(&field.name, custom_field_kind(field, true)))
+    }
+}
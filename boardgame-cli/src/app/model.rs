@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use boardgame_core::{
+    db::{
+        Boardgame, BoardgameDb, BoardgameFilter, BoardgameSort, Component, Credit, CustomField, GameCopy, HistoryEntry, HouseRule,
+        ImageInfo, PlayerCountPoll,
+    },
+    errors::Error,
+};
+
+/// Everything the screens show. It is loaded between frames, on startup and whenever the
+/// database or the choice of what to show changes, so drawing never touches the database.
+#[derive(Debug, Default)]
+pub struct Model {
+    pub boardgames: Vec<Boardgame>,
+    pub trash: Vec<Boardgame>,
+    pub custom_fields: Vec<CustomField>,
+    /// House rules matching the search input, each with the name of its game.
+    pub rule_search: Vec<(HouseRule, String)>,
+    pub viewed: Option<ViewedBoardgame>,
+}
+
+/// The boardgame open in the details screen and the screens under it.
+#[derive(Debug, Default)]
+pub struct ViewedBoardgame {
+    pub boardgame: Boardgame,
+    pub credits: Vec<Credit>,
+    /// Each credit with the other boardgames its person or company is credited on.
+    pub credited: Vec<(Credit, Vec<Boardgame>)>,
+    pub poll: PlayerCountPoll,
+    pub copies: Vec<GameCopy>,
+    /// Each copy's components, by copy id.
+    pub components: HashMap<i64, Vec<Component>>,
+    pub images: Vec<ImageInfo>,
+    pub house_rules: Vec<HouseRule>,
+    pub history: Vec<HistoryEntry>,
+}
+
+/// What the model should hold, taken from the app's state.
+#[derive(Debug)]
+pub struct Query<'a> {
+    pub filter: &'a BoardgameFilter,
+    pub sort: BoardgameSort,
+    pub viewing: Option<i64>,
+    pub rule_search: &'a str,
+}
+
+impl Model {
+    /// Loads everything in one transaction, so the screens never mix old and new data.
+    pub fn load(db: &BoardgameDb, query: &Query) -> Result<Model, Error> {
+        db.in_transaction(|db| {
+            let rule_search = db
+                .search_house_rules(query.rule_search)?
+                .into_iter()
+                .map(|rule| {
+                    let name = db.get_boardgame_by_id(rule.boardgame_id)?.map(|b| b.name);
+                    Ok((rule, name.unwrap_or_else(|| "Unknown game".to_string())))
+                })
+                .collect::<Result<_, Error>>()?;
+            let viewed = match query.viewing {
+                Some(id) => ViewedBoardgame::load(db, id)?,
+                None => None,
+            };
+            Ok(Model {
+                boardgames: db.find_boardgames_sorted(query.filter, query.sort)?,
+                trash: db.get_trashed_boardgames()?,
+                custom_fields: db.get_custom_fields()?,
+                rule_search,
+                viewed,
+            })
+        })
+    }
+}
+
+impl ViewedBoardgame {
+    fn load(db: &BoardgameDb, id: i64) -> Result<Option<ViewedBoardgame>, Error> {
+        let Some(boardgame) = db.get_boardgame_by_id(id)? else {
+            return Ok(None);
+        };
+        let copies = db.get_copies(id)?;
+        let components = copies
+            .iter()
+            .filter_map(|copy| copy.id)
+            .map(|copy_id| Ok((copy_id, db.get_components(copy_id)?)))
+            .collect::<Result<_, Error>>()?;
+        let credits = db.get_credits(id)?;
+        let credited = credits
+            .iter()
+            .map(|credit| {
+                let boardgames = if credit.role.is_company() {
+                    db.get_boardgames_by_company(credit.entity_id)?
+                } else {
+                    db.get_boardgames_by_person(credit.entity_id)?
+                };
+                let others = boardgames.into_iter().filter(|other| other.id != Some(id)).collect();
+                Ok((credit.clone(), others))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Some(ViewedBoardgame {
+            boardgame,
+            credits,
+            credited,
+            poll: db.get_player_count_poll(id)?,
+            copies,
+            components,
+            images: db.get_images(id)?,
+            house_rules: db.get_house_rules(id)?,
+            history: db.history_for(id)?,
+        }))
+    }
+}
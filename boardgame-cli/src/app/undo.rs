@@ -0,0 +1,279 @@
+use boardgame_core::{
+    db::{
+        Boardgame, BoardgameDb, Component, Credit, CreditRole, CustomField, CustomFieldType, CustomValue, GameCopy,
+        HouseRule, ImageKind, PlayerCountVote,
+    },
+    errors::Error,
+};
+
+/// How many actions can be undone.
+pub const UNDO_LIMIT: usize = 100;
+
+/// The names credited in each role, as a boardgame is created with.
+pub type Credits = Vec<(CreditRole, Vec<String>)>;
+
+/// A boardgame's credits grouped by role, with every role whether anyone has it or not.
+pub fn credits_by_role(credits: &[Credit]) -> Credits {
+    CreditRole::ALL
+        .into_iter()
+        .map(|role| {
+            let names = credits.iter().filter(|credit| credit.role == role).map(|credit| credit.name.clone());
+            (role, names.collect())
+        })
+        .collect()
+}
+
+/// What purging a boardgame takes with it, to find the commands left with nothing to act on.
+#[derive(Debug, Default)]
+pub struct Purged {
+    pub boardgame_id: i64,
+    pub copies: Vec<i64>,
+    pub components: Vec<i64>,
+    pub house_rules: Vec<i64>,
+}
+
+impl Purged {
+    /// Looks up what the boardgame has, so it must be called before it is purged.
+    pub fn load(db: &BoardgameDb, boardgame_id: i64) -> Result<Purged, Error> {
+        let copies: Vec<i64> = db.get_copies(boardgame_id)?.into_iter().filter_map(|copy| copy.id).collect();
+        let mut components = Vec::new();
+        for copy_id in &copies {
+            components.extend(db.get_components(*copy_id)?.into_iter().filter_map(|component| component.id));
+        }
+        let house_rules = db.get_house_rules(boardgame_id)?.into_iter().filter_map(|rule| rule.id).collect();
+        Ok(Purged { boardgame_id, copies, components, house_rules })
+    }
+}
+
+/// A change to the database. Applying one returns the command that reverses it, so undoing
+/// and redoing are both just applying whatever is on top of the stack.
+#[derive(Debug, Clone)]
+pub enum Command {
+    CreateBoardgame { boardgame: Boardgame, credits: Credits },
+    /// Replaces a boardgame's details, by its id, and the names credited in each role given.
+    UpdateBoardgame { boardgame: Boardgame, credits: Credits },
+    DeleteBoardgame(i64),
+    RestoreBoardgame(i64),
+    /// Sets or, with `None`, removes a player count vote.
+    Vote { boardgame_id: i64, player_count: i32, voter: String, vote: Option<PlayerCountVote> },
+    CreateCustomField { name: String, field_type: CustomFieldType },
+    DeleteCustomField(i64),
+    RestoreCustomField(CustomField, Vec<(i64, CustomValue)>),
+    AddImage { boardgame_id: i64, kind: ImageKind, data: Vec<u8> },
+    DeleteImage { boardgame_id: i64, image_id: i64 },
+    SetCover { boardgame_id: i64, image_id: i64 },
+    CreateCopy(GameCopy),
+    UpdateCopy(GameCopy),
+    DeleteCopy(i64),
+    CompleteAudit(i64),
+    AddComponent(Component),
+    UpdateComponent(Component),
+    DeleteComponent(i64),
+    CreateHouseRule(HouseRule),
+    UpdateHouseRule(HouseRule),
+    DeleteHouseRule(i64),
+    /// Applied in order, all or nothing, and reversed in the opposite order.
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    pub fn apply(self, db: &BoardgameDb) -> Result<Command, Error> {
+        Ok(match self {
+            Command::CreateBoardgame { boardgame, credits } => db.in_transaction(|db| {
+                let id = db.create_boardgame(&boardgame)?;
+                for (role, names) in credits {
+                    db.set_credits(id, role, &names)?;
+                }
+                Ok::<_, Error>(Command::DeleteBoardgame(id))
+            })?,
+            Command::UpdateBoardgame { boardgame, credits } => db.in_transaction(|db| {
+                let id = boardgame.id.ok_or_else(|| gone("boardgame"))?;
+                let previous = db.get_boardgame_by_id(id)?.ok_or_else(|| gone("boardgame"))?;
+                let previous_credits = credits_by_role(&db.get_credits(id)?);
+                changed(db.update_boardgame(&boardgame)?, "boardgame")?;
+                for (role, names) in credits {
+                    db.set_credits(id, role, &names)?;
+                }
+                Ok::<_, Error>(Command::UpdateBoardgame { boardgame: previous, credits: previous_credits })
+            })?,
+            Command::DeleteBoardgame(id) => {
+                changed(db.delete_boardgame(id)?, "boardgame")?;
+                Command::RestoreBoardgame(id)
+            }
+            Command::RestoreBoardgame(id) => {
+                changed(db.restore_boardgame(id)?, "boardgame")?;
+                Command::DeleteBoardgame(id)
+            }
+            Command::Vote { boardgame_id, player_count, voter, vote } => {
+                let previous = db.get_player_count_vote(boardgame_id, player_count, &voter)?;
+                match vote {
+                    Some(vote) => db.vote_player_count(boardgame_id, player_count, &voter, vote)?,
+                    None => {
+                        db.remove_player_count_vote(boardgame_id, player_count, &voter)?;
+                    }
+                }
+                Command::Vote { boardgame_id, player_count, voter, vote: previous }
+            }
+            Command::CreateCustomField { name, field_type } => {
+                Command::DeleteCustomField(db.create_custom_field(&name, &field_type)?)
+            }
+            Command::DeleteCustomField(id) => {
+                let field = db
+                    .get_custom_fields()?
+                    .into_iter()
+                    .find(|field| field.id == id)
+                    .ok_or_else(|| gone("custom field"))?;
+                let values = db.get_custom_field_values(&field)?;
+                db.delete_custom_field(id)?;
+                Command::RestoreCustomField(field, values)
+            }
+            Command::RestoreCustomField(field, values) => {
+                db.restore_custom_field(&field, &values)?;
+                Command::DeleteCustomField(field.id)
+            }
+            Command::AddImage { boardgame_id, kind, data } => {
+                let before = db.get_images(boardgame_id)?;
+                let id = db.add_image(boardgame_id, kind, &data)?;
+                let mut undo = Vec::new();
+                // Adding a file that is already attached gives back the existing image
+                if before.iter().all(|image| image.id != id) {
+                    undo.push(Command::DeleteImage { boardgame_id, image_id: id });
+                }
+                let cover = before.iter().find(|image| image.kind == ImageKind::Cover && image.id != id);
+                if let (ImageKind::Cover, Some(cover)) = (kind, cover) {
+                    undo.push(Command::SetCover { boardgame_id, image_id: cover.id });
+                }
+                Command::Batch(undo)
+            }
+            Command::DeleteImage { boardgame_id, image_id } => {
+                let image = db
+                    .get_images(boardgame_id)?
+                    .into_iter()
+                    .find(|image| image.id == image_id)
+                    .ok_or_else(|| gone("image"))?;
+                let data = db.get_image_data(image_id)?.ok_or_else(|| gone("image"))?;
+                db.delete_image(image_id)?;
+                Command::AddImage { boardgame_id, kind: image.kind, data }
+            }
+            Command::SetCover { boardgame_id, image_id } => {
+                let cover = db.get_images(boardgame_id)?.into_iter().find(|image| image.kind == ImageKind::Cover);
+                db.set_cover(image_id)?;
+                match cover {
+                    Some(cover) if cover.id != image_id => Command::SetCover { boardgame_id, image_id: cover.id },
+                    _ => Command::Batch(Vec::new()),
+                }
+            }
+            Command::CreateCopy(copy) => Command::DeleteCopy(db.create_copy(&copy)?),
+            Command::UpdateCopy(copy) => {
+                let previous = copy.id.map(|id| db.get_copy(id)).transpose()?.flatten().ok_or_else(|| gone("copy"))?;
+                db.update_copy(&copy)?;
+                Command::UpdateCopy(previous)
+            }
+            Command::DeleteCopy(id) => {
+                let copy = db.get_copy(id)?.ok_or_else(|| gone("copy"))?;
+                let components = db.get_components(id)?;
+                db.delete_copy(id)?;
+                let mut restore = vec![Command::CreateCopy(copy)];
+                restore.extend(components.into_iter().map(Command::AddComponent));
+                Command::Batch(restore)
+            }
+            Command::CompleteAudit(copy_id) => {
+                let previous = db.get_copy(copy_id)?.ok_or_else(|| gone("copy"))?;
+                db.complete_audit(copy_id)?;
+                Command::UpdateCopy(previous)
+            }
+            Command::AddComponent(component) => Command::DeleteComponent(db.add_component(&component)?),
+            Command::UpdateComponent(component) => {
+                let previous = component
+                    .id
+                    .map(|id| db.get_component(id))
+                    .transpose()?
+                    .flatten()
+                    .ok_or_else(|| gone("component"))?;
+                db.update_component(&component)?;
+                Command::UpdateComponent(previous)
+            }
+            Command::DeleteComponent(id) => {
+                let component = db.get_component(id)?.ok_or_else(|| gone("component"))?;
+                db.delete_component(id)?;
+                Command::AddComponent(component)
+            }
+            Command::CreateHouseRule(rule) => Command::DeleteHouseRule(db.create_house_rule(&rule)?),
+            Command::UpdateHouseRule(rule) => {
+                let previous = rule
+                    .id
+                    .map(|id| db.get_house_rule(id))
+                    .transpose()?
+                    .flatten()
+                    .ok_or_else(|| gone("house rule"))?;
+                db.update_house_rule(&rule)?;
+                Command::UpdateHouseRule(previous)
+            }
+            Command::DeleteHouseRule(id) => {
+                let rule = db.get_house_rule(id)?.ok_or_else(|| gone("house rule"))?;
+                db.delete_house_rule(id)?;
+                Command::CreateHouseRule(rule)
+            }
+            Command::Batch(commands) => db.in_transaction(|db| {
+                let mut undo = commands
+                    .into_iter()
+                    .map(|command| command.apply(db))
+                    .collect::<Result<Vec<Command>, Error>>()?;
+                undo.reverse();
+                Ok::<_, Error>(Command::Batch(undo))
+            })?,
+        })
+    }
+
+    /// The command without anything of the purged boardgame, or `None` if it can't be applied
+    /// without it. A batch goes whole or not at all.
+    pub fn without(self, purged: &Purged) -> Option<Command> {
+        match self {
+            Command::RestoreCustomField(field, values) => {
+                let values = values.into_iter().filter(|(boardgame_id, _)| *boardgame_id != purged.boardgame_id);
+                Some(Command::RestoreCustomField(field, values.collect()))
+            }
+            Command::Batch(commands) => {
+                commands.into_iter().map(|command| command.without(purged)).collect::<Option<_>>().map(Command::Batch)
+            }
+            command if command.acts_on(purged) => None,
+            command => Some(command),
+        }
+    }
+
+    fn acts_on(&self, purged: &Purged) -> bool {
+        let boardgame = |id: i64| id == purged.boardgame_id;
+        let copy = |id: &i64| purged.copies.contains(id);
+        match self {
+            Command::UpdateBoardgame { boardgame: game, .. } => game.id.is_some_and(boardgame),
+            Command::DeleteBoardgame(id) | Command::RestoreBoardgame(id) => boardgame(*id),
+            Command::Vote { boardgame_id, .. }
+            | Command::AddImage { boardgame_id, .. }
+            | Command::DeleteImage { boardgame_id, .. }
+            | Command::SetCover { boardgame_id, .. } => boardgame(*boardgame_id),
+            Command::CreateCopy(game_copy) | Command::UpdateCopy(game_copy) => boardgame(game_copy.boardgame_id),
+            Command::DeleteCopy(id) | Command::CompleteAudit(id) => copy(id),
+            Command::AddComponent(component) => copy(&component.copy_id),
+            Command::UpdateComponent(component) => component.id.is_some_and(|id| purged.components.contains(&id)),
+            Command::DeleteComponent(id) => purged.components.contains(id),
+            Command::CreateHouseRule(rule) | Command::UpdateHouseRule(rule) => boardgame(rule.boardgame_id),
+            Command::DeleteHouseRule(id) => purged.house_rules.contains(id),
+            Command::CreateBoardgame { .. }
+            | Command::CreateCustomField { .. }
+            | Command::DeleteCustomField(_)
+            | Command::RestoreCustomField(..)
+            | Command::Batch(_) => false,
+        }
+    }
+}
+
+fn gone(what: &str) -> Error {
+    Error::InvalidInput(format!("the {} no longer exists", what))
+}
+
+fn changed(rows: usize, what: &str) -> Result<(), Error> {
+    if rows == 0 {
+        return Err(gone(what));
+    }
+    Ok(())
+}
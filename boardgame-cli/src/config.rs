@@ -0,0 +1,520 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use boardgame_core::db::{BoardgameSort, TRASH_RETENTION_DAYS};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::{app::Mode, keymap::Keymap, keys::KeyChord};
+
+mod args;
+
+pub use args::{Command, Overrides, USAGE};
+
+/// The directory in the config directories that holds the app's settings.
+const APP_DIR: &str = "boardgame-manager";
+const FILE_NAME: &str = "config.toml";
+/// What `init-config` writes: every setting, commented out at its default.
+pub const DEFAULT_FILE: &str = include_str!("config/default.toml");
+/// Environment variables override the setting named by the rest of their name, so
+/// `BOARDGAME_MESSAGE_TIMEOUT` sets `message-timeout`.
+const ENV_PREFIX: &str = "BOARDGAME_";
+/// Names a config file to use instead of looking in the config directories.
+const CONFIG_ENV: &str = "BOARDGAME_CONFIG";
+
+/// How the app is set up, from defaults overridden by the config file, then the environment,
+/// then the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub database: PathBuf,
+    pub message_timeout: Duration,
+    /// Days a deleted boardgame stays in the trash before it is purged on startup.
+    pub trash_retention_days: u64,
+    /// The order of the boardgame list when the app starts.
+    pub sort: BoardgameSort,
+    /// Lets a key show the app's internal state.
+    pub debug: bool,
+    pub theme: Theme,
+    pub keys: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            database: PathBuf::from("boardgame.db"),
+            message_timeout: Duration::from_secs(3),
+            trash_retention_days: TRASH_RETENTION_DAYS,
+            sort: BoardgameSort::Name,
+            debug: false,
+            theme: Theme::default(),
+            keys: Keymap::default(),
+        }
+    }
+}
+
+/// Colours the screens are drawn with, by what they mark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// The selected list item and the focused input.
+    pub highlight: Color,
+    /// Labels in a boardgame's details.
+    pub label: Color,
+    /// Less important text, such as notes and timestamps.
+    pub muted: Color,
+    pub messages: Color,
+    pub errors: Color,
+    /// Buttons that add or confirm, and things added or complete.
+    pub positive: Color,
+    /// Buttons that delete or cancel, and things removed or missing.
+    pub negative: Color,
+    /// Other buttons.
+    pub neutral: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            highlight: Color::Magenta,
+            label: Color::Cyan,
+            muted: Color::DarkGray,
+            messages: Color::Green,
+            errors: Color::Red,
+            positive: Color::Green,
+            negative: Color::Red,
+            neutral: Color::Blue,
+        }
+    }
+}
+
+/// The settings one source gives, which override only what they set.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Settings {
+    database: Option<PathBuf>,
+    message_timeout: Option<f64>,
+    trash_retention_days: Option<u64>,
+    sort: Option<String>,
+    debug: Option<bool>,
+    theme: ThemeSettings,
+    /// A preset by name and the keys for commands everywhere, then tables of keys for the
+    /// commands on each screen.
+    keys: toml::Table,
+    /// The preset from the environment or command line, which have no tables to give it in.
+    #[serde(skip)]
+    key_preset: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeSettings {
+    highlight: Option<String>,
+    label: Option<String>,
+    muted: Option<String>,
+    messages: Option<String>,
+    errors: Option<String>,
+    positive: Option<String>,
+    negative: Option<String>,
+    neutral: Option<String>,
+}
+
+impl Settings {
+    /// The settings given by environment variables, read through `var`.
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Settings, String> {
+        let get = |name: &str| {
+            let variable = format!("{}{}", ENV_PREFIX, name.to_uppercase().replace('-', "_"));
+            var(&variable).map(|value| (variable, value))
+        };
+        Ok(Settings {
+            database: get("database").map(|(_, value)| PathBuf::from(value)),
+            message_timeout: get("message-timeout").map(|(name, value)| parse_value(&name, &value)).transpose()?,
+            trash_retention_days: get("trash-retention-days")
+                .map(|(name, value)| parse_value(&name, &value))
+                .transpose()?,
+            sort: get("sort").map(|(_, value)| value),
+            debug: get("debug").map(|(name, value)| parse_flag(&name, &value)).transpose()?,
+            key_preset: get("keys").map(|(_, value)| value),
+            ..Settings::default()
+        })
+    }
+}
+
+/// Parses a setting given as text, naming it if the text isn't valid.
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    value.trim().parse().map_err(|e| format!("{}: '{}' is not valid: {}", name, value.trim(), e))
+}
+
+fn parse_flag(name: &str, value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(format!("{}: expected true or false, got '{}'", name, value.trim())),
+    }
+}
+
+impl Config {
+    /// Reads the settings for a run of the app from the config file, the environment and the
+    /// command line.
+    pub fn load(overrides: Overrides) -> anyhow::Result<Config> {
+        Config::load_with(overrides, |name| std::env::var(name).ok())
+    }
+
+    /// As `load`, with environment variables read through `var`.
+    fn load_with(overrides: Overrides, var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Config> {
+        let mut config = Config::default();
+        // A file asked for by name has to be there, the usual places are only looked in
+        let path = match overrides.config.or_else(|| var(CONFIG_ENV).map(PathBuf::from)) {
+            Some(path) => Some(path),
+            None => search_paths(&var).into_iter().find(|path| path.is_file()),
+        };
+        if let Some(path) = path {
+            let text = fs::read_to_string(&path).with_context(|| format!("couldn't read {}", path.display()))?;
+            let settings = toml::from_str(&text).with_context(|| format!("invalid config file {}", path.display()))?;
+            config.apply(settings).with_context(|| format!("invalid config file {}", path.display()))?;
+        }
+        let env = Settings::from_env(&var).map_err(anyhow::Error::msg).context("invalid environment variable")?;
+        config.apply(env).context("invalid environment variable")?;
+        config.apply(overrides.settings).context("invalid command-line option")?;
+        config.keys.check().map_err(anyhow::Error::msg).context("invalid key bindings")?;
+        Ok(config)
+    }
+
+    /// Overrides whatever `settings` set, checking each value.
+    fn apply(&mut self, settings: Settings) -> anyhow::Result<()> {
+        if let Some(database) = settings.database {
+            if database.as_os_str().is_empty() {
+                bail!("database: the path is empty");
+            }
+            self.database = database;
+        }
+        if let Some(seconds) = settings.message_timeout {
+            self.message_timeout = Duration::try_from_secs_f64(seconds)
+                .ok()
+                .filter(|timeout| !timeout.is_zero())
+                .with_context(|| format!("message-timeout: expected a number of seconds above 0, got {}", seconds))?;
+        }
+        if let Some(days) = settings.trash_retention_days {
+            self.trash_retention_days = days;
+        }
+        if let Some(sort) = settings.sort {
+            self.sort = sort.parse().map_err(|_| {
+                let orders = BoardgameSort::ALL.map(|sort| sort.as_str()).join(", ");
+                anyhow::anyhow!("sort: unknown order '{}', expected one of {}", sort.trim(), orders)
+            })?;
+        }
+        if let Some(debug) = settings.debug {
+            self.debug = debug;
+        }
+
+        let theme = settings.theme;
+        let colors = [
+            ("highlight", theme.highlight, &mut self.theme.highlight),
+            ("label", theme.label, &mut self.theme.label),
+            ("muted", theme.muted, &mut self.theme.muted),
+            ("messages", theme.messages, &mut self.theme.messages),
+            ("errors", theme.errors, &mut self.theme.errors),
+            ("positive", theme.positive, &mut self.theme.positive),
+            ("negative", theme.negative, &mut self.theme.negative),
+            ("neutral", theme.neutral, &mut self.theme.neutral),
+        ];
+        for (name, value, color) in colors {
+            if let Some(value) = value {
+                *color = value.parse().map_err(|_| {
+                    anyhow::anyhow!("theme.{}: unknown colour '{}', expected a name, an index or #rrggbb", name, value)
+                })?;
+            }
+        }
+
+        if let Some(preset) = settings.key_preset {
+            self.keys.set_preset(preset.parse().map_err(|e| anyhow::anyhow!("keys: {}", e))?);
+        }
+        for (name, value) in settings.keys {
+            match (name.as_str(), value) {
+                ("preset", toml::Value::String(preset)) => {
+                    self.keys.set_preset(preset.parse().map_err(|e| anyhow::anyhow!("keys.preset: {}", e))?);
+                }
+                ("preset", value) => bail!("keys.preset: expected the name of a preset, got {}", value),
+                (_, toml::Value::Table(table)) => {
+                    let mode = Mode::ALL.into_iter().find(|mode| mode.name() == name).with_context(|| {
+                        let screens = Mode::ALL.map(Mode::name).join(", ");
+                        format!("keys.{}: unknown screen, expected one of {}", name, screens)
+                    })?;
+                    for (command, value) in table {
+                        let chords = parse_chords(&format!("keys.{}.{}", name, command), value)?;
+                        self.keys.bind(Some(mode), &command, chords).map_err(|e| anyhow::anyhow!("keys.{}: {}", name, e))?;
+                    }
+                }
+                (_, value) => {
+                    let chords = parse_chords(&format!("keys.{}", name), value)?;
+                    self.keys.bind(None, &name, chords).map_err(|e| anyhow::anyhow!("keys: {}", e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the keys for a command, given as one key or a list of them.
+fn parse_chords(name: &str, value: toml::Value) -> anyhow::Result<Vec<KeyChord>> {
+    let values = match value {
+        toml::Value::Array(values) => values,
+        value => vec![value],
+    };
+    values
+        .into_iter()
+        .map(|value| match value {
+            toml::Value::String(chord) => chord.parse().map_err(|e| anyhow::anyhow!("{}: {}", name, e)),
+            value => bail!("{}: expected a key or a list of keys, got {}", name, value),
+        })
+        .collect()
+}
+
+/// Where a config file is looked for, most important first: the user's config directory, then
+/// on Unix the system-wide ones in `XDG_CONFIG_DIRS`.
+fn search_paths(var: impl Fn(&str) -> Option<String>) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::config_dir().into_iter().collect();
+    if cfg!(unix) {
+        let system = var("XDG_CONFIG_DIRS").filter(|dirs| !dirs.is_empty()).unwrap_or_else(|| "/etc/xdg".to_string());
+        dirs.extend(system.split(':').map(PathBuf::from).filter(|dir| dir.is_absolute()));
+    }
+    dirs.into_iter().map(|dir| dir.join(APP_DIR).join(FILE_NAME)).collect()
+}
+
+/// Writes the commented default settings to `path`, or to the user's config directory, and
+/// returns where they went. An existing file is only replaced with `force`.
+pub fn init(path: Option<PathBuf>, force: bool) -> anyhow::Result<PathBuf> {
+    let path = match path {
+        Some(path) => path,
+        None => dirs::config_dir()
+            .map(|dir| dir.join(APP_DIR).join(FILE_NAME))
+            .context("couldn't find a config directory, name a file to write instead")?,
+    };
+    if path.exists() && !force {
+        bail!("{} already exists, use --force to replace it", path.display());
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("couldn't create {}", dir.display()))?;
+    }
+    fs::write(&path, DEFAULT_FILE).with_context(|| format!("couldn't write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::keymap::{KeyCommand, Preset};
+
+    fn args(args: &[&str]) -> Result<Command, String> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn overrides(list: &[&str]) -> Overrides {
+        match args(list) {
+            Ok(Command::Run(overrides)) => *overrides,
+            other => panic!("expected a run, got {:?}", other),
+        }
+    }
+
+    /// Loads with only the given environment variables set.
+    fn load(overrides: Overrides, env: &[(&str, &str)]) -> anyhow::Result<Config> {
+        let env: HashMap<String, String> = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::load_with(overrides, |name| env.get(name).cloned())
+    }
+
+    /// The full error, with its contexts, as shown to the user.
+    fn error(result: anyhow::Result<Config>) -> String {
+        format!("{:#}", result.expect_err("expected the settings to be rejected"))
+    }
+
+    #[test]
+    fn test_default_file() {
+        let settings: Settings = toml::from_str(DEFAULT_FILE).expect("the default file is valid");
+        let mut config = Config::default();
+        config.apply(settings).expect("the default file is valid");
+        assert_eq!(config, Config::default());
+
+        // Uncommented, it gives the defaults
+        let uncommented: String = DEFAULT_FILE
+            .lines()
+            .map(|line| line.strip_prefix('#').filter(|line| !line.starts_with(' ')).unwrap_or(line))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let settings: Settings = toml::from_str(&uncommented).expect("the uncommented default file is valid");
+
+        // and lists every command
+        for scope in std::iter::once(None).chain(Mode::ALL.map(Some)) {
+            let table = match scope {
+                Some(mode) => settings.keys.get(mode.name()).and_then(toml::Value::as_table),
+                None => Some(&settings.keys),
+            };
+            for binding in config.keys.bindings(scope) {
+                let listed = table.is_some_and(|table| table.contains_key(binding.command.name()));
+                assert!(listed, "{} on {:?} isn't in the default file", binding.command.name(), scope);
+            }
+        }
+
+        let mut config = Config::default();
+        config.apply(settings).expect("the defaults are valid");
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let dir = tempdir().expect("failed to create temp directory");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "database = \"file.db\"\nmessage-timeout = 5\nsort = \"year\"\n[keys]\nquit = \"ctrl-q\"\n",
+        )
+        .unwrap();
+        let config_arg = path.to_str().unwrap();
+
+        let config = load(overrides(&["--config", config_arg]), &[]).unwrap();
+        assert_eq!(config.database, PathBuf::from("file.db"));
+        assert_eq!(config.message_timeout, Duration::from_secs(5));
+        assert_eq!(config.sort, BoardgameSort::Year);
+        let quit = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(config.keys.lookup(Some(Mode::Main), &quit), Some(KeyCommand::Quit));
+        assert_eq!(config.trash_retention_days, TRASH_RETENTION_DAYS);
+
+        let env = [
+            ("BOARDGAME_DATABASE", "env.db"),
+            ("BOARDGAME_SORT", "weight"),
+            ("BOARDGAME_DEBUG", "yes"),
+            ("BOARDGAME_KEYS", "vim"),
+        ];
+        let config = load(overrides(&["--config", config_arg]), &env).unwrap();
+        // The preset changes, but the keys the file binds stay
+        let mut keys = Keymap::new(Preset::Vim);
+        keys.bind(None, "quit", vec!["ctrl-q".parse().unwrap()]).unwrap();
+        assert_eq!(config.keys, keys);
+        assert_eq!(config.database, PathBuf::from("env.db"));
+        assert_eq!(config.message_timeout, Duration::from_secs(5));
+        assert_eq!(config.sort, BoardgameSort::Weight);
+        assert!(config.debug);
+
+        let config = load(overrides(&["--config", config_arg, "--database=cli.db", "--sort", "added"]), &env).unwrap();
+        assert_eq!(config.database, PathBuf::from("cli.db"));
+        assert_eq!(config.sort, BoardgameSort::Added);
+
+        // The environment can name the file too
+        let config = load(Overrides::default(), &[(CONFIG_ENV, config_arg)]).unwrap();
+        assert_eq!(config.database, PathBuf::from("file.db"));
+
+        let missing = dir.path().join("missing.toml");
+        let message = error(load(overrides(&["-c", missing.to_str().unwrap()]), &[]));
+        assert!(message.starts_with("couldn't read"), "{}", message);
+    }
+
+    #[test]
+    fn test_validation() {
+        let dir = tempdir().expect("failed to create temp directory");
+        let path = dir.path().join("config.toml");
+        let load_file = |text: &str| {
+            fs::write(&path, text).unwrap();
+            load(overrides(&["--config", path.to_str().unwrap()]), &[])
+        };
+
+        assert!(error(load_file("databse = \"x.db\"")).contains("unknown field `databse`"));
+        assert!(error(load_file("message-timeout = 0")).contains("message-timeout: expected a number of seconds above 0"));
+        assert!(error(load_file("sort = \"price\"")).contains("sort: unknown order 'price', expected one of name,"));
+        assert!(error(load_file("[theme]\nlabel = \"teal\"")).contains("theme.label: unknown colour 'teal'"));
+        assert!(error(load_file("[keys]\nundo = \"hyper-u\"")).contains("keys.undo: unknown modifier 'hyper'"));
+        assert!(error(load_file("[keys]\nundo = \"f13\"")).contains("keys.undo: unknown key 'f13'"));
+        assert!(error(load_file("[keys]\npreset = \"nano\"")).contains("keys.preset: unknown preset 'nano', expected one of default, vim, emacs"));
+        assert!(error(load_file("[keys]\nundo = 5")).contains("keys.undo: expected a key or a list of keys, got 5"));
+        assert!(error(load_file("[keys]\nopen = \"o\"")).contains("keys: unknown command 'open', expected one of force-quit,"));
+        assert!(error(load_file("[keys.main]\nvote = \"v\"")).contains("keys.main: unknown command 'vote', expected one of open,"));
+        assert!(error(load_file("[keys.voting]\nvote = \"v\"")).contains("keys.voting: unknown command 'vote', no keys can be bound on this screen"));
+        assert!(error(load_file("[keys.lobby]\nopen = \"o\"")).contains("keys.lobby: unknown screen, expected one of main,"));
+        assert!(error(load_file("[keys]\nsuspend = \"z\"")).contains("keys: 'z' types text, but suspend works while typing"));
+        let message = error(load_file("[keys]\nundo = [\"u\", \"f\"]"));
+        assert_eq!(message, "invalid key bindings: 'f' is bound to both undo and filter on the main screen");
+        let message = error(load_file("[keys]\nundo = \"q\""));
+        assert_eq!(message, "invalid key bindings: 'q' is bound to both quit and undo everywhere");
+
+        let config = load_file("[keys]\nback = []\nundo = [\"u\", \"ctrl-z\"]\nsuspend = []").unwrap();
+        assert_eq!(config.keys.bindings(None).find(|binding| binding.command == KeyCommand::Back).unwrap().chords, []);
+
+        let config = load_file("[theme]\nhighlight = \"#ff8800\"\nmuted = \"244\"").unwrap();
+        assert_eq!(config.theme.highlight, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(config.theme.muted, Color::Indexed(244));
+
+        // Through an empty file, so a config file of whoever runs the tests isn't read
+        fs::write(&path, "").unwrap();
+        let empty = path.to_str().unwrap();
+        let message = error(load(Overrides::default(), &[(CONFIG_ENV, empty), ("BOARDGAME_MESSAGE_TIMEOUT", "soon")]));
+        assert!(message.starts_with("invalid environment variable: BOARDGAME_MESSAGE_TIMEOUT: 'soon' is not valid"), "{}", message);
+        let message = error(load(Overrides::default(), &[(CONFIG_ENV, empty), ("BOARDGAME_DEBUG", "maybe")]));
+        assert!(message.contains("expected true or false, got 'maybe'"), "{}", message);
+        let message = error(load(overrides(&["--config", empty, "--database", ""]), &[]));
+        assert!(message.starts_with("invalid command-line option: database: the path is empty"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(&[]), Ok(Command::Run(Box::default())));
+        assert_eq!(args(&["--sort", "weight", "-h"]), Ok(Command::Help));
+        assert_eq!(args(&["init-config"]), Ok(Command::InitConfig { path: None, force: false }));
+        assert_eq!(
+            args(&["init-config", "--force", "here.toml"]),
+            Ok(Command::InitConfig { path: Some(PathBuf::from("here.toml")), force: true })
+        );
+
+        assert_eq!(args(&["keys", "--keys", "vim"]), Ok(Command::Keys(Box::new(overrides(&["--keys", "vim"])))));
+        assert!(args(&["keys", "init-config"]).is_err());
+        assert_eq!(
+            args(&["export", "--database", "games.db"]),
+            Ok(Command::Export { overrides: Box::new(overrides(&["--database", "games.db"])), path: None })
+        );
+        assert_eq!(
+            args(&["import", "games.csv"]),
+            Ok(Command::Import { overrides: Box::default(), path: PathBuf::from("games.csv") })
+        );
+        assert_eq!(args(&["import"]), Err("import needs a CSV file to read".to_string()));
+        assert!(args(&["keys", "games.csv"]).is_err());
+
+        let run = overrides(&["--message-timeout=1.5", "--trash-retention-days", "7", "--debug"]);
+        assert_eq!(run.settings.message_timeout, Some(1.5));
+        assert_eq!(run.settings.trash_retention_days, Some(7));
+        assert_eq!(run.settings.debug, Some(true));
+
+        assert_eq!(args(&["--database"]), Err("--database needs a value".to_string()));
+        assert!(args(&["--trash-retention-days", "-1"]).unwrap_err().starts_with("--trash-retention-days: '-1' is not valid"));
+        assert_eq!(args(&["--force"]), Err("--force only goes with init-config".to_string()));
+        assert_eq!(args(&["init-config", "--database", "games.db"]), Err("init-config only takes --force and a path".to_string()));
+        assert!(args(&["--message-timeout", "5", "init-config"]).is_err());
+        assert_eq!(args(&["games.db"]), Err("unexpected argument 'games.db', try --help".to_string()));
+        assert!(args(&["init-config", "a.toml", "b.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_init() {
+        let dir = tempdir().expect("failed to create temp directory");
+        let path = dir.path().join("nested").join("config.toml");
+
+        assert_eq!(init(Some(path.clone()), false).unwrap(), path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), DEFAULT_FILE);
+
+        fs::write(&path, "sort = \"year\"").unwrap();
+        let message = format!("{:#}", init(Some(path.clone()), false).unwrap_err());
+        assert!(message.ends_with("already exists, use --force to replace it"), "{}", message);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "sort = \"year\"");
+
+        init(Some(path.clone()), true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), DEFAULT_FILE);
+    }
+}
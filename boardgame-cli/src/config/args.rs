@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use super::{parse_value, Settings};
+
+pub const USAGE: &str = "\
+Keeps track of a boardgame collection.
+
+Usage: boardgame-cli [OPTIONS]
+       boardgame-cli keys [OPTIONS]
+       boardgame-cli export [OPTIONS] [PATH]
+       boardgame-cli import [OPTIONS] PATH
+       boardgame-cli init-config [--force] [PATH]
+
+Commands:
+  keys                        List the keys for every screen, from the preset and config file
+  export [PATH]               Write the boardgames, with their custom fields, as CSV to PATH or
+                              standard output
+  import PATH                 Add the boardgames in a CSV file, as export writes them
+  init-config [PATH]          Write a config file with every setting at its default, commented
+                              out, to PATH or the user's config directory
+
+Options:
+  -c, --config <PATH>         Read settings from this file instead of the config directories
+      --database <PATH>       The database file
+      --message-timeout <S>   Seconds a message stays on screen
+      --trash-retention-days <DAYS>
+                              Days a deleted boardgame stays in the trash
+      --sort <ORDER>          Order of the boardgame list: name, weight, play-time, year or added
+      --keys <PRESET>         Start from the default, vim or emacs keys
+      --debug                 Let the d key show the app's internal state
+      --force                 Let init-config replace an existing file
+  -h, --help                  Print this help
+
+Settings are read from ~/.config/boardgame-manager/config.toml or another config directory,
+then from BOARDGAME_DATABASE, BOARDGAME_MESSAGE_TIMEOUT, BOARDGAME_TRASH_RETENTION_DAYS,
+BOARDGAME_SORT, BOARDGAME_KEYS and BOARDGAME_DEBUG, then from the options. BOARDGAME_CONFIG names a config
+file as --config does.
+";
+
+/// What the app was asked to do on the command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Box<Overrides>),
+    /// Lists the keys as they would be for a run.
+    Keys(Box<Overrides>),
+    /// Writes the boardgames as CSV to the file, or standard output.
+    Export { overrides: Box<Overrides>, path: Option<PathBuf> },
+    /// Adds the boardgames in a CSV file.
+    Import { overrides: Box<Overrides>, path: PathBuf },
+    InitConfig { path: Option<PathBuf>, force: bool },
+    Help,
+}
+
+/// What the command line sets for a run of the app.
+#[derive(Debug, Default, PartialEq)]
+pub struct Overrides {
+    /// The config file to read instead of looking in the config directories.
+    pub config: Option<PathBuf>,
+    pub(super) settings: Settings,
+}
+
+impl Command {
+    /// Reads the arguments after the program name, both `--option value` and `--option=value`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+        let mut args = args.into_iter();
+        let mut overrides = Overrides::default();
+        let mut subcommand: Option<String> = None;
+        let mut path = None;
+        let mut force = false;
+        while let Some(arg) = args.next() {
+            let (option, inline_value) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => (option.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value.clone().or_else(|| args.next()).ok_or_else(|| format!("{} needs a value", option))
+            };
+            let settings = &mut overrides.settings;
+            match option.as_str() {
+                "-h" | "--help" => return Ok(Command::Help),
+                "-c" | "--config" => overrides.config = Some(PathBuf::from(value()?)),
+                "--database" => settings.database = Some(PathBuf::from(value()?)),
+                "--message-timeout" => settings.message_timeout = Some(parse_value(&option, &value()?)?),
+                "--trash-retention-days" => settings.trash_retention_days = Some(parse_value(&option, &value()?)?),
+                "--sort" => settings.sort = Some(value()?),
+                "--keys" => settings.key_preset = Some(value()?),
+                "--debug" => settings.debug = Some(true),
+                "--force" => force = true,
+                "init-config" | "keys" | "export" | "import" if subcommand.is_none() => subcommand = Some(arg),
+                _ if subcommand.as_deref().is_some_and(|command| command != "keys")
+                    && path.is_none()
+                    && !arg.starts_with('-') =>
+                {
+                    path = Some(PathBuf::from(arg))
+                }
+                _ => return Err(format!("unexpected argument '{}', try --help", arg)),
+            }
+        }
+        let overrides = Box::new(overrides);
+        match subcommand.as_deref() {
+            Some("init-config") if *overrides != Overrides::default() => {
+                Err("init-config only takes --force and a path".to_string())
+            }
+            Some("init-config") => Ok(Command::InitConfig { path, force }),
+            _ if force => Err("--force only goes with init-config".to_string()),
+            Some("keys") => Ok(Command::Keys(overrides)),
+            Some("export") => Ok(Command::Export { overrides, path }),
+            Some("import") => match path {
+                Some(path) => Ok(Command::Import { overrides, path }),
+                None => Err("import needs a CSV file to read".to_string()),
+            },
+            _ => Ok(Command::Run(overrides)),
+        }
+    }
+}
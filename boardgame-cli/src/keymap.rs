@@ -0,0 +1,557 @@
+use std::{fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{app::{Mode, FOCUS_ONLY_KEYS}, keys::KeyChord};
+
+/// Something a key can be bound to. The item it acts on, if any, is the one selected on the
+/// screen it is pressed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCommand {
+    /// Quits without asking, and works while typing as Ctrl-C would stop other programs.
+    ForceQuit,
+    Suspend,
+    Quit,
+    Back,
+    Undo,
+    Redo,
+    Reload,
+    Debug,
+    Help,
+    Up,
+    Down,
+    Filter,
+    ShowTrash,
+    CustomFields,
+    RuleSearch,
+    Sort,
+    Open,
+    Edit,
+    Delete,
+    Vote,
+    AddImage,
+    Copies,
+    HouseRules,
+    History,
+    PreviousImage,
+    NextImage,
+    Restore,
+    Purge,
+    Audit,
+    AddOne,
+    RemoveOne,
+    Toggle,
+}
+
+impl KeyCommand {
+    /// The name the command is bound by in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyCommand::ForceQuit => "force-quit",
+            KeyCommand::Suspend => "suspend",
+            KeyCommand::Quit => "quit",
+            KeyCommand::Back => "back",
+            KeyCommand::Undo => "undo",
+            KeyCommand::Redo => "redo",
+            KeyCommand::Reload => "reload",
+            KeyCommand::Debug => "debug",
+            KeyCommand::Help => "help",
+            KeyCommand::Up => "up",
+            KeyCommand::Down => "down",
+            KeyCommand::Filter => "filter",
+            KeyCommand::ShowTrash => "trash",
+            KeyCommand::CustomFields => "custom-fields",
+            KeyCommand::RuleSearch => "rule-search",
+            KeyCommand::Sort => "sort",
+            KeyCommand::Open => "open",
+            KeyCommand::Edit => "edit",
+            KeyCommand::Delete => "delete",
+            KeyCommand::Vote => "vote",
+            KeyCommand::AddImage => "add-image",
+            KeyCommand::Copies => "copies",
+            KeyCommand::HouseRules => "house-rules",
+            KeyCommand::History => "history",
+            KeyCommand::PreviousImage => "previous-image",
+            KeyCommand::NextImage => "next-image",
+            KeyCommand::Restore => "restore",
+            KeyCommand::Purge => "purge",
+            KeyCommand::Audit => "audit",
+            KeyCommand::AddOne => "add-one",
+            KeyCommand::RemoveOne => "remove-one",
+            KeyCommand::Toggle => "toggle",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyCommand::ForceQuit => "Quit at once, even while typing",
+            KeyCommand::Suspend => "Suspend to the shell, even while typing",
+            KeyCommand::Quit => "Quit",
+            KeyCommand::Back => "Go back to the previous screen",
+            KeyCommand::Undo => "Undo the last change",
+            KeyCommand::Redo => "Redo the last undone change",
+            KeyCommand::Reload => "Load the data again after it failed to",
+            KeyCommand::Debug => "Show the app's internal state, with --debug",
+            KeyCommand::Help => "Show the keys and mouse actions for the screen",
+            KeyCommand::Up => "Select the previous item",
+            KeyCommand::Down => "Select the next item",
+            KeyCommand::Filter => "Filter the boardgames",
+            KeyCommand::ShowTrash => "Show the trash",
+            KeyCommand::CustomFields => "Manage the custom fields",
+            KeyCommand::RuleSearch => "Search the house rules",
+            KeyCommand::Sort => "Sort the boardgames the next way round",
+            KeyCommand::Open => "Open the selected boardgame",
+            KeyCommand::Edit => "Edit the boardgame",
+            KeyCommand::Delete => "Delete the selected item",
+            KeyCommand::Vote => "Vote on the best player counts",
+            KeyCommand::AddImage => "Add an image",
+            KeyCommand::Copies => "Show the copies",
+            KeyCommand::HouseRules => "Show the house rules",
+            KeyCommand::History => "Show the history of changes",
+            KeyCommand::PreviousImage => "Show the previous image",
+            KeyCommand::NextImage => "Show the next image",
+            KeyCommand::Restore => "Restore the selected boardgame",
+            KeyCommand::Purge => "Delete the selected boardgame for good",
+            KeyCommand::Audit => "Audit the selected copy",
+            KeyCommand::AddOne => "Count one more of the selected component",
+            KeyCommand::RemoveOne => "Count one less of the selected component",
+            KeyCommand::Toggle => "Turn the selected house rule on or off",
+        }
+    }
+
+    /// Whether the command's keys are taken even from a focused input, so they can't be keys
+    /// that type something.
+    pub fn works_in_inputs(self) -> bool {
+        matches!(self, KeyCommand::ForceQuit | KeyCommand::Suspend)
+    }
+}
+
+/// A set of keys to start from, which the config file can then change key by key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Preset {
+    #[default]
+    Default,
+    /// hjkl-style movement and Esc to go back.
+    Vim,
+    /// Ctrl-P and Ctrl-N to move and Ctrl-G to go back.
+    Emacs,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Default, Preset::Vim, Preset::Emacs];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Preset::Default => "default",
+            Preset::Vim => "vim",
+            Preset::Emacs => "emacs",
+        }
+    }
+
+    /// The bindings the preset changes from the default ones.
+    fn bindings(self) -> &'static [Entry] {
+        match self {
+            Preset::Default => &[],
+            Preset::Vim => VIM,
+            Preset::Emacs => EMACS,
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL.into_iter().find(|preset| preset.as_str().eq_ignore_ascii_case(s.trim())).ok_or_else(|| {
+            let presets = Preset::ALL.map(Preset::as_str).join(", ");
+            format!("unknown preset '{}', expected one of {}", s.trim(), presets)
+        })
+    }
+}
+
+/// Where a command's keys work, the screen it is bound on, or everywhere for `None`, then the
+/// command and its keys.
+type Entry = (Option<Mode>, KeyCommand, &'static [KeyChord]);
+
+const fn key(ch: char) -> KeyChord {
+    KeyChord::key(KeyCode::Char(ch))
+}
+
+const fn named(code: KeyCode) -> KeyChord {
+    KeyChord::key(code)
+}
+
+const DELETE: KeyChord = named(KeyCode::Delete);
+const ENTER: KeyChord = named(KeyCode::Enter);
+const ESC: KeyChord = named(KeyCode::Esc);
+const UP: KeyChord = named(KeyCode::Up);
+const DOWN: KeyChord = named(KeyCode::Down);
+
+/// Every command that can be bound, on the screens it can be bound on, with its default keys.
+/// The listings show them in this order.
+const DEFAULT: &[Entry] = &[
+    (None, KeyCommand::ForceQuit, &[KeyChord::ctrl('c')]),
+    (None, KeyCommand::Suspend, &[KeyChord::ctrl('z')]),
+    (None, KeyCommand::Quit, &[key('q')]),
+    (None, KeyCommand::Back, &[named(KeyCode::Backspace), ESC]),
+    (None, KeyCommand::Undo, &[key('u')]),
+    (None, KeyCommand::Redo, &[KeyChord::ctrl('r')]),
+    (None, KeyCommand::Reload, &[named(KeyCode::F(5))]),
+    (None, KeyCommand::Debug, &[key('d')]),
+    (None, KeyCommand::Help, &[key('?')]),
+    (None, KeyCommand::Up, &[UP]),
+    (None, KeyCommand::Down, &[DOWN]),
+    (Some(Mode::Main), KeyCommand::Open, &[ENTER]),
+    (Some(Mode::Main), KeyCommand::Edit, &[key('e')]),
+    (Some(Mode::Main), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::Main), KeyCommand::Filter, &[key('f')]),
+    (Some(Mode::Main), KeyCommand::Sort, &[key('s')]),
+    (Some(Mode::Main), KeyCommand::ShowTrash, &[key('t')]),
+    (Some(Mode::Main), KeyCommand::CustomFields, &[key('c')]),
+    (Some(Mode::Main), KeyCommand::RuleSearch, &[key('r')]),
+    (Some(Mode::Details), KeyCommand::Edit, &[key('e')]),
+    (Some(Mode::Details), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::Details), KeyCommand::Vote, &[key('v')]),
+    (Some(Mode::Details), KeyCommand::AddImage, &[key('i')]),
+    (Some(Mode::Details), KeyCommand::PreviousImage, &[key('[')]),
+    (Some(Mode::Details), KeyCommand::NextImage, &[key(']')]),
+    (Some(Mode::Details), KeyCommand::Copies, &[key('c')]),
+    (Some(Mode::Details), KeyCommand::HouseRules, &[key('h')]),
+    (Some(Mode::Details), KeyCommand::History, &[key('l')]),
+    (Some(Mode::Trash), KeyCommand::Restore, &[ENTER]),
+    (Some(Mode::Trash), KeyCommand::Purge, &[DELETE]),
+    (Some(Mode::CustomFields), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::Copies), KeyCommand::Audit, &[ENTER]),
+    (Some(Mode::Copies), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::Audit), KeyCommand::AddOne, &[key('+')]),
+    (Some(Mode::Audit), KeyCommand::RemoveOne, &[key('-')]),
+    (Some(Mode::Audit), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::HouseRules), KeyCommand::Toggle, &[key(' ')]),
+    (Some(Mode::HouseRules), KeyCommand::Delete, &[DELETE]),
+    (Some(Mode::RuleSearch), KeyCommand::Open, &[ENTER]),
+];
+
+const VIM: &[Entry] = &[
+    (None, KeyCommand::Back, &[ESC]),
+    (None, KeyCommand::Up, &[UP, key('k')]),
+    (None, KeyCommand::Down, &[DOWN, key('j')]),
+    (Some(Mode::Main), KeyCommand::Open, &[ENTER, key('l')]),
+    (Some(Mode::Main), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::Main), KeyCommand::Filter, &[key('f'), key('/')]),
+    (Some(Mode::Details), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::Trash), KeyCommand::Restore, &[ENTER, key('l')]),
+    (Some(Mode::Trash), KeyCommand::Purge, &[DELETE, key('x')]),
+    (Some(Mode::CustomFields), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::Copies), KeyCommand::Audit, &[ENTER, key('l')]),
+    (Some(Mode::Copies), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::Audit), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::HouseRules), KeyCommand::Delete, &[DELETE, key('x')]),
+    (Some(Mode::RuleSearch), KeyCommand::Open, &[ENTER, key('l')]),
+];
+
+const EMACS: &[Entry] = &[
+    (None, KeyCommand::Back, &[ESC, KeyChord::ctrl('g')]),
+    (None, KeyCommand::Up, &[UP, KeyChord::ctrl('p')]),
+    (None, KeyCommand::Down, &[DOWN, KeyChord::ctrl('n')]),
+    (Some(Mode::Main), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::Main), KeyCommand::Filter, &[key('f'), KeyChord::ctrl('s')]),
+    (Some(Mode::Details), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::Trash), KeyCommand::Purge, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::CustomFields), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::Copies), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::Audit), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+    (Some(Mode::HouseRules), KeyCommand::Delete, &[DELETE, KeyChord::ctrl('d')]),
+];
+
+/// A command and the keys bound to it, everywhere or on one screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub mode: Option<Mode>,
+    pub command: KeyCommand,
+    pub chords: Vec<KeyChord>,
+}
+
+/// Which command each key runs on each screen: a preset's keys, changed by the ones the config
+/// file binds.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    preset: Preset,
+    /// The bindings set over the preset's, in the order they were set.
+    overrides: Vec<Binding>,
+    bindings: Vec<Binding>,
+}
+
+/// Keymaps are the same if their keys are, however they were bound.
+impl PartialEq for Keymap {
+    fn eq(&self, other: &Self) -> bool {
+        self.bindings == other.bindings
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(Preset::Default)
+    }
+}
+
+impl Keymap {
+    pub fn new(preset: Preset) -> Self {
+        let mut keymap = Keymap { preset, overrides: Vec::new(), bindings: Vec::new() };
+        keymap.rebuild();
+        keymap
+    }
+
+    /// Starts from another preset, keeping the keys bound over it.
+    pub fn set_preset(&mut self, preset: Preset) {
+        self.preset = preset;
+        self.rebuild();
+    }
+
+    /// Binds a command by its name to `chords` instead of its current keys, which unbinds it if
+    /// they are empty. Commands bound everywhere have no `mode`.
+    pub fn bind(&mut self, mode: Option<Mode>, name: &str, chords: Vec<KeyChord>) -> Result<(), String> {
+        let command = DEFAULT
+            .iter()
+            .find(|(scope, command, _)| *scope == mode && command.name() == name)
+            .map(|(_, command, _)| *command)
+            .ok_or_else(|| {
+                let names: Vec<&str> = commands(mode).map(KeyCommand::name).collect();
+                if names.is_empty() {
+                    format!("unknown command '{}', no keys can be bound on this screen", name)
+                } else {
+                    format!("unknown command '{}', expected one of {}", name, names.join(", "))
+                }
+            })?;
+        if let Some(chord) = chords.iter().find(|chord| command.works_in_inputs() && types_text(chord)) {
+            return Err(format!("'{}' types text, but {} works while typing, so it needs ctrl or alt", chord, name));
+        }
+        self.overrides.retain(|binding| binding.mode != mode || binding.command != command);
+        self.overrides.push(Binding { mode, command, chords });
+        self.rebuild();
+        Ok(())
+    }
+
+    fn rebuild(&mut self) {
+        let preset = self.preset.bindings().iter().map(|&(mode, command, chords)| Binding {
+            mode,
+            command,
+            chords: chords.to_vec(),
+        });
+        let mut bindings: Vec<Binding> = DEFAULT
+            .iter()
+            .map(|&(mode, command, chords)| Binding { mode, command, chords: chords.to_vec() })
+            .collect();
+        for change in preset.chain(self.overrides.iter().cloned()) {
+            if let Some(binding) =
+                bindings.iter_mut().find(|binding| binding.mode == change.mode && binding.command == change.command)
+            {
+                binding.chords = change.chords;
+            }
+        }
+        self.bindings = bindings;
+    }
+
+    /// Checks that no key runs two commands on any screen, counting the keys that work
+    /// everywhere, and that none is taken by moving focus first.
+    pub fn check(&self) -> Result<(), String> {
+        let place = |scope: Option<Mode>| match scope {
+            Some(mode) => format!("on the {} screen", mode.name()),
+            None => "everywhere".to_string(),
+        };
+        for binding in &self.bindings {
+            if let Some(chord) = binding.chords.iter().find(|chord| FOCUS_ONLY_KEYS.contains(&chord.code)) {
+                return Err(format!(
+                    "'{}' is bound to both moving focus and {} {}",
+                    chord,
+                    binding.command.name(),
+                    place(binding.mode)
+                ));
+            }
+        }
+        let scopes = std::iter::once(None).chain(Mode::ALL.map(Some));
+        for scope in scopes {
+            let mut seen: Vec<(&KeyChord, &Binding)> = Vec::new();
+            // Clashes between keys for everywhere are only told once, not for every screen
+            for binding in self.bindings.iter().filter(|binding| binding.mode.is_none() || binding.mode == scope) {
+                for chord in &binding.chords {
+                    let clash = seen.iter().find(|(other, _)| same_key(chord, other));
+                    if let Some((_, other)) = clash.filter(|_| scope.is_none() || binding.mode.is_some()) {
+                        return Err(format!(
+                            "'{}' is bound to both {} and {} {}",
+                            chord,
+                            other.command.name(),
+                            binding.command.name(),
+                            place(scope)
+                        ));
+                    }
+                    seen.push((chord, binding));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The command a key runs on a screen, or for `None`, the command it runs everywhere.
+    pub fn lookup(&self, mode: Option<Mode>, key: &KeyEvent) -> Option<KeyCommand> {
+        let bound = |scope: Option<Mode>| {
+            self.bindings
+                .iter()
+                .find(|binding| binding.mode == scope && binding.chords.iter().any(|chord| chord.matches(key)))
+                .map(|binding| binding.command)
+        };
+        mode.and_then(|mode| bound(Some(mode))).or_else(|| bound(None))
+    }
+
+    /// The bindings for a screen, or for `None` the ones that work everywhere, in listing order.
+    pub fn bindings(&self, mode: Option<Mode>) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(move |binding| binding.mode == mode)
+    }
+
+    /// Every binding, by where it works, as the `keys` command prints it.
+    pub fn listing(&self) -> String {
+        let mut listing = format!("Keys from the {} preset and the config file.\n", self.preset);
+        for scope in std::iter::once(None).chain(Mode::ALL.map(Some)) {
+            if self.bindings(scope).next().is_none() {
+                continue;
+            }
+            let (place, section) = match scope {
+                Some(mode) => (format!("On the {} screen", mode.name()), format!("[keys.{}]", mode.name())),
+                None => ("Everywhere".to_string(), "[keys]".to_string()),
+            };
+            listing.push_str(&format!("\n{}, bound in {}:\n", place, section));
+            for binding in self.bindings(scope) {
+                listing.push_str(&format!(
+                    "  {:<20} {:<16} {}\n",
+                    chord_list(&binding.chords),
+                    binding.command.name(),
+                    binding.command.description()
+                ));
+            }
+        }
+        listing
+    }
+}
+
+/// The commands that can be bound on a screen, or everywhere for `None`.
+fn commands(mode: Option<Mode>) -> impl Iterator<Item = KeyCommand> {
+    DEFAULT.iter().filter(move |(scope, _, _)| *scope == mode).map(|(_, command, _)| *command)
+}
+
+/// Keys as a list is written, or "none" when a command is unbound.
+pub fn chord_list(chords: &[KeyChord]) -> String {
+    if chords.is_empty() {
+        return "none".to_string();
+    }
+    chords.iter().map(KeyChord::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Whether two chords are pressed the same way, which for characters leaves out Shift as
+/// `KeyChord::matches` does.
+fn same_key(a: &KeyChord, b: &KeyChord) -> bool {
+    let ignored = match a.code {
+        KeyCode::Char(_) => KeyModifiers::SHIFT,
+        _ => KeyModifiers::NONE,
+    };
+    a.code == b.code && a.modifiers - ignored == b.modifiers - ignored
+}
+
+fn types_text(chord: &KeyChord) -> bool {
+    matches!(chord.code, KeyCode::Char(_)) && !chord.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in Preset::ALL {
+            let keymap = Keymap::new(preset);
+            assert_eq!(keymap.check(), Ok(()), "{} preset", preset);
+            // Every command has a key in every preset
+            for binding in &keymap.bindings {
+                assert!(!binding.chords.is_empty(), "{} in the {} preset", binding.command.name(), preset);
+            }
+        }
+        assert_eq!(Preset::from_str(" Vim "), Ok(Preset::Vim));
+
+        let vim = Keymap::new(Preset::Vim);
+        assert_eq!(vim.lookup(Some(Mode::Main), &press(KeyCode::Char('j'))), Some(KeyCommand::Down));
+        assert_eq!(vim.lookup(Some(Mode::Main), &press(KeyCode::Backspace)), None);
+        let emacs = Keymap::new(Preset::Emacs);
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(emacs.lookup(Some(Mode::Trash), &ctrl_p), Some(KeyCommand::Up));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut keymap = Keymap::default();
+        let c = press(KeyCode::Char('c'));
+        assert_eq!(keymap.lookup(Some(Mode::Main), &c), Some(KeyCommand::CustomFields));
+        assert_eq!(keymap.lookup(Some(Mode::Details), &c), Some(KeyCommand::Copies));
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &c), None);
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &press(KeyCode::Char('q'))), Some(KeyCommand::Quit));
+        // Shift is left out for characters, however the terminal reports it
+        let shifted = KeyEvent::new(KeyCode::Char('+'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.lookup(Some(Mode::Audit), &shifted), Some(KeyCommand::AddOne));
+
+        keymap.bind(Some(Mode::Trash), "restore", vec!["r".parse().unwrap(), "enter".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &press(KeyCode::Char('r'))), Some(KeyCommand::Restore));
+        // Binding again replaces the keys rather than adding to them
+        keymap.bind(Some(Mode::Trash), "restore", vec!["o".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &press(KeyCode::Enter)), None);
+        // and a new preset keeps them
+        keymap.set_preset(Preset::Vim);
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &press(KeyCode::Char('o'))), Some(KeyCommand::Restore));
+        assert_eq!(keymap.lookup(Some(Mode::Trash), &press(KeyCode::Char('l'))), None);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Some(Mode::Details), "history", vec!["v".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Err("'v' is bound to both vote and history on the details screen".to_string()));
+        keymap.bind(Some(Mode::Details), "vote", vec![]).unwrap();
+        assert_eq!(keymap.check(), Ok(()));
+
+        // Keys for one screen clash with the ones for every screen, but not with other screens'
+        keymap.bind(Some(Mode::Copies), "audit", vec!["shift-u".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Err("'shift-u' is bound to both undo and audit on the copies screen".to_string()));
+        keymap.bind(Some(Mode::Copies), "audit", vec!["a".parse().unwrap()]).unwrap();
+        keymap.bind(Some(Mode::Audit), "add-one", vec!["a".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Ok(()));
+
+        // Tab and Shift-Tab always move focus, but the keys focus only uses while something is
+        // focused go to the bindings otherwise
+        keymap.bind(Some(Mode::Main), "sort", vec!["shift-backtab".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Err("'shift-backtab' is bound to both moving focus and sort on the main screen".to_string()));
+        keymap.bind(None, "help", vec!["tab".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Err("'tab' is bound to both moving focus and help everywhere".to_string()));
+        keymap.bind(Some(Mode::Main), "sort", vec!["right".parse().unwrap()]).unwrap();
+        keymap.bind(None, "help", vec!["f1".parse().unwrap()]).unwrap();
+        assert_eq!(keymap.check(), Ok(()));
+    }
+
+    #[test]
+    fn test_listing() {
+        let mut keymap = Keymap::default();
+        keymap.bind(None, "reload", vec![]).unwrap();
+        let listing = keymap.listing();
+        assert!(listing.starts_with("Keys from the default preset and the config file.\n\nEverywhere, bound in [keys]:\n"));
+        assert!(listing.contains("\n  backspace, esc       back             Go back to the previous screen\n"));
+        assert!(listing.contains("\n  none                 reload           "));
+        assert!(listing.contains("\nOn the house-rules screen, bound in [keys.house-rules]:\n  space                toggle"));
+        // Screens without keys of their own are left out
+        assert!(!listing.contains("voting"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Keys with a name rather than the character they type, by the name they are written with.
+const NAMED_KEYS: [(&str, KeyCode); 16] = [
+    ("backspace", KeyCode::Backspace),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("space", KeyCode::Char(' ')),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 3] =
+    [("ctrl", KeyModifiers::CONTROL), ("alt", KeyModifiers::ALT), ("shift", KeyModifiers::SHIFT)];
+
+/// A key with the modifiers held with it, written like `q`, `f5`, `ctrl-r` or `shift-tab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        KeyChord { code, modifiers }
+    }
+
+    pub const fn key(code: KeyCode) -> Self {
+        KeyChord::new(code, KeyModifiers::NONE)
+    }
+
+    pub const fn ctrl(ch: char) -> Self {
+        KeyChord::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+    }
+
+    /// Whether a key press is this chord. Terminals report Shift with characters it changed,
+    /// so it is left out for them: `Q` is matched however the terminal sends it.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let ignored = match key.code {
+            KeyCode::Char(_) => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        self.code == key.code && (self.modifiers - ignored) == (key.modifiers & relevant) - ignored
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}-")?;
+            }
+        }
+        match self.code {
+            KeyCode::F(n) => write!(f, "f{n}"),
+            KeyCode::Char(ch) if ch != ' ' => write!(f, "{ch}"),
+            code => {
+                let name = NAMED_KEYS.iter().find(|(_, named)| *named == code).map_or("?", |(name, _)| *name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        // The last part is the key itself, so `ctrl--` is Ctrl and minus
+        while let Some((prefix, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            let (_, modifier) = MODIFIERS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(prefix))
+                .ok_or_else(|| format!("unknown modifier '{}' in '{}', expected ctrl, alt or shift", prefix, s.trim()))?;
+            modifiers |= *modifier;
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => {
+                let name = rest.to_ascii_lowercase();
+                let function_key = name.strip_prefix('f').and_then(|n| n.parse().ok()).filter(|n| (1..=12).contains(n));
+                match function_key {
+                    Some(n) => KeyCode::F(n),
+                    None => NAMED_KEYS
+                        .iter()
+                        .find(|(named, _)| *named == name)
+                        .map(|(_, code)| *code)
+                        .ok_or_else(|| format!("unknown key '{}'", rest))?,
+                }
+            }
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
@@ -1,37 +1,75 @@
 mod app;
+mod config;
+mod keymap;
+mod keys;
+mod terminal;
+#[cfg(test)]
+mod tests;
 mod ui;
 mod widgets;
 
-use std::io;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter},
 };
-use ratatui::{prelude::*, Terminal};
-use app::App;
-
-const DB_PATH: &str = "boardgame.db";
-
-fn main() -> Result<(), io::Error> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
 
-    // Run app
-    App::new(DB_PATH).run(&mut terminal)?;
+use anyhow::Context;
+use boardgame_core::db::BoardgameDb;
+use app::App;
+use config::{Command, Config};
+use terminal::TerminalGuard;
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+fn main() -> anyhow::Result<()> {
+    let command = Command::parse(std::env::args().skip(1)).map_err(anyhow::Error::msg)?;
+    let overrides = match command {
+        Command::Run(overrides) => *overrides,
+        Command::Keys(overrides) => {
+            print!("{}", Config::load(*overrides)?.keys.listing());
+            return Ok(());
+        }
+        Command::Export { overrides, path } => {
+            let db = open_database(&Config::load(*overrides)?)?;
+            let exported = match &path {
+                Some(path) => {
+                    let file = File::create(path).with_context(|| format!("couldn't create {}", path.display()))?;
+                    db.export_csv(BufWriter::new(file))
+                }
+                None => db.export_csv(io::stdout().lock()),
+            };
+            let exported = exported.context("couldn't export the boardgames")?;
+            eprintln!("Exported {} boardgames", exported);
+            return Ok(());
+        }
+        Command::Import { overrides, path } => {
+            let db = open_database(&Config::load(*overrides)?)?;
+            let file = File::open(&path).with_context(|| format!("couldn't open {}", path.display()))?;
+            let imported = db
+                .import_csv(BufReader::new(file))
+                .with_context(|| format!("couldn't import {}", path.display()))?;
+            println!("Imported {} boardgames from {}", imported.len(), path.display());
+            return Ok(());
+        }
+        Command::InitConfig { path, force } => {
+            let path = config::init(path, force)?;
+            println!("Wrote the default settings to {}", path.display());
+            return Ok(());
+        }
+        Command::Help => {
+            print!("{}", config::USAGE);
+            return Ok(());
+        }
+    };
+    let config = Config::load(overrides)?;
+    let database = config.database.clone();
+    let mut app = App::new(config).with_context(|| format!("couldn't open the database {}", database.display()))?;
 
+    terminal::install_panic_hook();
+    // Dropping the guard restores the terminal, so an error is printed to the shell after it
+    let mut terminal = TerminalGuard::new().context("couldn't set up the terminal")?;
+    app.run(&mut terminal)?;
     Ok(())
 }
+
+fn open_database(config: &Config) -> anyhow::Result<BoardgameDb> {
+    BoardgameDb::new(&config.database).with_context(|| format!("couldn't open the database {}", config.database.display()))
+}
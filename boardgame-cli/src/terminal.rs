@@ -0,0 +1,132 @@
+use std::{
+    io::{self, Stdout},
+    ops::{Deref, DerefMut},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{prelude::CrosstermBackend, Terminal};
+use signal_hook::{consts::TERM_SIGNALS, flag};
+
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// Takes over the terminal for the app and gives it back to the shell when dropped, whether
+/// the app quit, returned an error or panicked.
+pub struct TerminalGuard {
+    terminal: Tui,
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<Self, io::Error> {
+        enter()?;
+        // Give the terminal back if creating it fails too
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout())).inspect_err(|_| {
+            let _ = restore();
+        })?;
+        Ok(TerminalGuard { terminal })
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Tui;
+
+    fn deref(&self) -> &Tui {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Tui {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+fn enter() -> Result<(), io::Error> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)
+}
+
+/// Leaves raw mode and the alternate screen. Every step is tried even if one fails, so as much
+/// of the shell as possible is usable again.
+fn restore() -> Result<(), io::Error> {
+    let raw_mode = disable_raw_mode();
+    let screen = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste, Show);
+    raw_mode.and(screen)
+}
+
+/// Restores the terminal before a panic's message is printed, which would otherwise be lost
+/// with the alternate screen and mangled by raw mode.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+}
+
+/// Gives the terminal back and stops the process like Ctrl-Z in a shell would, taking the
+/// terminal over again once the shell resumes it.
+#[cfg(unix)]
+pub fn suspend(terminal: &mut Tui) -> Result<(), io::Error> {
+    restore()?;
+    signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+    enter()?;
+    // Whatever the shell drew since is still on screen, so draw everything again
+    terminal.clear()
+}
+
+#[cfg(not(unix))]
+pub fn suspend(_terminal: &mut Tui) -> Result<(), io::Error> {
+    Ok(())
+}
+
+/// Signals that ask the app to quit or suspend, noted as they arrive so the main loop can act
+/// on them between frames.
+pub struct Signals {
+    quit: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+}
+
+impl Signals {
+    pub fn register() -> Result<Self, io::Error> {
+        let quit = Arc::new(AtomicBool::new(false));
+        let suspend = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        let quit_signals = TERM_SIGNALS.iter().chain([&signal_hook::consts::SIGHUP]);
+        #[cfg(not(unix))]
+        let quit_signals = TERM_SIGNALS.iter();
+        for &signal in quit_signals {
+            // Only noted, never acted on in the handler, so the terminal is always restored on the
+            // way out
+            flag::register(signal, Arc::clone(&quit))?;
+        }
+        #[cfg(unix)]
+        flag::register(signal_hook::consts::SIGTSTP, Arc::clone(&suspend))?;
+        Ok(Signals { quit, suspend })
+    }
+
+    /// Whether a quit signal arrived since this was last asked.
+    pub fn take_quit(&self) -> bool {
+        self.quit.swap(false, Ordering::Relaxed)
+    }
+
+    /// Whether a suspend signal arrived since this was last asked.
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,374 @@
+//! Drives the app headlessly: scripted keys and clicks go through the same event handling as
+//! the terminal's, frames are drawn to a `TestBackend` and checked against the snapshots in
+//! `src/snapshots`. Run `cargo insta review` after changing what a screen draws.
+
+use std::time::{Duration, Instant};
+
+use boardgame_core::db::{Boardgame, BoardgameDb};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use insta::assert_snapshot;
+use ratatui::{backend::TestBackend, layout::Position, Terminal};
+use tempfile::{tempdir, TempDir};
+
+use crate::{
+    app::{App, Mode},
+    config::Config,
+    keymap::{Keymap, Preset},
+};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 40;
+
+/// An app on a fresh database in a temporary directory, drawn after every event.
+struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    /// A second connection to the app's database, to check what was stored.
+    db: BoardgameDb,
+    _dir: TempDir,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Harness::with_config(Config::default())
+    }
+
+    /// A harness for an app with `config`, on a new database whatever it names.
+    fn with_config(config: Config) -> Self {
+        let dir = tempdir().expect("failed to create temp directory");
+        let path = dir.path().join("test.db");
+        let app = App::new(Config { database: path.clone(), ..config }).expect("failed to open test database");
+        let db = BoardgameDb::new(&path).expect("failed to open test database");
+        let terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminals can't fail");
+        let mut harness = Harness { app, terminal, db, _dir: dir };
+        harness.draw();
+        harness
+    }
+
+    fn draw(&mut self) {
+        self.app.draw(&mut self.terminal).expect("test terminals can't fail");
+    }
+
+    fn event(&mut self, event: Event) {
+        self.app.handle_event(event);
+        self.draw();
+    }
+
+    fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE);
+    }
+
+    fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    fn type_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.press(KeyCode::Char(ch));
+        }
+    }
+
+    fn click(&mut self, position: Position) {
+        self.event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: position.x,
+            row: position.y,
+            modifiers: KeyModifiers::NONE,
+        }));
+    }
+
+    /// Clicks the first place `text` is drawn, such as a button's label or an input's title.
+    fn click_on(&mut self, text: &str) {
+        let position = self.find(text).unwrap_or_else(|| panic!("'{}' is not on screen:\n{}", text, self.screen()));
+        self.click(position);
+    }
+
+    /// Clicks into the input titled `label` and types `text`.
+    fn fill(&mut self, label: &str, text: &str) {
+        self.click_on(label);
+        self.type_text(text);
+    }
+
+    fn find(&self, text: &str) -> Option<Position> {
+        let buffer = self.terminal.backend().buffer();
+        (0..buffer.area.height).find_map(|y| {
+            let row: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+            // Every cell here is one column wide, so a byte offset in the row is a column
+            let x = row.find(text)?;
+            Some(Position::new(row[..x].chars().count() as u16, y))
+        })
+    }
+
+    fn screen(&self) -> String {
+        self.terminal.backend().to_string()
+    }
+
+    /// Ages the oldest message past any timeout, as if it had been on screen for a while.
+    fn age_message(&mut self) {
+        if let Some((_, sent)) = self.app.messages.borrow_mut().front_mut() {
+            *sent = Instant::now() - Duration::from_secs(60 * 60);
+        }
+    }
+
+    fn boardgames(&self) -> Vec<Boardgame> {
+        self.db.get_all_boardgames().expect("failed to read boardgames")
+    }
+}
+
+#[test]
+fn test_start() {
+    let harness = Harness::new();
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    assert_snapshot!(harness.screen());
+}
+
+#[test]
+fn test_add_boardgame() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Adding));
+    assert_snapshot!("add_form_empty", harness.screen());
+
+    harness.fill("Name", "Azul");
+    harness.fill("Min players", "2");
+    harness.fill("Max players", "4");
+    harness.fill("Play time", "45");
+    // Weight steps by tenths, and a digit over the heaviest isn't typed
+    harness.fill("Weight", "73.5+");
+    harness.fill("Description", "Tile drafting\nand pattern building");
+    assert_snapshot!("add_form_filled", harness.screen());
+
+    // The button, not the title
+    harness.click_on(" Add  ");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    let boardgames = harness.boardgames();
+    assert_eq!(boardgames.len(), 1);
+    let azul = &boardgames[0];
+    assert_eq!((azul.name.as_str(), azul.min_players, azul.max_players, azul.play_time_minutes), ("Azul", 2, 4, 45));
+    assert_eq!(azul.description, "Tile drafting\nand pattern building");
+    assert_eq!(azul.weight, Some(3.6));
+    assert_snapshot!("add_boardgame_added", harness.screen());
+}
+
+#[test]
+fn test_add_boardgame_invalid() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    harness.fill("Name", "Azul");
+    harness.click_on(" Add  ");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Adding), "the form stays open to fix");
+    assert!(harness.find("'Min players' is required").is_some(), "{}", harness.screen());
+    assert!(harness.boardgames().is_empty());
+}
+
+#[test]
+fn test_edit_boardgame() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    harness.fill("Name", "Azul");
+    harness.fill("Min players", "2");
+    harness.fill("Max players", "4");
+    harness.fill("Play time", "45");
+    harness.fill("Designers", "Michael Kiesling");
+    harness.click_on(" Add  ");
+
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Char('e'));
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Editing));
+    assert_snapshot!("edit_form", harness.screen());
+    // Max players is a spinner, so + steps it up
+    harness.fill("Max players", "+");
+    harness.fill("Artists", "Chris Quilliams");
+    harness.click_on(" Save ");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    assert!(harness.find("Saved Azul").is_some(), "{}", harness.screen());
+    let boardgames = harness.boardgames();
+    assert_eq!((boardgames.len(), boardgames[0].max_players), (1, 5));
+    let id = boardgames[0].id.unwrap();
+    let names = |db: &BoardgameDb| db.get_credits(id).unwrap().into_iter().map(|credit| credit.name).collect::<Vec<_>>();
+    assert_eq!(names(&harness.db), ["Michael Kiesling", "Chris Quilliams"]);
+
+    // Undo puts back the boardgame and its credits as they were
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.boardgames()[0].max_players, 4);
+    assert_eq!(names(&harness.db), ["Michael Kiesling"]);
+}
+
+#[test]
+fn test_edit_description() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    harness.fill("Name", "Azul");
+    harness.fill("Min players", "2");
+    harness.fill("Max players", "4");
+    harness.fill("Play time", "45");
+    harness.fill("Description", "Tile drafting\nand pattern building");
+    harness.click_on(" Add  ");
+
+    // The description opens as it was written, line for line, and Enter starts a new line in it
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Char('e'));
+    assert!(harness.find("and pattern building").is_some(), "{}", harness.screen());
+    harness.click_on("and pattern building");
+    harness.press(KeyCode::End);
+    harness.press(KeyCode::Enter);
+    harness.type_text("for 2-4 players");
+    harness.press(KeyCode::Up);
+    harness.press(KeyCode::Up);
+    harness.press(KeyCode::Home);
+    harness.type_text("Quick ");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Editing), "Enter and the arrows stay in the description");
+    harness.click_on(" Save ");
+    assert_eq!(harness.boardgames()[0].description, "Quick Tile drafting\nand pattern building\nfor 2-4 players");
+}
+
+#[test]
+fn test_more_by() {
+    let mut harness = Harness::new();
+    for name in ["Azul", "Heaven & Ale"] {
+        harness.click_on("Add Boardgame");
+        harness.fill("Name", name);
+        harness.fill("Min players", "2");
+        harness.fill("Max players", "4");
+        harness.fill("Play time", "45");
+        harness.fill("Designers", "Michael Kiesling");
+        harness.click_on(" Add  ");
+    }
+
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Details));
+    assert!(harness.find("More by Michael Kiesling: Heaven & Ale").is_some(), "{}", harness.screen());
+}
+
+#[test]
+fn test_purge_forgets_undo() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    harness.fill("Name", "Azul");
+    harness.fill("Min players", "2");
+    harness.fill("Max players", "4");
+    harness.fill("Play time", "45");
+    harness.click_on(" Add  ");
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Delete);
+
+    harness.press(KeyCode::Char('t'));
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Delete);
+    assert!(harness.find("Permanently deleted Azul").is_some(), "{}", harness.screen());
+    // Neither the delete nor the add can be undone, since there is nothing left to restore
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.get_messages().back().map(|(message, _)| message.as_str()), Some("Nothing to undo"));
+}
+
+#[test]
+fn test_quit_confirmation() {
+    let mut harness = Harness::new();
+    harness.press(KeyCode::Char('q'));
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Quitting));
+    assert_snapshot!(harness.screen());
+
+    harness.click_on("No");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    assert!(!harness.app.state.should_quit);
+
+    // The quit button in the title asks too, and the keyboard can answer
+    harness.click_on("Quit");
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Quitting));
+    harness.press(KeyCode::Tab);
+    harness.press(KeyCode::Tab);
+    let yes = harness.find("Yes").expect("the quit screen asks");
+    assert!(harness.app.state.focused_button.is_some_and(|button| button.contains(yes)));
+    harness.press(KeyCode::Enter);
+    assert!(harness.app.state.should_quit);
+}
+
+#[test]
+fn test_ctrl_c_and_ctrl_z() {
+    let mut harness = Harness::new();
+    harness.click_on("Add Boardgame");
+    harness.fill("Name", "Az");
+    harness.press_with(KeyCode::Char('z'), KeyModifiers::CONTROL);
+    assert!(harness.app.state.should_suspend, "suspends even while typing");
+    assert_eq!(harness.app.state.input_state["Name"], "Az");
+    harness.press_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(harness.app.state.should_quit, "quits without asking");
+}
+
+#[test]
+fn test_message_timeout() {
+    let mut harness = Harness::new();
+    harness.press(KeyCode::Char('z'));
+    harness.press(KeyCode::Char('x'));
+    assert_snapshot!(harness.screen());
+
+    // Messages leave one at a time, oldest first, and are gone from the next frame
+    harness.age_message();
+    harness.draw();
+    assert_eq!(harness.app.get_messages().len(), 1);
+    harness.draw();
+    assert!(harness.find("Char('z')").is_none());
+    assert!(harness.find("Char('x')").is_some(), "a newer message waits out its own timeout");
+    harness.draw();
+    assert!(harness.find("Char('x')").is_some());
+    harness.age_message();
+    harness.draw();
+    harness.draw();
+    assert!(harness.app.get_messages().is_empty());
+    assert!(harness.find("Unhandled key").is_none());
+}
+
+#[test]
+fn test_remapped_keys() {
+    let mut keys = Keymap::new(Preset::Vim);
+    keys.bind(Some(Mode::Main), "filter", vec!["ctrl-f".parse().unwrap()]).unwrap();
+    keys.bind(Some(Mode::Main), "sort", vec!["o".parse().unwrap()]).unwrap();
+    let mut harness = Harness::with_config(Config { keys, ..Config::default() });
+    assert!(harness.find("(o to sort, delete to delete, ? for help)").is_some(), "hints follow the keys");
+
+    harness.press(KeyCode::Char('f'));
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    harness.press_with(KeyCode::Char('f'), KeyModifiers::CONTROL);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Filtering));
+
+    // Vim goes back with Esc, leaving Backspace to edit text
+    harness.press(KeyCode::Backspace);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Filtering));
+    harness.press(KeyCode::Esc);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    // The preset's / for filter was replaced
+    harness.press(KeyCode::Char('/'));
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+    assert!(harness.find("Unhandled key: Char('/')").is_some());
+}
+
+#[test]
+fn test_help() {
+    let mut harness = Harness::new();
+    harness.press(KeyCode::Char('?'));
+    assert_snapshot!(harness.screen());
+    assert!(harness.find("Sort the boardgames the next way round").is_some());
+    assert!(harness.find("Press Add Boardgame").is_some(), "lists the buttons drawn");
+    assert!(harness.find("internal state").is_none(), "debug is off");
+    assert!(harness.find("Go back").is_none(), "there is nowhere to go back to");
+
+    // Keys only scroll or close it, rather than acting on the screen underneath
+    harness.press(KeyCode::Down);
+    assert_eq!(harness.app.state.help, Some(0), "it fits, so there is nothing to scroll");
+    harness.press(KeyCode::Char('q'));
+    assert_eq!(harness.app.state.help, None);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Main));
+
+    // Each screen lists its own keys, and a click anywhere closes it
+    harness.click_on("Add Boardgame");
+    harness.press(KeyCode::Char('?'));
+    assert!(harness.find("Help for the adding screen").is_some());
+    assert!(harness.find("Keys on this screen").is_none());
+    assert!(harness.find("Select the previous item").is_none(), "there is no list to move through");
+    assert!(harness.find("Go back").is_some());
+    assert!(harness.find("Type in it").is_some());
+    harness.click(Position::new(0, 0));
+    assert_eq!(harness.app.state.help, None);
+    assert_eq!(harness.app.get_curr_mode(), Some(Mode::Adding));
+}
@@ -1,21 +1,103 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 use ratatui::{prelude::*, widgets::*};
-use boardgame_core::strings::BG_FIELDS;
-use crate::{app::Mode, widgets::button::Button, App};
+use boardgame_core::{
+    db::{
+        BoardgameSort, Component, CreditRole, CustomFieldType, HistoryAction, HistoryEntry, ImageInfo, ImageKind, Mechanic,
+        PlayerCountVote,
+    },
+    strings::{BG_CREDIT_FIELDS, PLACEHOLDERS},
+};
+use crate::{
+    app::{Action, Focus, Mode, FOCUS_KEYS},
+    config::Theme,
+    keymap::{self, Binding, KeyCommand},
+    widgets::{
+        button::Button,
+        image::{HalfBlockImage, ImagePlaceholder, ImageProtocol},
+        markdown::markdown_lines,
+        checkbox::{self, Checkbox},
+        date_picker::{self, Calendar, DatePicker},
+        form::{Field, FieldKind, Form},
+        select::{self, Select, SelectList},
+        spinner::Spinner,
+        text_input::{TextInput, TextInputState},
+        textarea::{TextArea, TextAreaState},
+    },
+    App,
+};
 
-pub fn render(frame: &mut Frame, app: &mut App) {
+/// Columns given to the image beside a boardgame's details.
+const IMAGE_WIDTH: u16 = 32;
+
+/// Rows given to each textarea, its borders included.
+const TEXTAREA_HEIGHT: u16 = 7;
+
+/// What a frame drew that the app needs afterwards. Drawing only reads the app, which keeps
+/// this once the frame is done.
+#[derive(Debug, Default)]
+pub struct View {
+    /// Buttons by where they are drawn, with what pressing them does and their label.
+    pub buttons: Vec<(Rect, Action, String)>,
+    /// Inputs by where they are drawn, by label.
+    pub inputs: Vec<(Rect, String)>,
+    /// Where the frame wants a kitty image drawn.
+    pub image_placement: Option<(i64, Rect)>,
+    /// Where the screen's list and inputs scrolled to, for the next frame to start from.
+    pub list: Option<ListState>,
+    pub text_inputs: HashMap<String, TextInputState>,
+    pub textareas: HashMap<String, TextAreaState>,
+    /// How far the help scrolled, kept within its lines.
+    pub help: Option<u16>,
+}
+
+impl View {
+    /// What clicking at `position` does: focusing the input or pressing the button there.
+    pub fn hit_test(&self, position: Position) -> Option<Action> {
+        if let Some((_, name)) = self.inputs.iter().find(|(area, _)| area.contains(position)) {
+            return Some(Action::SetFocus(Some(Focus::Input(name.clone()))));
+        }
+        self.buttons.iter().find(|(area, _, _)| area.contains(position)).map(|(_, action, _)| action.clone())
+    }
+
+    /// What pressing the button drawn at `area` does.
+    pub fn button_action(&self, area: Rect) -> Option<Action> {
+        self.buttons.iter().find(|(button, _, _)| *button == area).map(|(_, action, _)| action.clone())
+    }
+}
+
+/// Draws the current screen. Whatever it draws is all that can be clicked or focused.
+pub fn render(frame: &mut Frame, app: &App) -> View {
+    let mut drawn = View::default();
+    let view = &mut drawn;
     if let Some(mode) = app.get_curr_mode() {
         match mode {
-            Mode::Main => render_main(frame, app),
-            Mode::Adding => render_adding(frame, app),
-            Mode::Quitting => render_quitting(frame, app),
+            Mode::Main => render_main(frame, app, view),
+            Mode::Adding => render_adding(frame, app, view),
+            Mode::Editing => render_editing(frame, app, view),
+            Mode::Details => render_details(frame, app, view),
+            Mode::Filtering => render_filtering(frame, app, view),
+            Mode::Voting => render_voting(frame, app, view),
+            Mode::CustomFields => render_custom_fields(frame, app, view),
+            Mode::AddingImage => render_adding_image(frame, app, view),
+            Mode::Copies => render_copies(frame, app, view),
+            Mode::Audit => render_audit(frame, app, view),
+            Mode::HouseRules => render_house_rules(frame, app, view),
+            Mode::RuleSearch => render_rule_search(frame, app, view),
+            Mode::Trash => render_trash(frame, app, view),
+            Mode::History => render_history(frame, app, view),
+            Mode::Quitting => render_quitting(frame, app, view),
+        }
+        add_popup(frame, app, view);
+        if let Some(scroll) = app.state.help {
+            add_help(mode, scroll, frame, app, view);
         }
     } else {
         panic!("no mode")
     }
+    drawn
 }
 
-fn render_quitting(frame: &mut Frame, app: &mut App) {
+fn render_quitting(frame: &mut Frame, app: &App, view: &mut View) {
     let constraints = [
         Constraint::Min(2),
         Constraint::Length(3),
@@ -28,6 +110,7 @@ fn render_quitting(frame: &mut Frame, app: &mut App) {
         vertical_layout[1],
         frame,
         app,
+        view,
         false,
     );
     let button_line = Layout::default()
@@ -35,72 +118,612 @@ fn render_quitting(frame: &mut Frame, app: &mut App) {
         .constraints([Constraint::Min(10); 2])
         .split(vertical_layout[2]);
     add_button(
-        Button::new("Yes").green(),
+        Button::new("Yes").color(app.theme().positive),
         button_line[0],
-        App::quit,
+        Action::Quit,
         frame,
         app,
+        view,
     );
     add_button(
-        Button::new("No").red(),
+        Button::new("No").color(app.theme().negative),
         button_line[1],
-        App::prev_mode,
+        Action::Back,
         frame,
         app,
+        view,
     );
-    // add_messages(app, vertical_layout[3], frame);
+    // add_messages(app, view, vertical_layout[3], frame);
+}
+
+fn render_adding(frame: &mut Frame, app: &App, view: &mut View) {
+    render_boardgame_form("Add new boardgame...", "Add", Action::AddBoardgame, frame, app, view);
+}
+
+fn render_editing(frame: &mut Frame, app: &App, view: &mut View) {
+    let Some(boardgame) = app.get_edited_boardgame() else {
+        let vertical_layout = create_vertical_layout(frame.area(), &[Constraint::Length(3), Constraint::Min(2), Constraint::Length(5)]);
+        add_title("Boardgame not found", vertical_layout[0], frame, app, view, false);
+        add_messages(app, view, vertical_layout[2], frame);
+        return;
+    };
+    let title = format!("Edit {}...", boardgame.name);
+    let save = Action::SaveBoardgame(boardgame.id.expect("saved boardgame"));
+    render_boardgame_form(&title, "Save", save, frame, app, view);
 }
 
-fn render_adding(frame: &mut Frame, app: &mut App) {
+/// The boardgame form, shared by adding and editing, with the button that submits it.
+fn render_boardgame_form(title: &str, button: &str, action: Action, frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
     let vertical_layout = create_vertical_layout(
         frame.area(),
         &[
             Constraint::Length(3), // Title
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Button
             Constraint::Min(2),
             Constraint::Length(5), // Messages
         ],
     );
-    add_title(
-        "Add new boardgame...",
-        vertical_layout[0],
-        frame,
-        app,
-        false,
+    add_title(title, vertical_layout[0], frame, app, view, false);
+    add_inputs(&form, vertical_layout[1], frame, app, view);
+    add_button(Button::new(button).color(app.theme().positive), vertical_layout[2], action, frame, app, view);
+    add_messages(app, view, *vertical_layout.last().expect("no constraint"), frame);
+}
+
+fn render_filtering(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Buttons
+            Constraint::Min(2),
+            Constraint::Length(5), // Messages
+        ],
     );
-    for (i, name) in BG_FIELDS
-        .into_iter()
-        .enumerate()
-    {
-        let row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Fill(1)])
-            .split(vertical_layout[i + 1]);
-        let text = app.state.input_state.get(name).cloned().unwrap_or_default();
-        let mut input = Paragraph::new(text).block(
+    add_title("Filter boardgames...", vertical_layout[0], frame, app, view, false);
+    add_inputs(&form, vertical_layout[1], frame, app, view);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 2])
+        .split(vertical_layout[2]);
+    add_button(Button::new("Apply").color(app.theme().positive), button_line[0], Action::ApplyFilter, frame, app, view);
+    add_button(Button::new("Clear").color(app.theme().negative), button_line[1], Action::ClearFilter, frame, app, view);
+    add_messages(app, view, *vertical_layout.last().expect("no constraint"), frame);
+}
+
+fn render_custom_fields(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Fields
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Button
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("Custom fields", vertical_layout[0], frame, app, view, false);
+    let fields = app.get_custom_fields();
+    let field_list = List::new(fields.iter().map(|field| {
+        let options = match &field.field_type {
+            CustomFieldType::Enum(options) => format!(": {}", options.join(", ")),
+            _ => String::new(),
+        };
+        ListItem::new(format!("{} ({}{})", field.name, field.field_type.kind(), options))
+    }))
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title("Fields", Mode::CustomFields, &[(KeyCommand::Delete, "to delete")], app)),
+    );
+    let mut list_state = app.state.custom_field_list.clone();
+    frame.render_stateful_widget(field_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    add_inputs(&form, vertical_layout[2], frame, app, view);
+    add_button(Button::new("Add field").color(app.theme().positive), vertical_layout[3], Action::AddCustomField, frame, app, view);
+    add_messages(app, view, vertical_layout[4], frame);
+}
+
+fn render_copies(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Copies
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Button
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("Copies", vertical_layout[0], frame, app, view, false);
+    let copies = app.get_copies();
+    let copy_list = List::new(copies.iter().map(|copy| {
+        let components = copy.id.map(|id| app.get_components(id)).unwrap_or_default();
+        let missing: i32 = components.iter().map(Component::missing).sum();
+        let audited = copy
+            .last_audited_at
+            .map_or("never audited".to_string(), |at| format!("audited {}", at.format("%Y-%m-%d")));
+        ListItem::new(format!("{} - {} missing pieces, {}", copy.label(), missing, audited))
+    }))
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title("Copies", Mode::Copies, &[(KeyCommand::Audit, "to audit"), (KeyCommand::Delete, "to delete")], app)),
+    );
+    let mut list_state = app.state.copy_list.clone();
+    frame.render_stateful_widget(copy_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    add_inputs(&form, vertical_layout[2], frame, app, view);
+    add_button(Button::new("Add copy").color(app.theme().positive), vertical_layout[3], Action::AddCopy, frame, app, view);
+    add_messages(app, view, vertical_layout[4], frame);
+}
+
+fn render_audit(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Components
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Buttons
+            Constraint::Length(5), // Messages
+        ],
+    );
+    let title = app
+        .get_audited_copy()
+        .map_or("Box audit".to_string(), |copy| format!("Box audit: {}", copy.label()));
+    add_title(&title, vertical_layout[0], frame, app, view, false);
+    let components = app.state.auditing.map(|id| app.get_components(id)).unwrap_or_default();
+    let component_list = List::new(components.iter().map(|component| {
+        let (mark, color) = if component.is_complete() { ("[x]", app.theme().positive) } else { ("[ ]", app.theme().negative) };
+        let mut line = vec![
+            Span::styled(format!("{} ", mark), Style::default().fg(color)),
+            Span::raw(format!("{} {}/{}", component.name, component.present, component.expected)),
+        ];
+        if !component.notes.is_empty() {
+            line.push(Span::styled(format!(" - {}", component.notes), Style::default().fg(app.theme().muted)));
+        }
+        ListItem::new(Line::from(line))
+    }))
+    .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title(
+                "Components",
+                Mode::Audit,
+                &[(KeyCommand::AddOne, "to add one"), (KeyCommand::RemoveOne, "to remove one"), (KeyCommand::Delete, "to delete")],
+                app,
+            )),
+    );
+    let mut list_state = app.state.component_list.clone();
+    frame.render_stateful_widget(component_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    add_inputs(&form, vertical_layout[2], frame, app, view);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 3])
+        .split(vertical_layout[3]);
+    add_button(Button::new("Add component").color(app.theme().positive), button_line[0], Action::AddComponent, frame, app, view);
+    add_button(Button::new("Save notes").color(app.theme().neutral), button_line[1], app.selected_component().and_then(|component| component.id).map(Action::SaveComponentNotes), frame, app, view);
+    add_button(Button::new("Finish audit").color(app.theme().positive), button_line[2], Action::FinishAudit, frame, app, view);
+    add_messages(app, view, vertical_layout[4], frame);
+}
+
+fn render_house_rules(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Rules
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Button
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("House rules", vertical_layout[0], frame, app, view, false);
+    let rules = app.get_house_rules();
+    let rule_list = List::new(rules.iter().map(|rule| {
+        let (mark, color) = if rule.active { ("[x]", app.theme().positive) } else { ("[ ]", app.theme().muted) };
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("{} ", mark), Style::default().fg(color)),
+            Span::raw(format!("{} by {}", rule.title, rule.author)),
+        ]))
+    }))
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title("Rules", Mode::HouseRules, &[(KeyCommand::Toggle, "to toggle"), (KeyCommand::Delete, "to delete")], app)),
+    );
+    let mut list_state = app.state.rule_list.clone();
+    frame.render_stateful_widget(rule_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    add_inputs(&form, vertical_layout[2], frame, app, view);
+    add_button(Button::new("Add rule").color(app.theme().positive), vertical_layout[3], Action::AddHouseRule, frame, app, view);
+    add_messages(app, view, vertical_layout[4], frame);
+}
+
+fn render_rule_search(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Length(form_height(&form)),
+            Constraint::Min(2),    // Results
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("Search house rules", vertical_layout[0], frame, app, view, false);
+    add_inputs(&form, vertical_layout[1], frame, app, view);
+    let rules = app.search_house_rules();
+    let result_list = List::new(rules.iter().map(|(rule, boardgame_name)| {
+        let style = if rule.active { Style::default() } else { Style::default().fg(app.theme().muted) };
+        ListItem::new(Line::styled(
+            format!("{}: {} by {}", boardgame_name, rule.title, rule.author),
+            style,
+        ))
+    }))
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title("Results", Mode::RuleSearch, &[(KeyCommand::Open, "to open the game")], app)),
+    );
+    let mut list_state = app.state.rule_list.clone();
+    frame.render_stateful_widget(result_list, vertical_layout[2], &mut list_state);
+    view.list = Some(list_state);
+    add_messages(app, view, vertical_layout[3], frame);
+}
+
+fn render_trash(frame: &mut Frame, app: &App, view: &mut View) {
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Trashed boardgames
+            Constraint::Length(3), // Buttons
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("Trash", vertical_layout[0], frame, app, view, false);
+    let boardgames = app.get_trashed_boardgames();
+    let trash_list = List::new(boardgames.iter().map(|boardgame| {
+        let deleted = boardgame
+            .deleted_at
+            .map(|at| format!(" - deleted {}", at.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        ListItem::new(Line::from(vec![
+            Span::raw(boardgame.name.clone()),
+            Span::styled(deleted, Style::default().fg(app.theme().muted)),
+        ]))
+    }))
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(hinted_title(
+                "Deleted boardgames",
+                Mode::Trash,
+                &[(KeyCommand::Restore, "to restore"), (KeyCommand::Purge, "to delete permanently")],
+                app,
+            )),
+    );
+    let mut list_state = app.state.trash_list.clone();
+    frame.render_stateful_widget(trash_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 2])
+        .split(vertical_layout[2]);
+    add_button(Button::new("Restore").color(app.theme().positive), button_line[0], app.selected_trashed_boardgame().and_then(|boardgame| boardgame.id).map(Action::RestoreBoardgame), frame, app, view);
+    add_button(Button::new("Delete permanently").color(app.theme().negative), button_line[1], app.selected_trashed_boardgame().and_then(|boardgame| boardgame.id).map(Action::PurgeBoardgame), frame, app, view);
+    add_messages(app, view, vertical_layout[3], frame);
+}
+
+fn render_history(frame: &mut Frame, app: &App, view: &mut View) {
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),    // Entries
+            Constraint::Length(5), // Messages
+        ],
+    );
+    let title = app
+        .get_viewed_boardgame()
+        .map_or("History".to_string(), |boardgame| format!("History: {}", boardgame.name));
+    add_title(&title, vertical_layout[0], frame, app, view, false);
+    let history = app.get_history();
+    let history_list = List::new(history.iter().map(|entry| history_item(entry, app.theme())))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ")
+        .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .title(name),
+                .title("Changes, newest first"),
         );
-        if let Some(selected) = &app.state.selected_input {
-            if selected == name {
-                input = input.style(Style::default().fg(Color::Magenta));
+    let mut list_state = app.state.history_list.clone();
+    frame.render_stateful_widget(history_list, vertical_layout[1], &mut list_state);
+    view.list = Some(list_state);
+    add_messages(app, view, vertical_layout[2], frame);
+}
+
+/// A history entry as a heading line followed by one line per changed field.
+fn history_item(entry: &HistoryEntry, theme: &Theme) -> ListItem<'static> {
+    let record = match entry.table.as_str() {
+        "boardgames" => "details",
+        "boardgame_mechanics" => "mechanic",
+        "person_credits" | "company_credits" => "credit",
+        "player_count_votes" => "player count vote",
+        "custom_field_values" => "custom value",
+        "images" => "image",
+        "copies" => "copy",
+        "components" => "component",
+        "house_rules" => "house rule",
+        other => other,
+    };
+    let (verb, color) = match entry.action {
+        HistoryAction::Create => ("added", theme.positive),
+        HistoryAction::Update => ("changed", Color::Yellow),
+        HistoryAction::Delete => ("removed", theme.negative),
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(entry.changed_at.format("%Y-%m-%d %H:%M ").to_string(), Style::default().fg(theme.muted)),
+        Span::raw(format!("{} ", entry.actor)),
+        Span::styled(verb, Style::default().fg(color)),
+        Span::raw(format!(" {}", record)),
+    ])];
+    for change in &entry.changes {
+        let mut line = vec![Span::raw(format!("    {}: ", change.field))];
+        if let Some(before) = &change.before {
+            line.push(Span::styled(before.clone(), Style::default().fg(theme.negative).add_modifier(Modifier::CROSSED_OUT)));
+        }
+        if change.before.is_some() && change.after.is_some() {
+            line.push(Span::raw(" → "));
+        }
+        if let Some(after) = &change.after {
+            line.push(Span::styled(after.clone(), Style::default().fg(theme.positive)));
+        }
+        lines.push(Line::from(line));
+    }
+    ListItem::new(lines)
+}
+
+fn render_adding_image(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Buttons
+            Constraint::Min(2),
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("Add image...", vertical_layout[0], frame, app, view, false);
+    add_inputs(&form, vertical_layout[1], frame, app, view);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 2])
+        .split(vertical_layout[2]);
+    add_button(Button::new("Add as cover").color(app.theme().positive), button_line[0], Action::AddImage(ImageKind::Cover), frame, app, view);
+    add_button(Button::new("Add as photo").color(app.theme().neutral), button_line[1], Action::AddImage(ImageKind::Photo), frame, app, view);
+    add_messages(app, view, *vertical_layout.last().expect("no constraint"), frame);
+}
+
+fn render_voting(frame: &mut Frame, app: &App, view: &mut View) {
+    let form = app.form();
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Length(form_height(&form)),
+            Constraint::Length(3), // Buttons
+            Constraint::Min(2),
+            Constraint::Length(5), // Messages
+        ],
+    );
+    add_title("How well does it play at...", vertical_layout[0], frame, app, view, false);
+    add_inputs(&form, vertical_layout[1], frame, app, view);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 3])
+        .split(vertical_layout[2]);
+    add_button(Button::new("Best").color(app.theme().positive), button_line[0], Action::Vote(PlayerCountVote::Best), frame, app, view);
+    add_button(Button::new("Recommended").color(app.theme().neutral), button_line[1], Action::Vote(PlayerCountVote::Recommended), frame, app, view);
+    add_button(Button::new("Not recommended").color(app.theme().negative), button_line[2], Action::Vote(PlayerCountVote::NotRecommended), frame, app, view);
+    add_messages(app, view, *vertical_layout.last().expect("no constraint"), frame);
+}
+
+fn render_details(frame: &mut Frame, app: &App, view: &mut View) {
+    let vertical_layout = create_vertical_layout(
+        frame.area(),
+        &[
+            Constraint::Length(3), // Title
+            Constraint::Min(2),
+            Constraint::Length(3), // Buttons
+            Constraint::Length(5), // Messages
+        ],
+    );
+    let Some(boardgame) = app.get_viewed_boardgame() else {
+        add_title("Boardgame not found", vertical_layout[0], frame, app, view, false);
+        add_messages(app, view, vertical_layout[3], frame);
+        return;
+    };
+    add_title(&boardgame.name, vertical_layout[0], frame, app, view, false);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 6])
+        .split(vertical_layout[2]);
+    let id = boardgame.id.expect("saved boardgame");
+    add_button(Button::new("Edit").color(app.theme().neutral), button_line[0], Action::EditBoardgame(id), frame, app, view);
+    add_button(
+        Button::new("Vote on player counts").color(app.theme().neutral),
+        button_line[1],
+        Action::GoTo(Mode::Voting),
+        frame,
+        app,
+        view,
+    );
+    add_button(Button::new("Add image").color(app.theme().neutral), button_line[2], Action::GoTo(Mode::AddingImage), frame, app, view);
+    add_button(Button::new("Copies").color(app.theme().neutral), button_line[3], Action::GoTo(Mode::Copies), frame, app, view);
+    add_button(Button::new("House rules").color(app.theme().neutral), button_line[4], Action::GoTo(Mode::HouseRules), frame, app, view);
+    add_button(Button::new("History").color(app.theme().neutral), button_line[5], Action::GoTo(Mode::History), frame, app, view);
+    let images = app.get_images();
+    let body = if images.is_empty() {
+        vertical_layout[1]
+    } else {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(IMAGE_WIDTH), Constraint::Min(10)])
+            .split(vertical_layout[1]);
+        let index = app.state.image_index.min(images.len() - 1);
+        add_image(&images[index], index, images.len(), columns[0], frame, app, view);
+        columns[1]
+    };
+
+    let label = Style::default().fg(app.theme().label);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Players: ", label),
+            Span::raw(format!("{}-{}", boardgame.min_players, boardgame.max_players)),
+        ]),
+        Line::from(vec![
+            Span::styled("Play time: ", label),
+            Span::raw(format!("{} minutes", boardgame.play_time_minutes)),
+        ]),
+    ];
+    let metadata = [
+        ("Weight: ", boardgame.weight.map(|weight| format!("{:.1} / 5", weight))),
+        ("Age: ", boardgame.min_age.map(|age| format!("{}+", age))),
+        ("Published: ", boardgame.year_published.map(|year| year.to_string())),
+        (
+            "Mechanics: ",
+            Some(boardgame.mechanics.iter().map(Mechanic::label).collect::<Vec<_>>().join(", "))
+                .filter(|mechanics| !mechanics.is_empty()),
+        ),
+    ];
+    for (name, value) in metadata {
+        if let Some(value) = value {
+            lines.push(Line::from(vec![Span::styled(name, label), Span::raw(value)]));
+        }
+    }
+    for field in app.get_custom_fields() {
+        if let Some(value) = boardgame.custom_values.get(&field.id) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}: ", field.name), label),
+                Span::raw(value.to_string()),
+            ]));
+        }
+    }
+    let copies = app.get_copies();
+    if !copies.is_empty() {
+        let labels: Vec<String> = copies.iter().map(|copy| copy.label()).collect();
+        lines.push(Line::from(vec![Span::styled("Copies: ", label), Span::raw(labels.join("; "))]));
+    }
+    let poll = app.get_player_count_poll();
+    if !poll.tallies.is_empty() {
+        let counts = |counts: Vec<i32>| {
+            counts.iter().map(|count| count.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Best at: ", label),
+            Span::raw(counts(poll.best_at())),
+            Span::styled("  Recommended at: ", label),
+            Span::raw(counts(poll.recommended_at())),
+        ]));
+        for tally in &poll.tallies {
+            lines.push(Line::raw(format!(
+                "  {} players: {} best, {} recommended, {} not recommended",
+                tally.player_count, tally.best, tally.recommended, tally.not_recommended
+            )));
+        }
+    }
+    let credits = app.get_credits();
+    for (role, field) in CreditRole::ALL.into_iter().zip(BG_CREDIT_FIELDS) {
+        let names: Vec<&str> = credits
+            .iter()
+            .filter(|credit| credit.role == role)
+            .map(|credit| credit.name.as_str())
+            .collect();
+        if !names.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}: ", field), label),
+                Span::raw(names.join(", ")),
+            ]));
+        }
+    }
+    lines.push(Line::default());
+    lines.extend(boardgame.description.lines().map(|line| Line::raw(line.to_string())));
+    let mut separated = false;
+    for (credit, others) in app.get_credited_boardgames() {
+        if !others.is_empty() {
+            let others: Vec<String> = others.into_iter().map(|other| other.name).collect();
+            if !separated {
+                lines.push(Line::default());
+                separated = true;
             }
+            lines.push(Line::from(vec![
+                Span::styled(format!("More by {}: ", credit.name), label),
+                Span::raw(others.join(", ")),
+            ]));
         }
-        app.add_input(row[0], name);
-        frame.render_widget(input, row[0]);
     }
-    add_button(Button::new("Add").green(), vertical_layout[vertical_layout.len() - 3], App::add_new_boardgame, frame, app);
-    add_messages(app, *vertical_layout.last().expect("no constraint"), frame);
+    let rules = app.get_house_rules();
+    let (active, inactive): (Vec<_>, Vec<_>) = rules.into_iter().partition(|rule| rule.active);
+    for rule in active {
+        lines.push(Line::default());
+        let added = rule.created_at.map(|at| format!(" on {}", at.format("%Y-%m-%d"))).unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(rule.title, label.add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" by {}{}", rule.author, added), Style::default().fg(app.theme().muted)),
+        ]));
+        lines.extend(markdown_lines(&rule.body));
+    }
+    if !inactive.is_empty() {
+        let titles: Vec<String> = inactive.into_iter().map(|rule| rule.title).collect();
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            format!("Not in use: {}", titles.join(", ")),
+            Style::default().fg(app.theme().muted),
+        ));
+    }
+    let details = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title("Details"),
+    );
+    frame.render_widget(details, body);
+    add_messages(app, view, vertical_layout[3], frame);
 }
 
-pub fn render_main(frame: &mut Frame, app: &mut App) {
+pub fn render_main(frame: &mut Frame, app: &App, view: &mut View) {
     // Create the layout
     let vertical_layout = create_vertical_layout(
         frame.area(),
@@ -111,28 +734,77 @@ pub fn render_main(frame: &mut Frame, app: &mut App) {
             Constraint::Length(5), // Messages
         ],
     );
-    add_title("Boardgame Manager", vertical_layout[0], frame, app, true);
+    add_title("Boardgame Manager", vertical_layout[0], frame, app, view, true);
+    let button_line = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(10); 5])
+        .split(vertical_layout[1]);
     add_button(
-        Button::new("Add Boardgame").green(),
-        vertical_layout[1],
-        App::go_to_add_new,
+        Button::new("Add Boardgame").color(app.theme().positive),
+        button_line[0],
+        Action::GoTo(Mode::Adding),
         frame,
         app,
+        view,
     );
-    let boardgames = app.get_boardgames();
-    let boardgame_list = List::new(
-        boardgames
-            .iter()
-            .map(|b| ListItem::new(b.name.to_string())),
-    )
+    add_button(
+        Button::new("Filter").color(app.theme().neutral),
+        button_line[1],
+        Action::GoTo(Mode::Filtering),
+        frame,
+        app,
+        view,
+    );
+    add_button(
+        Button::new("Custom fields").color(app.theme().neutral),
+        button_line[2],
+        Action::GoTo(Mode::CustomFields),
+        frame,
+        app,
+        view,
+    );
+    add_button(
+        Button::new("Search rules").color(app.theme().neutral),
+        button_line[3],
+        Action::GoTo(Mode::RuleSearch),
+        frame,
+        app,
+        view,
+    );
+    add_button(
+        Button::new("Trash").color(app.theme().negative),
+        button_line[4],
+        Action::GoTo(Mode::Trash),
+        frame,
+        app,
+        view,
+    );
+    let items: Vec<ListItem> = app.get_boardgames()
+        .iter()
+        .map(|b| ListItem::new(b.name.to_string()))
+        .collect();
+    let mut list_title = String::from("Boardgames");
+    if app.state.sort != BoardgameSort::Name {
+        list_title.push_str(&format!(" by {}", app.state.sort));
+    }
+    if !app.state.filter.is_empty() {
+        list_title.push_str(" (filtered)");
+    }
+    // The help is the way to find the other keys, so its own key is worth a place here
+    let hints = [(KeyCommand::Sort, "to sort"), (KeyCommand::Delete, "to delete"), (KeyCommand::Help, "for help")];
+    let boardgame_list = List::new(items)
+    .highlight_style(Style::default().fg(app.theme().highlight))
+    .highlight_symbol("> ")
     .block(
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title("Boardgames"),
+            .title(hinted_title(&list_title, Mode::Main, &hints, app)),
     );
-    frame.render_widget(boardgame_list, vertical_layout[2]);
-    add_messages(app, vertical_layout[3], frame);
+    let mut list_state = app.state.boardgame_list.clone();
+    frame.render_stateful_widget(boardgame_list, vertical_layout[2], &mut list_state);
+    view.list = Some(list_state);
+    add_messages(app, view, vertical_layout[3], frame);
 }
 
 fn create_vertical_layout(area: Rect, constraints: &[Constraint]) -> Rc<[Rect]> {
@@ -143,7 +815,276 @@ fn create_vertical_layout(area: Rect, constraints: &[Constraint]) -> Rc<[Rect]>
         .split(area)
 }
 
-fn add_title(title: &str, area: Rect, frame: &mut Frame, app: &mut App, quit: bool) {
+/// A list's title with hints at the keys it takes on `mode`'s screen, as in "Copies (enter to
+/// audit)". Hints for commands with no key are left out.
+fn hinted_title(title: &str, mode: Mode, hints: &[(KeyCommand, &str)], app: &App) -> String {
+    let hints: Vec<String> = hints
+        .iter()
+        .filter_map(|(command, hint)| Some(format!("{} {}", app.key_for(mode, *command)?, hint)))
+        .collect();
+    if hints.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} ({})", title, hints.join(", "))
+    }
+}
+
+/// The keys to flip between images, as " ([ ])" after their count.
+fn image_keys(app: &App) -> String {
+    let keys: Vec<String> = [KeyCommand::PreviousImage, KeyCommand::NextImage]
+        .into_iter()
+        .filter_map(|command| app.key_for(Mode::Details, command))
+        .collect();
+    if keys.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", keys.join(" "))
+    }
+}
+
+fn add_image(image: &ImageInfo, index: usize, count: usize, area: Rect, frame: &mut Frame, app: &App, view: &mut View) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(format!("{} {}/{}{}", image.kind, index + 1, count, image_keys(app)));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let Some(thumbnail) = app.get_thumbnail(image.id) else {
+        return;
+    };
+    match app.image_protocol {
+        ImageProtocol::Kitty => {
+            frame.render_widget(ImagePlaceholder, inner);
+            view.image_placement = Some((image.id, inner));
+        }
+        ImageProtocol::HalfBlocks => frame.render_widget(HalfBlockImage::new(&thumbnail.image), inner),
+    }
+}
+
+/// Height needed by `add_inputs` for the given form.
+fn form_height(form: &Form) -> u16 {
+    let (multiline, grid) = split_multiline(form);
+    form_rows(&grid) as u16 * 3 + multiline.len() as u16 * TEXTAREA_HEIGHT
+}
+
+fn split_multiline(form: &Form) -> (Vec<&Field>, Vec<&Field>) {
+    form.fields().iter().partition(|field| field.kind == FieldKind::Multiline)
+}
+
+fn form_rows(fields: &[&Field]) -> usize {
+    // Longer forms are split into columns of at most five inputs so they fit on the screen
+    let columns = fields.len().div_ceil(5).max(1);
+    fields.len().div_ceil(columns)
+}
+
+/// Lays out the form's one-line fields in a grid, with any textareas below it at full width.
+fn add_inputs(form: &Form, area: Rect, frame: &mut Frame, app: &App, view: &mut View) {
+    let (multiline, fields) = split_multiline(form);
+    let rows = form_rows(&fields);
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            std::iter::once(Constraint::Length(rows as u16 * 3))
+                .chain(multiline.iter().map(|_| Constraint::Length(TEXTAREA_HEIGHT))),
+        )
+        .split(area);
+    for (field, area) in multiline.iter().zip(sections.iter().skip(1)) {
+        add_textarea(&field.label, *area, frame, app, view);
+    }
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Fill(1); fields.len().div_ceil(rows.max(1))])
+        .split(sections[0]);
+    for (i, field) in fields.iter().enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3); rows])
+            .split(columns[i / rows]);
+        add_field(field, cells[i % rows], frame, app, view);
+    }
+}
+
+/// The bordered block an input is drawn in, and its style, which show whether it is focused.
+fn input_block<'a>(name: &'a str, focused: bool, theme: &Theme) -> (Block<'a>, Style) {
+    let (style, border_type) = if focused {
+        (Style::default().fg(theme.highlight), BorderType::Thick)
+    } else {
+        (Style::default(), BorderType::Rounded)
+    };
+    (Block::default().borders(Borders::ALL).border_type(border_type).title(name), style)
+}
+
+/// Draws a one-line field with the widget for its kind.
+fn add_field(field: &Field, area: Rect, frame: &mut Frame, app: &App, view: &mut View) {
+    let name = field.label.as_str();
+    let focused = app.state.selected_input.as_deref() == Some(name);
+    let value = app.state.input_state.get(name).map_or("", String::as_str);
+    let (block, style) = input_block(name, focused, app.theme());
+    match &field.kind {
+        FieldKind::Text | FieldKind::Multiline => return add_input(name, area, frame, app, view),
+        FieldKind::Number { min, max, .. } => {
+            let spinner = Spinner::new(value.trim().parse().ok(), *min, *max).block(block).style(style);
+            frame.render_widget(spinner, area)
+        }
+        FieldKind::Decimal { min, max, .. } => {
+            frame.render_widget(Spinner::decimal(value, *min, *max).block(block).style(style), area)
+        }
+        FieldKind::Select(options) => {
+            let select = Select::new(options, select::position(options, value)).block(block).style(style);
+            frame.render_widget(select, area)
+        }
+        FieldKind::Checkbox => {
+            frame.render_widget(Checkbox::new(checkbox::is_checked(value)).block(block).style(style), area)
+        }
+        FieldKind::Date => {
+            frame.render_widget(DatePicker::new(date_picker::parse(value)).block(block).style(style), area)
+        }
+    }
+    view.inputs.push((area, name.to_string()));
+}
+
+fn add_input(name: &str, area: Rect, frame: &mut Frame, app: &App, view: &mut View) {
+    let focused = app.state.selected_input.as_deref() == Some(name);
+    let value = app.state.input_state.get(name).map_or("", String::as_str);
+    let placeholder = PLACEHOLDERS.iter().find(|(field, _)| *field == name).map(|(_, placeholder)| *placeholder);
+    let (block, style) = input_block(name, focused, app.theme());
+    let input = TextInput::new(value).block(block).style(style).placeholder(placeholder).focused(focused);
+    let mut state = app.state.text_inputs.get(name).cloned().unwrap_or_else(|| TextInputState::at_end(value));
+    frame.render_stateful_widget(input, area, &mut state);
+    if let Some(position) = state.screen_cursor() {
+        frame.set_cursor_position(position);
+    }
+    view.text_inputs.insert(name.to_string(), state);
+    view.inputs.push((area, name.to_string()));
+}
+
+fn add_textarea(name: &str, area: Rect, frame: &mut Frame, app: &App, view: &mut View) {
+    let focused = app.state.selected_input.as_deref() == Some(name);
+    let value = app.state.input_state.get(name).map_or("", String::as_str);
+    let placeholder = PLACEHOLDERS.iter().find(|(field, _)| *field == name).map(|(_, placeholder)| *placeholder);
+    let (block, style) = input_block(name, focused, app.theme());
+    let textarea = TextArea::new(value).block(block).style(style).placeholder(placeholder).focused(focused);
+    let mut state = app.state.textareas.get(name).cloned().unwrap_or_else(|| TextAreaState::at_end(value));
+    frame.render_stateful_widget(textarea, area, &mut state);
+    if let Some(position) = state.screen_cursor() {
+        frame.set_cursor_position(position);
+    }
+    view.textareas.insert(name.to_string(), state);
+    view.inputs.push((area, name.to_string()));
+}
+
+/// Opens the dropdown of a focused select or the calendar of a focused date picker, over
+/// whatever is drawn below the field, or above it where there is no room below.
+fn add_popup(frame: &mut Frame, app: &App, view: &View) {
+    let Some(name) = app.state.selected_input.as_deref() else {
+        return;
+    };
+    let Some(field_area) = view.inputs.iter().find(|(_, input)| *input == name).map(|(area, _)| *area) else {
+        return;
+    };
+    let value = app.state.input_state.get(name).map_or("", String::as_str);
+    let form = app.form();
+    let place = |width: u16, height: u16| {
+        let screen = frame.area();
+        let y = if field_area.bottom() + height <= screen.bottom() {
+            field_area.bottom()
+        } else {
+            field_area.y.saturating_sub(height)
+        };
+        Rect::new(field_area.x, y, width.min(screen.right() - field_area.x), height.min(screen.height))
+    };
+    match form.kind(name) {
+        Some(FieldKind::Select(options)) => {
+            let list = SelectList::new(options, select::position(options, value));
+            let area = place(field_area.width, list.height());
+            frame.render_widget(list, area);
+        }
+        Some(FieldKind::Date) => {
+            let (width, height) = Calendar::SIZE;
+            frame.render_widget(Calendar::new(date_picker::parse(value)), place(width, height));
+        }
+        _ => {}
+    }
+}
+
+/// Draws the keys and mouse actions for the screen over it. While it is shown, a click anywhere
+/// closes it rather than reaching what is underneath.
+fn add_help(mode: Mode, scroll: u16, frame: &mut Frame, app: &App, view: &mut View) {
+    let theme = app.theme();
+    let heading = |text: &str| Line::styled(text.to_string(), Style::default().fg(theme.highlight).bold());
+    let mut rows: Vec<Line> = Vec::new();
+    let mut add_rows = |title: &str, entries: Vec<(String, String)>| {
+        if entries.is_empty() {
+            return;
+        }
+        if !rows.is_empty() {
+            rows.push(Line::default());
+        }
+        rows.push(heading(title));
+        for (keys, description) in entries {
+            rows.push(Line::from(vec![
+                Span::styled(format!("  {:<18} ", keys), Style::default().fg(theme.label)),
+                Span::raw(description),
+            ]));
+        }
+    };
+
+    let bindings = app.bindings(mode);
+    let (own, everywhere): (Vec<_>, Vec<_>) = bindings.into_iter().partition(|binding| binding.mode.is_some());
+    let keys = |bindings: Vec<&Binding>| {
+        bindings
+            .into_iter()
+            .map(|binding| (keymap::chord_list(&binding.chords), binding.command.description().to_string()))
+            .collect()
+    };
+    add_rows("Keys on this screen", keys(own));
+    add_rows("Keys everywhere", keys(everywhere));
+    if !view.buttons.is_empty() || !view.inputs.is_empty() {
+        add_rows("Focus", FOCUS_KEYS.iter().map(|(keys, description)| (keys.to_string(), description.to_string())).collect());
+    }
+    let mut mouse = Vec::new();
+    let mut labels: Vec<&str> = Vec::new();
+    for (_, _, label) in &view.buttons {
+        if !label.is_empty() && !labels.contains(&label.as_str()) {
+            labels.push(label);
+        }
+    }
+    for label in labels {
+        mouse.push(("click".to_string(), format!("Press {}", label)));
+    }
+    if !view.inputs.is_empty() {
+        mouse.push(("click an input".to_string(), "Type in it".to_string()));
+    }
+    add_rows("Mouse", mouse);
+
+    let screen = frame.area();
+    let width = 76.min(screen.width);
+    let height = (rows.len() as u16 + 2).min(screen.height);
+    let area = Rect::new(screen.x + (screen.width - width) / 2, screen.y + (screen.height - height) / 2, width, height);
+    let scroll = scroll.min((rows.len() as u16 + 2).saturating_sub(height));
+    let scroll_keys = app.help_scroll_keys();
+    let footer = if scroll_keys.is_empty() {
+        " Any key to close ".to_string()
+    } else {
+        format!(" {} to scroll, any other key to close ", scroll_keys.join(" and "))
+    };
+    let help = Paragraph::new(rows).scroll((scroll, 0)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.highlight))
+            .title(format!(" Help for the {} screen ", mode.name()))
+            .title_bottom(footer),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(help, area);
+
+    view.buttons = vec![(screen, Action::ToggleHelp, String::new())];
+    view.inputs.clear();
+    view.help = Some(scroll);
+}
+
+fn add_title(title: &str, area: Rect, frame: &mut Frame, app: &App, view: &mut View, quit: bool) {
     let layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Min(1), Constraint::Length(6)])
@@ -156,45 +1097,61 @@ fn add_title(title: &str, area: Rect, frame: &mut Frame, app: &mut App, quit: bo
     frame.render_widget(title, layout[0]);
     let button_text = if quit { "Quit" } else { "Back" };
     let button_function = if quit {
-        App::go_to_quit
+        Action::GoTo(Mode::Quitting)
     } else {
-        App::prev_mode
+        Action::Back
     };
-    let color = if quit { Button::red } else { Button::blue };
+    let color = if quit { app.theme().negative } else { app.theme().neutral };
     add_button(
-        color(Button::new(button_text)),
+        Button::new(button_text).color(color),
         layout[1],
         button_function,
         frame,
         app,
+        view,
     );
 }
 
 fn add_button(
     mut button: Button,
     area: Rect,
-    func: fn(&mut App) -> (),
+    action: impl Into<Option<Action>>,
     frame: &mut Frame,
-    app: &mut App,
+    app: &App,
+    view: &mut View,
 ) {
-    app.add_button(area, func);
+    match action.into() {
+        Some(action) => view.buttons.push((area, action, button.label().trim().to_string())),
+        None => button.disable(),
+    }
     if let Some(pos) = app.cursor {
         if area.contains(pos) {
             button.highlight();
         }
     }
+    if app.state.focused_button == Some(area) {
+        button.focus();
+    }
     frame.render_widget(button, area);
 }
 
-fn add_messages(app: &mut App, area: Rect, frame: &mut Frame) {
-    let message_style = Style::default().fg(Color::Green);
-    let messages = app.get_messages();
-    let message_text = messages
-        .iter()
-        .map(|(msg, _)| msg.to_owned())
-        .collect::<Vec<String>>()
-        .join("\n");
-    let message = Paragraph::new(message_text)
+/// Shows the messages and, while loading data is failing, the error with a button to retry.
+fn add_messages(app: &App, view: &mut View, mut area: Rect, frame: &mut Frame) {
+    let message_style = Style::default().fg(app.theme().messages);
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(error) = app.get_load_error() {
+        lines.push(Line::styled(error.to_string(), Style::default().fg(app.theme().errors)));
+        let retry = match app.get_curr_mode().and_then(|mode| app.key_for(mode, KeyCommand::Reload)) {
+            Some(key) => format!("Retry ({})", key),
+            None => "Retry".to_string(),
+        };
+        let width = retry.chars().count() as u16 + 4;
+        let [messages_area, retry_area] = Layout::horizontal([Constraint::Min(0), Constraint::Length(width)]).areas(area);
+        area = messages_area;
+        add_button(Button::new(&retry).color(app.theme().negative), retry_area, Action::RetryLoad, frame, app, view);
+    }
+    lines.extend(app.get_messages().iter().map(|(msg, _)| Line::raw(msg.to_owned())));
+    let message = Paragraph::new(lines)
         .style(message_style)
         .alignment(Alignment::Center)
         .block(
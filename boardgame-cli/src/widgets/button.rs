@@ -3,6 +3,8 @@ use ratatui::{prelude::*, widgets::*};
 pub struct Button {
     text: String,
     color: Color,
+    focused: bool,
+    bold: bool,
 }
 
 impl Button {
@@ -10,44 +12,61 @@ impl Button {
         Self {
             text: text.to_string(),
             color: Color::White,
+            focused: false,
+            bold: false,
         }
     }
 
-    pub fn red(self) -> Self {
-        self.color(Color::Red)
+    pub fn label(&self) -> &str {
+        &self.text
     }
 
-    pub fn green(self) -> Self {
-        self.color(Color::Green)
+    pub fn color(self, color: Color) -> Self {
+        Self { color, ..self }
     }
 
-    pub fn blue(self) -> Self {
-        self.color(Color::Blue)
+    /// Marks the button as having keyboard focus.
+    pub fn focus(&mut self) {
+        self.focused = true;
     }
 
-    fn color(self, color: Color) -> Self {
-        Self { color, ..self }
+    /// Dims a button that has nothing to act on, such as one for the selected item when none is.
+    pub fn disable(&mut self) {
+        self.color = Color::DarkGray;
     }
 
+    /// Lightens the button, or for colours without a lighter one, makes its label bold.
     pub fn highlight(&mut self) {
         self.color = match self.color {
             Color::Red => Color::LightRed,
             Color::Green => Color::LightGreen,
             Color::Blue => Color::LightBlue,
-            color => color,
+            Color::Yellow => Color::LightYellow,
+            Color::Magenta => Color::LightMagenta,
+            Color::Cyan => Color::LightCyan,
+            color => {
+                self.bold = true;
+                color
+            }
         };
     }
 }
 
 impl Widget for Button {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let (style, border_type) = if self.focused {
+            (Style::default().fg(self.color).add_modifier(Modifier::BOLD | Modifier::REVERSED), BorderType::Thick)
+        } else {
+            (Style::default().fg(self.color), BorderType::Rounded)
+        };
+        let style = if self.bold { style.add_modifier(Modifier::BOLD) } else { style };
         let button = Paragraph::new(self.text)
             .alignment(Alignment::Center)
-            .style(Style::default().fg(self.color))
+            .style(style)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded),
+                    .border_type(border_type),
             );
         button.render(area, buf);
     }
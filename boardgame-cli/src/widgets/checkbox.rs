@@ -0,0 +1,56 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+/// Whether a checkbox's value is checked. Values are stored as "yes" and "no", as custom fields
+/// show them, and an empty value is unchecked.
+pub fn is_checked(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "y" | "yes" | "true" | "1")
+}
+
+/// Applies a key to a checkbox, returning whether the key was one. Space, Left and Right toggle
+/// it, and y and n check and uncheck it.
+pub fn handle_key(checked: &mut bool, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => *checked = !*checked,
+        KeyCode::Char('y') => *checked = true,
+        KeyCode::Char('n') => *checked = false,
+        _ => return false,
+    }
+    true
+}
+
+pub struct Checkbox<'a> {
+    checked: bool,
+    block: Option<Block<'a>>,
+    style: Style,
+}
+
+impl<'a> Checkbox<'a> {
+    pub fn new(checked: bool) -> Self {
+        Checkbox { checked, block: None, style: Style::default() }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        Checkbox { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Checkbox { style, ..self }
+    }
+}
+
+impl Widget for Checkbox<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        let text = if self.checked { "[x] Yes" } else { "[ ] No" };
+        Line::raw(text).render(inner, buf);
+    }
+}
@@ -0,0 +1,166 @@
+use boardgame_core::db::DATE_FORMAT;
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+/// The date in a date picker's value, if it holds one.
+pub fn parse(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+}
+
+/// Applies a key to a date, returning whether the key was one.
+///
+/// Left and Right step a day, Up and Down a week and PageUp and PageDown a month. An empty
+/// picker steps from today, which t also picks. Backspace and Delete empty it.
+pub fn handle_key(date: &mut Option<NaiveDate>, key: KeyEvent) -> bool {
+    let today = Local::now().date_naive();
+    let from = date.unwrap_or(today);
+    let stepped = match key.code {
+        KeyCode::Left => from.checked_sub_days(Days::new(1)),
+        KeyCode::Right => from.checked_add_days(Days::new(1)),
+        KeyCode::Up => from.checked_sub_days(Days::new(7)),
+        KeyCode::Down => from.checked_add_days(Days::new(7)),
+        KeyCode::PageUp => from.checked_sub_months(Months::new(1)),
+        KeyCode::PageDown => from.checked_add_months(Months::new(1)),
+        KeyCode::Char('t') => Some(today),
+        KeyCode::Backspace | KeyCode::Delete => {
+            *date = None;
+            return true;
+        }
+        _ => return false,
+    };
+    // Stepping from an empty picker only shows today, so it can be moved from there
+    *date = if date.is_none() { Some(today) } else { stepped.or(*date) };
+    true
+}
+
+pub struct DatePicker<'a> {
+    date: Option<NaiveDate>,
+    block: Option<Block<'a>>,
+    style: Style,
+}
+
+impl<'a> DatePicker<'a> {
+    pub fn new(date: Option<NaiveDate>) -> Self {
+        DatePicker { date, block: None, style: Style::default() }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        DatePicker { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        DatePicker { style, ..self }
+    }
+}
+
+impl Widget for DatePicker<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        let line = match self.date {
+            Some(date) => Line::raw(date.format("%Y-%m-%d, %a").to_string()),
+            None => Line::styled("YYYY-MM-DD", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)),
+        };
+        line.render(inner, buf);
+    }
+}
+
+/// The month around a date picker's date, drawn under it while it is focused.
+pub struct Calendar {
+    date: NaiveDate,
+}
+
+impl Calendar {
+    /// Width and height of the calendar, its borders included.
+    pub const SIZE: (u16, u16) = (22, 9);
+
+    /// A calendar of the month `date` is in, or of this month without a date.
+    pub fn new(date: Option<NaiveDate>) -> Self {
+        Calendar { date: date.unwrap_or_else(|| Local::now().date_naive()) }
+    }
+}
+
+impl Widget for Calendar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(self.date.format(" %B %Y ").to_string());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let first = self.date.with_day(1).expect("every month has a first day");
+        let offset = first.weekday().num_days_from_monday() as usize;
+        let mut lines = vec![Line::styled("Mo Tu We Th Fr Sa Su", Style::default().fg(Color::DarkGray))];
+        let days = first.iter_days().take_while(|day| day.month() == first.month());
+        let mut week = vec![Span::raw("   ".repeat(offset))];
+        for day in days {
+            let style = if day == self.date {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            week.push(Span::styled(format!("{:>2}", day.day()), style));
+            if day.weekday().num_days_from_monday() == 6 {
+                lines.push(Line::from(std::mem::take(&mut week)));
+            } else {
+                week.push(Span::raw(" "));
+            }
+        }
+        if !week.is_empty() {
+            lines.push(Line::from(week));
+        }
+        Paragraph::new(lines).render(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn test_stepping_rolls_over() {
+        let stepped = |from, code| {
+            let mut picked = from;
+            assert!(handle_key(&mut picked, press(code)));
+            picked
+        };
+        assert_eq!(stepped(date(2024, 12, 31), KeyCode::Right), date(2025, 1, 1));
+        assert_eq!(stepped(date(2025, 3, 1), KeyCode::Left), date(2025, 2, 28));
+        assert_eq!(stepped(date(2024, 2, 26), KeyCode::Down), date(2024, 3, 4));
+        assert_eq!(stepped(date(2025, 1, 3), KeyCode::Up), date(2024, 12, 27));
+        // A month on from a day the next month doesn't have is its last day
+        assert_eq!(stepped(date(2024, 1, 31), KeyCode::PageDown), date(2024, 2, 29));
+        assert_eq!(stepped(date(2024, 3, 31), KeyCode::PageUp), date(2024, 2, 29));
+        // At the end of what can be represented the date stays put
+        assert_eq!(stepped(Some(NaiveDate::MAX), KeyCode::Right), Some(NaiveDate::MAX));
+
+        // An empty picker starts from today
+        let today = Local::now().date_naive();
+        assert_eq!(stepped(None, KeyCode::PageDown), Some(today));
+        assert_eq!(stepped(date(2020, 1, 1), KeyCode::Char('t')), Some(today));
+        assert_eq!(stepped(date(2020, 1, 1), KeyCode::Delete), None);
+        assert!(!handle_key(&mut date(2020, 1, 1), press(KeyCode::Enter)));
+
+        assert_eq!(parse(" 2024-02-29 "), date(2024, 2, 29));
+        assert_eq!(parse("2023-02-29"), None);
+    }
+}
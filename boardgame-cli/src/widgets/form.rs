@@ -0,0 +1,223 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use boardgame_core::db::DATE_FORMAT;
+use crossterm::event::KeyEvent;
+
+use super::{checkbox, date_picker, select, spinner};
+
+/// What a form field holds, which decides the widget it is edited with. Values are kept as text
+/// either way, in the form the screens parse them from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    /// Free text, in a text input.
+    Text,
+    /// Text of several lines, in a textarea.
+    Multiline,
+    /// A whole number from `min` to `max`, in a spinner. Stepping an empty one starts at `start`.
+    Number { min: i64, max: i64, start: i64 },
+    /// A number from `min` to `max` that may have decimals, in a spinner stepping by `step`.
+    Decimal { min: f64, max: f64, step: f64 },
+    /// One of a list of options, in a dropdown. An empty option leaves the field unset.
+    Select(Vec<String>),
+    /// Yes or no, in a checkbox.
+    Checkbox,
+    /// A date, in a date picker.
+    Date,
+}
+
+impl FieldKind {
+    /// Applies a key to `value`, returning whether the key was one. Text fields are edited
+    /// through their input's state instead, so this only handles the typed kinds.
+    pub fn handle_key(&self, value: &mut String, key: KeyEvent) -> bool {
+        match self {
+            FieldKind::Text | FieldKind::Multiline => false,
+            FieldKind::Number { min, max, start } => {
+                let mut number = value.trim().parse().ok();
+                let handled = spinner::handle_key(&mut number, *min, *max, *start, key);
+                if handled {
+                    *value = number.map(|n| n.to_string()).unwrap_or_default();
+                }
+                handled
+            }
+            FieldKind::Decimal { min, max, step } => spinner::handle_decimal_key(value, *min, *max, *step, key),
+            FieldKind::Select(options) => {
+                let mut selected = select::position(options, value);
+                let handled = select::handle_key(options, &mut selected, key);
+                if handled {
+                    *value = selected.map(|i| options[i].clone()).unwrap_or_default();
+                }
+                handled
+            }
+            FieldKind::Checkbox => {
+                let mut checked = checkbox::is_checked(value);
+                let handled = checkbox::handle_key(&mut checked, key);
+                if handled {
+                    *value = if checked { "yes" } else { "no" }.to_string();
+                }
+                handled
+            }
+            FieldKind::Date => {
+                let mut date = date_picker::parse(value);
+                let handled = date_picker::handle_key(&mut date, key);
+                if handled {
+                    *value = date.map(|date| date.format(DATE_FORMAT).to_string()).unwrap_or_default();
+                }
+                handled
+            }
+        }
+    }
+
+    /// Replaces `value` with pasted text if it is a valid value for a typed field. Text fields
+    /// take pastes through their input's state instead.
+    pub fn paste(&self, value: &mut String, text: &str) {
+        let text = text.trim();
+        let valid = match self {
+            FieldKind::Text | FieldKind::Multiline => false,
+            FieldKind::Number { min, max, .. } => text.parse::<i64>().is_ok_and(|n| (*min..=*max).contains(&n)),
+            FieldKind::Decimal { min, max, .. } => text.parse::<f64>().is_ok_and(|n| (*min..=*max).contains(&n)),
+            FieldKind::Select(options) => select::position(options, text).is_some(),
+            FieldKind::Checkbox => ["yes", "no"].contains(&text),
+            FieldKind::Date => date_picker::parse(text).is_some(),
+        };
+        if valid {
+            *value = text.to_string();
+        }
+    }
+
+    /// Whether the field uses Up and Down itself, rather than leaving them to move focus.
+    pub fn uses_up_down(&self) -> bool {
+        matches!(self, FieldKind::Multiline | FieldKind::Select(_) | FieldKind::Date)
+    }
+
+    /// Whether Enter belongs to the field rather than leaving it.
+    pub fn uses_enter(&self) -> bool {
+        *self == FieldKind::Multiline
+    }
+}
+
+/// One field of a form, named by the label it is drawn with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub label: String,
+    pub kind: FieldKind,
+}
+
+/// The fields of a screen's form, declared in the order they are laid out. Screens draw their
+/// inputs from it and keys are handled by the kind of the focused field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Form {
+    fields: Vec<Field>,
+}
+
+impl Form {
+    pub fn new() -> Self {
+        Form::default()
+    }
+
+    pub fn field(mut self, label: &str, kind: FieldKind) -> Self {
+        self.fields.push(Field { label: label.to_string(), kind });
+        self
+    }
+
+    pub fn text(self, label: &str) -> Self {
+        self.field(label, FieldKind::Text)
+    }
+
+    pub fn multiline(self, label: &str) -> Self {
+        self.field(label, FieldKind::Multiline)
+    }
+
+    /// A whole number from `min` to `max`, which stepping starts at `min`.
+    pub fn number(self, label: &str, min: i64, max: i64) -> Self {
+        self.field(label, FieldKind::Number { min, max, start: min })
+    }
+
+    pub fn decimal(self, label: &str, min: f64, max: f64, step: f64) -> Self {
+        self.field(label, FieldKind::Decimal { min, max, step })
+    }
+
+    pub fn select<S: ToString>(self, label: &str, options: impl IntoIterator<Item = S>) -> Self {
+        self.field(label, FieldKind::Select(options.into_iter().map(|option| option.to_string()).collect()))
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// The kind of the field with this label, if the form has one.
+    pub fn kind(&self, label: &str) -> Option<&FieldKind> {
+        self.fields.iter().find(|field| field.label == label).map(|field| &field.kind)
+    }
+
+    /// The form's values as typed, read from the text `inputs` keeps them as by label.
+    pub fn values<'a>(&'a self, inputs: &'a HashMap<String, String>) -> Values<'a> {
+        Values { form: self, inputs }
+    }
+}
+
+/// A form's values, each read as the kind of its field. Reading one that isn't valid for its
+/// field gives the message to show.
+pub struct Values<'a> {
+    form: &'a Form,
+    inputs: &'a HashMap<String, String>,
+}
+
+impl Values<'_> {
+    /// The text of a field as it was typed.
+    pub fn text(&self, label: &str) -> String {
+        self.inputs.get(label).cloned().unwrap_or_default()
+    }
+
+    /// The trimmed text of a field, or `None` if it was left blank.
+    fn filled(&self, label: &str) -> Option<&str> {
+        self.inputs.get(label).map(|value| value.trim()).filter(|value| !value.is_empty())
+    }
+
+    /// A whole number field, checked against its range and converted to the type it is stored as.
+    pub fn number<T: TryFrom<i64>>(&self, label: &str) -> Result<Option<T>, String> {
+        let Some(FieldKind::Number { min, max, .. }) = self.form.kind(label) else {
+            panic!("'{}' isn't a number field", label);
+        };
+        let Some(value) = self.filled(label) else {
+            return Ok(None);
+        };
+        value
+            .parse::<i64>()
+            .ok()
+            .filter(|n| (*min..=*max).contains(n))
+            .and_then(|n| T::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| format!("'{}' needs a whole number from {} to {}, got '{}'", label, min, max, value))
+    }
+
+    /// A decimal field, checked against its range.
+    pub fn decimal(&self, label: &str) -> Result<Option<f64>, String> {
+        let Some(FieldKind::Decimal { min, max, .. }) = self.form.kind(label) else {
+            panic!("'{}' isn't a decimal field", label);
+        };
+        let Some(value) = self.filled(label) else {
+            return Ok(None);
+        };
+        match value.parse::<f64>() {
+            Ok(n) if (*min..=*max).contains(&n) => Ok(Some(n)),
+            Ok(_) => Err(format!("'{}' needs a number from {} to {}, got '{}'", label, min, max, value)),
+            Err(_) => Err(format!("'{}' needs a number, got '{}'", label, value)),
+        }
+    }
+
+    /// A field that has to be filled in.
+    pub fn required<T>(&self, label: &str, read: impl FnOnce(&Self, &str) -> Result<Option<T>, String>) -> Result<T, String> {
+        read(self, label)?.ok_or_else(|| format!("'{}' is required", label))
+    }
+
+    /// A field whose text is parsed as `T`, such as a select of a type's names.
+    pub fn parse<T>(&self, label: &str) -> Result<Option<T>, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.filled(label)
+            .map(|value| value.parse().map_err(|e| format!("Bad value for '{}': {}", label, e)))
+            .transpose()
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{cursor::MoveTo, queue};
+use image::{imageops::FilterType, DynamicImage};
+use ratatui::{prelude::*, widgets::*};
+
+/// How images are drawn in the terminal.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageProtocol {
+    /// The kitty graphics protocol, also spoken by WezTerm and Ghostty.
+    Kitty,
+    /// Coloured `▀` characters, two pixels per cell. Works anywhere with true colour.
+    HalfBlocks,
+}
+
+impl ImageProtocol {
+    pub fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || term_program == "WezTerm"
+        {
+            ImageProtocol::Kitty
+        } else {
+            ImageProtocol::HalfBlocks
+        }
+    }
+}
+
+/// Draws an image scaled to fit its area, centered, out of half blocks.
+pub struct HalfBlockImage<'a> {
+    image: &'a DynamicImage,
+}
+
+impl<'a> HalfBlockImage<'a> {
+    pub fn new(image: &'a DynamicImage) -> Self {
+        Self { image }
+    }
+}
+
+impl Widget for HalfBlockImage<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        // Each cell is two pixels tall, which keeps the image roughly square
+        let scaled = self
+            .image
+            .resize(area.width.into(), u32::from(area.height) * 2, FilterType::Triangle)
+            .to_rgb8();
+        let width = scaled.width() as u16;
+        let height = scaled.height().div_ceil(2) as u16;
+        let left = area.x + (area.width - width) / 2;
+        let top = area.y + (area.height - height) / 2;
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = |row: u32| {
+                    let [r, g, b] = scaled.get_pixel(x.into(), row.min(scaled.height() - 1)).0;
+                    Color::Rgb(r, g, b)
+                };
+                let row = u32::from(y) * 2;
+                buf[(left + x, top + y)]
+                    .set_char('▀')
+                    .set_fg(pixel(row))
+                    .set_bg(pixel(row + 1));
+            }
+        }
+    }
+}
+
+/// Reserves an area for a kitty graphics placement by clearing it, since the image itself
+/// is written straight to the terminal after the frame is drawn.
+pub struct ImagePlaceholder;
+
+impl Widget for ImagePlaceholder {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+    }
+}
+
+/// Transmits a PNG and displays it scaled into `area`, using the kitty graphics protocol.
+pub fn kitty_display<W: Write>(out: &mut W, id: i64, png: &[u8], area: Rect) -> io::Result<()> {
+    queue!(out, MoveTo(area.x, area.y))?;
+    let encoded = STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            // q=2 stops the terminal replying, C=1 keeps the cursor where it is
+            write!(
+                out,
+                "\x1b_Ga=T,f=100,i={},c={},r={},q=2,C=1,m={};",
+                // Kitty ids are 32 bit and must not be zero
+                (id as u32).max(1),
+                area.width,
+                area.height,
+                more
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    out.flush()
+}
+
+/// Removes every image placed with `kitty_display`.
+pub fn kitty_clear<W: Write>(out: &mut W) -> io::Result<()> {
+    write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+    out.flush()
+}
@@ -0,0 +1,65 @@
+use ratatui::prelude::*;
+
+/// Renders the subset of markdown used in house rules: `#` headings, `-`/`*` bullets,
+/// `**bold**`, `*italic*`/`_italic_` and `` `code` ``. Anything else is shown as written.
+pub fn markdown_lines(text: &str) -> Vec<Line<'static>> {
+    text.lines().map(markdown_line).collect()
+}
+
+fn markdown_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let heading_level = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&heading_level) && trimmed[heading_level..].starts_with(' ') {
+        let style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        return Line::from(Span::styled(trimmed[heading_level + 1..].to_string(), style));
+    }
+
+    let indent = &line[..line.len() - trimmed.len()];
+    let (prefix, rest) = match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+        Some(rest) => (format!("{}• ", indent), rest),
+        None => (indent.to_string(), trimmed),
+    };
+    let mut spans = vec![Span::raw(prefix)];
+    spans.extend(inline_spans(rest));
+    Line::from(spans)
+}
+
+/// Splits text on `**`, `*`, `_` and `` ` `` markers. Unclosed markers are left as text.
+fn inline_spans(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let marker = ["**", "`", "*", "_"].into_iter().find(|marker| rest.starts_with(marker));
+        let styled = marker.and_then(|marker| {
+            let inner = &rest[marker.len()..];
+            let end = inner.find(marker).filter(|end| *end > 0)?;
+            let style = match marker {
+                "**" => Style::default().add_modifier(Modifier::BOLD),
+                "`" => Style::default().fg(Color::Yellow),
+                _ => Style::default().add_modifier(Modifier::ITALIC),
+            };
+            Some((Span::styled(inner[..end].to_string(), style), marker.len() * 2 + end))
+        });
+
+        match styled {
+            Some((span, consumed)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(span);
+                rest = &rest[consumed..];
+            }
+            None => {
+                let ch = rest.chars().next().expect("rest is not empty");
+                plain.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
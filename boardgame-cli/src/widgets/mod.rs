@@ -1 +1,10 @@
-pub mod button;
\ No newline at end of file
+pub mod button;
+pub mod checkbox;
+pub mod date_picker;
+pub mod form;
+pub mod image;
+pub mod markdown;
+pub mod select;
+pub mod spinner;
+pub mod text_input;
+pub mod textarea;
@@ -0,0 +1,113 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+/// What an option is shown as. An empty option leaves the value unset.
+fn label(option: &str) -> &str {
+    if option.is_empty() { "(none)" } else { option }
+}
+
+/// Which option `value` is, ignoring case as custom fields do.
+pub fn position(options: &[String], value: &str) -> Option<usize> {
+    options.iter().position(|option| option.eq_ignore_ascii_case(value.trim()))
+}
+
+/// Applies a key to the chosen option, returning whether the key was one. Down, Right and +
+/// choose the next option, Up, Left and - the previous one, and Home and End the first and last.
+/// A letter chooses the next option starting with it.
+pub fn handle_key(options: &[String], selected: &mut Option<usize>, key: KeyEvent) -> bool {
+    let last = options.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Down | KeyCode::Right | KeyCode::Char('+') => {
+            *selected = Some(selected.map_or(0, |i| (i + 1).min(last)))
+        }
+        KeyCode::Up | KeyCode::Left | KeyCode::Char('-') => *selected = Some(selected.map_or(0, |i| i.saturating_sub(1))),
+        KeyCode::Home => *selected = Some(0),
+        KeyCode::End => *selected = Some(last),
+        KeyCode::Char(ch) => {
+            let after = selected.map_or(0, |i| i + 1);
+            let starts_with = |i: &usize| {
+                options[*i].chars().next().is_some_and(|first| first.eq_ignore_ascii_case(&ch))
+            };
+            if let Some(i) = (after..options.len()).chain(0..after).find(starts_with) {
+                *selected = Some(i);
+            }
+        }
+        _ => return false,
+    }
+    if options.is_empty() {
+        *selected = None;
+    }
+    true
+}
+
+/// A dropdown choice of one of several options. The list itself is drawn as a `SelectList`
+/// while the select is focused.
+pub struct Select<'a> {
+    options: &'a [String],
+    selected: Option<usize>,
+    block: Option<Block<'a>>,
+    style: Style,
+}
+
+impl<'a> Select<'a> {
+    pub fn new(options: &'a [String], selected: Option<usize>) -> Self {
+        Select { options, selected, block: None, style: Style::default() }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        Select { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Select { style, ..self }
+    }
+}
+
+impl Widget for Select<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if inner.is_empty() {
+            return;
+        }
+        let arrow = Span::styled("▾", Style::default().fg(Color::DarkGray));
+        let text = self.selected.and_then(|i| self.options.get(i)).map_or("(none)", |option| label(option));
+        let width = inner.width.saturating_sub(2);
+        Line::raw(text).render(Rect { width, ..inner }, buf);
+        arrow.render(Rect { x: inner.right() - 1, width: 1, ..inner }, buf);
+    }
+}
+
+/// The open list of a select's options, with the chosen one highlighted.
+pub struct SelectList<'a> {
+    options: &'a [String],
+    selected: Option<usize>,
+}
+
+impl<'a> SelectList<'a> {
+    pub fn new(options: &'a [String], selected: Option<usize>) -> Self {
+        SelectList { options, selected }
+    }
+
+    /// Rows the list needs to show every option, its borders included.
+    pub fn height(&self) -> u16 {
+        self.options.len() as u16 + 2
+    }
+}
+
+impl Widget for SelectList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let list = List::new(self.options.iter().map(|option| label(option)))
+            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(list, area, buf, &mut ListState::default().with_selected(self.selected));
+    }
+}
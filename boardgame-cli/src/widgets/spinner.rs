@@ -0,0 +1,198 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+/// Applies a key to a spinner's value, returning whether the key was one.
+///
+/// Plus and Right step up and minus and Left step down, staying from `min` to `max`. An empty
+/// spinner steps to `start`. Digits are typed onto the end as long as the value stays at most `max`,
+/// Backspace takes the last one off and Delete empties the spinner.
+pub fn handle_key(value: &mut Option<i64>, min: i64, max: i64, start: i64, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('+') | KeyCode::Right => {
+            *value = Some(value.map_or(start, |v| v.saturating_add(1)).clamp(min, max))
+        }
+        KeyCode::Char('-') | KeyCode::Left => {
+            *value = Some(value.map_or(start, |v| v.saturating_sub(1)).clamp(min, max))
+        }
+        KeyCode::Char(ch) if ch.is_ascii_digit() => {
+            let digit = ch.to_digit(10).expect("checked to be a digit") as i64;
+            let typed = value.unwrap_or(0).saturating_mul(10).saturating_add(digit);
+            if typed <= max {
+                *value = Some(typed);
+            }
+        }
+        KeyCode::Backspace => *value = value.map(|v| v / 10).filter(|v| *v != 0),
+        KeyCode::Delete => *value = None,
+        _ => return false,
+    }
+    true
+}
+
+/// Applies a key to a spinner of decimal numbers, kept as the text typed so far so that a
+/// point can be typed before the digits after it.
+///
+/// Plus and Right step up by `step` and minus and Left step down, staying from `min` to `max`.
+/// An empty spinner steps to the nearest of those to 0. Digits and a point are typed onto the
+/// end as long as the value stays at most `max`, Backspace takes the last character off and
+/// Delete empties the spinner.
+pub fn handle_decimal_key(value: &mut String, min: f64, max: f64, step: f64, key: KeyEvent) -> bool {
+    let current = value.trim().parse::<f64>().ok();
+    let stepped = |delta: f64| {
+        let next = current.map_or(0.0, |v| v + delta).clamp(min, max);
+        // Rounded, so repeated steps of 0.1 don't show as 0.30000000000000004
+        ((next * 1e6).round() / 1e6).to_string()
+    };
+    match key.code {
+        KeyCode::Char('+') | KeyCode::Right => *value = stepped(step),
+        KeyCode::Char('-') | KeyCode::Left => *value = stepped(-step),
+        KeyCode::Char(ch) if ch.is_ascii_digit() || ch == '.' => {
+            let typed = format!("{}{}", value.trim(), ch);
+            if typed.parse::<f64>().is_ok_and(|v| v <= max) {
+                *value = typed;
+            }
+        }
+        KeyCode::Backspace => {
+            value.pop();
+        }
+        KeyCode::Delete => value.clear(),
+        _ => return false,
+    }
+    true
+}
+
+/// A number input, shown with arrows hinting that it steps. While empty it shows its range.
+pub struct Spinner<'a> {
+    value: Option<String>,
+    range: String,
+    block: Option<Block<'a>>,
+    style: Style,
+}
+
+impl<'a> Spinner<'a> {
+    pub fn new(value: Option<i64>, min: i64, max: i64) -> Self {
+        Spinner::with_range(value.map(|v| v.to_string()), format!("{}-{}", min, max))
+    }
+
+    /// A spinner of decimal numbers, showing `value` as typed.
+    pub fn decimal(value: &str, min: f64, max: f64) -> Self {
+        let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        // Unbounded ends aren't worth showing
+        let range = match (min > f64::MIN, max < f64::MAX) {
+            (true, true) => format!("{}-{}", min, max),
+            (true, false) => format!("{}+", min),
+            (false, true) => format!("up to {}", max),
+            (false, false) => "number".to_string(),
+        };
+        Spinner::with_range(value, range)
+    }
+
+    fn with_range(value: Option<String>, range: String) -> Self {
+        Spinner { value, range, block: None, style: Style::default() }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        Spinner { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Spinner { style, ..self }
+    }
+}
+
+impl Widget for Spinner<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        let arrow = Style::default().fg(Color::DarkGray);
+        let value = match self.value {
+            Some(value) => Span::raw(value),
+            None => Span::styled(self.range, arrow.add_modifier(Modifier::ITALIC)),
+        };
+        Line::from(vec![Span::styled("- ", arrow), value, Span::styled(" +", arrow)]).render(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_stepping_and_typing() {
+        let mut value = None;
+        assert!(handle_key(&mut value, 1, 10, 2, press(KeyCode::Char('+'))));
+        assert_eq!(value, Some(2));
+
+        // Stepping stops at either end, and a value out of range steps back into it
+        for _ in 0..20 {
+            handle_key(&mut value, 1, 10, 2, press(KeyCode::Right));
+        }
+        assert_eq!(value, Some(10));
+        for _ in 0..20 {
+            handle_key(&mut value, 1, 10, 2, press(KeyCode::Char('-')));
+        }
+        assert_eq!(value, Some(1));
+        let mut value = Some(50);
+        handle_key(&mut value, 1, 10, 2, press(KeyCode::Left));
+        assert_eq!(value, Some(10));
+        let mut value = Some(i64::MAX);
+        handle_key(&mut value, 0, i64::MAX, 0, press(KeyCode::Right));
+        assert_eq!(value, Some(i64::MAX));
+
+        // A digit that would go over the maximum isn't typed
+        let mut value = None;
+        for digit in ['1', '2', '5'] {
+            handle_key(&mut value, 1, 120, 60, press(KeyCode::Char(digit)));
+        }
+        assert_eq!(value, Some(12));
+        handle_key(&mut value, 1, 120, 60, press(KeyCode::Backspace));
+        assert_eq!(value, Some(1));
+        handle_key(&mut value, 1, 120, 60, press(KeyCode::Backspace));
+        assert_eq!(value, None);
+        handle_key(&mut value, 1, 120, 60, press(KeyCode::Char('9')));
+        assert!(handle_key(&mut value, 1, 120, 60, press(KeyCode::Delete)));
+        assert_eq!(value, None);
+        assert!(!handle_key(&mut value, 1, 120, 60, press(KeyCode::Char('x'))));
+    }
+
+    #[test]
+    fn test_decimal() {
+        let mut value = String::new();
+        assert!(handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Char('+'))));
+        assert_eq!(value, "1");
+        for _ in 0..3 {
+            handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Right));
+        }
+        assert_eq!(value, "1.3");
+        for _ in 0..60 {
+            handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Right));
+        }
+        assert_eq!(value, "5");
+
+        // A point can be typed before the digits after it, but not past the maximum
+        handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Delete));
+        for ch in ['7', '3', '.', '.', '2', '5'] {
+            handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Char(ch)));
+        }
+        assert_eq!(value, "3.25");
+        handle_decimal_key(&mut value, 1.0, 5.0, 0.1, press(KeyCode::Backspace));
+        assert_eq!(value, "3.2");
+
+        // Unbounded, as custom number fields are, an empty spinner steps from 0
+        let mut value = String::new();
+        handle_decimal_key(&mut value, f64::MIN, f64::MAX, 1.0, press(KeyCode::Char('-')));
+        handle_decimal_key(&mut value, f64::MIN, f64::MAX, 1.0, press(KeyCode::Left));
+        assert_eq!(value, "-1");
+    }
+}
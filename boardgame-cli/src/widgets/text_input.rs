@@ -0,0 +1,317 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+/// Where the cursor is in a text input, what is selected and how far it is scrolled. The text
+/// itself lives with the app's other input values, and is passed in to edit it.
+///
+/// Positions count characters, not bytes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInputState {
+    pub(super) cursor: usize,
+    /// The other end of the selection, which runs from here to the cursor.
+    pub(super) anchor: Option<usize>,
+    /// The first character shown, moved to keep the cursor in view.
+    scroll: usize,
+    /// Where the terminal cursor goes, set when rendered.
+    screen_cursor: Option<Position>,
+}
+
+impl TextInputState {
+    /// Puts the cursor after the last character, as when an input is first selected.
+    pub fn at_end(value: &str) -> Self {
+        TextInputState { cursor: value.chars().count(), ..Default::default() }
+    }
+
+    pub fn screen_cursor(&self) -> Option<Position> {
+        self.screen_cursor
+    }
+
+    /// The selected character range, if anything is selected.
+    pub(super) fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor.filter(|anchor| *anchor != self.cursor)?;
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Applies an editing key to `value`, returning whether the key was one.
+    pub fn handle_key(&mut self, value: &mut String, key: KeyEvent) -> bool {
+        // The value may have been replaced since the last key
+        self.cursor = self.cursor.min(value.chars().count());
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => {
+                self.anchor = Some(0);
+                self.cursor = value.chars().count();
+            }
+            KeyCode::Char('w') if ctrl => self.delete_to(value, word_start(value, self.cursor)),
+            KeyCode::Char(ch) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => self.insert(value, &ch.to_string()),
+            KeyCode::Backspace if ctrl || key.modifiers.contains(KeyModifiers::ALT) => {
+                self.delete_to(value, word_start(value, self.cursor))
+            }
+            KeyCode::Backspace => self.delete_to(value, self.cursor.saturating_sub(1)),
+            KeyCode::Delete if ctrl => self.delete_to(value, word_end(value, self.cursor)),
+            KeyCode::Delete => self.delete_to(value, (self.cursor + 1).min(value.chars().count())),
+            KeyCode::Left if ctrl => self.move_to(word_start(value, self.cursor), shift),
+            KeyCode::Left => self.move_to(self.cursor.saturating_sub(1), shift),
+            KeyCode::Right if ctrl => self.move_to(word_end(value, self.cursor), shift),
+            KeyCode::Right => self.move_to((self.cursor + 1).min(value.chars().count()), shift),
+            KeyCode::Home => self.move_to(0, shift),
+            KeyCode::End => self.move_to(value.chars().count(), shift),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Inserts pasted text at the cursor. Inputs are one line, so line breaks become spaces.
+    pub fn paste(&mut self, value: &mut String, text: &str) {
+        self.cursor = self.cursor.min(value.chars().count());
+        let text = text.trim_end_matches(['\r', '\n']).replace("\r\n", " ").replace(['\r', '\n'], " ");
+        self.insert(value, &text);
+    }
+
+    pub(super) fn move_to(&mut self, position: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = position;
+    }
+
+    /// Replaces the selection, if any, with `text`.
+    pub(super) fn insert(&mut self, value: &mut String, text: &str) {
+        if let Some((start, end)) = self.selection() {
+            self.remove(value, start, end);
+        }
+        self.anchor = None;
+        value.insert_str(byte_index(value, self.cursor), text);
+        self.cursor += text.chars().count();
+    }
+
+    /// Deletes the selection or, without one, from the cursor to `position`.
+    fn delete_to(&mut self, value: &mut String, position: usize) {
+        let (start, end) = self
+            .selection()
+            .unwrap_or((position.min(self.cursor), position.max(self.cursor)));
+        self.remove(value, start, end);
+    }
+
+    fn remove(&mut self, value: &mut String, start: usize, end: usize) {
+        value.replace_range(byte_index(value, start)..byte_index(value, end), "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+}
+
+fn byte_index(value: &str, position: usize) -> usize {
+    value.char_indices().nth(position).map_or(value.len(), |(i, _)| i)
+}
+
+/// Where the word before `position` starts, skipping spaces right before it.
+fn word_start(value: &str, position: usize) -> usize {
+    let chars: Vec<char> = value.chars().collect();
+    let mut i = position;
+    while i > 0 && chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    while i > 0 && !chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    i
+}
+
+/// Where the word after `position` ends, skipping spaces right after it.
+fn word_end(value: &str, position: usize) -> usize {
+    let chars: Vec<char> = value.chars().collect();
+    let mut i = position;
+    while i < chars.len() && chars[i].is_whitespace() {
+        i += 1;
+    }
+    while i < chars.len() && !chars[i].is_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// A one-line text input. Text too long for it scrolls sideways to keep the cursor in view.
+pub struct TextInput<'a> {
+    value: &'a str,
+    block: Option<Block<'a>>,
+    style: Style,
+    placeholder: Option<&'a str>,
+    mask: Option<char>,
+    focused: bool,
+}
+
+impl<'a> TextInput<'a> {
+    pub fn new(value: &'a str) -> Self {
+        TextInput { value, block: None, style: Style::default(), placeholder: None, mask: None, focused: false }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        TextInput { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        TextInput { style, ..self }
+    }
+
+    /// Shown dimmed while the input is empty.
+    pub fn placeholder(self, placeholder: Option<&'a str>) -> Self {
+        TextInput { placeholder, ..self }
+    }
+
+    /// Shows every character as `mask`, for values that shouldn't be on screen.
+    #[allow(dead_code)] // No form has a secret field yet
+    pub fn mask(self, mask: char) -> Self {
+        TextInput { mask: Some(mask), ..self }
+    }
+
+    /// Whether to show the cursor and selection.
+    pub fn focused(self, focused: bool) -> Self {
+        TextInput { focused, ..self }
+    }
+}
+
+impl StatefulWidget for TextInput<'_> {
+    type State = TextInputState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TextInputState) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        state.screen_cursor = None;
+        if inner.is_empty() {
+            return;
+        }
+
+        let chars: Vec<char> = match self.mask {
+            Some(mask) => self.value.chars().map(|_| mask).collect(),
+            None => self.value.chars().collect(),
+        };
+        state.cursor = state.cursor.min(chars.len());
+        // One column is kept free at the end for the cursor after the last character
+        let width = inner.width as usize;
+        if state.cursor < state.scroll {
+            state.scroll = state.cursor;
+        } else if state.cursor >= state.scroll + width {
+            state.scroll = state.cursor + 1 - width;
+        }
+        state.scroll = state.scroll.min(chars.len().saturating_sub(width - 1));
+
+        if chars.is_empty() {
+            if let Some(placeholder) = self.placeholder {
+                let style = self.style.fg(Color::DarkGray).add_modifier(Modifier::ITALIC);
+                buf.set_stringn(inner.x, inner.y, placeholder, width, style);
+            }
+        }
+        let selection = state.selection().filter(|_| self.focused);
+        for (column, (i, ch)) in chars.iter().enumerate().skip(state.scroll).take(width).enumerate() {
+            let mut style = self.style;
+            if selection.is_some_and(|(start, end)| (start..end).contains(&i)) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            buf.set_string(inner.x + column as u16, inner.y, ch.to_string(), style);
+        }
+        if self.focused {
+            state.screen_cursor = Some(Position::new(inner.x + (state.cursor - state.scroll) as u16, inner.y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn type_keys(state: &mut TextInputState, value: &mut String, keys: &[KeyEvent]) {
+        for &key in keys {
+            assert!(state.handle_key(value, key), "{:?} isn't an editing key", key);
+        }
+    }
+
+    #[test]
+    fn test_editing() {
+        let none = KeyModifiers::NONE;
+        let mut value = "Brass".to_string();
+        let mut state = TextInputState::at_end(&value);
+        assert_eq!(state.cursor, 5);
+
+        // Typing and deleting work at the cursor, counting characters rather than bytes
+        let keys = [key(KeyCode::Home, none), key(KeyCode::Char('É'), none), key(KeyCode::Char(' '), none)];
+        type_keys(&mut state, &mut value, &keys);
+        assert_eq!((value.as_str(), state.cursor), ("É Brass", 2));
+        type_keys(&mut state, &mut value, &[key(KeyCode::Backspace, none), key(KeyCode::Delete, none)]);
+        assert_eq!((value.as_str(), state.cursor), ("Érass", 1));
+        type_keys(&mut state, &mut value, &[key(KeyCode::Left, none), key(KeyCode::Left, none)]);
+        assert_eq!(state.cursor, 0);
+        type_keys(&mut state, &mut value, &[key(KeyCode::End, none), key(KeyCode::Right, none)]);
+        assert_eq!(state.cursor, 5);
+
+        // Whole words
+        let mut value = "Terraforming  Mars".to_string();
+        let mut state = TextInputState::at_end(&value);
+        type_keys(&mut state, &mut value, &[key(KeyCode::Left, KeyModifiers::CONTROL)]);
+        assert_eq!(state.cursor, 14);
+        type_keys(&mut state, &mut value, &[key(KeyCode::Char('w'), KeyModifiers::CONTROL)]);
+        assert_eq!((value.as_str(), state.cursor), ("Mars", 0));
+        type_keys(&mut state, &mut value, &[key(KeyCode::Delete, KeyModifiers::CONTROL)]);
+        assert_eq!(value, "");
+
+        // Keys that aren't for editing are left to the app
+        assert!(!state.handle_key(&mut value, key(KeyCode::Enter, none)));
+        assert!(!state.handle_key(&mut value, key(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+
+        // The value may have been shortened since the cursor was put at its end
+        let mut value = "Root".to_string();
+        let mut state = TextInputState::at_end("Root: The Riverfolk");
+        type_keys(&mut state, &mut value, &[key(KeyCode::Char('!'), none)]);
+        assert_eq!(value, "Root!");
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut value = "Azul: Summer Pavilion".to_string();
+        let mut state = TextInputState::at_end(&value);
+        let select_word = key(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        type_keys(&mut state, &mut value, &[select_word]);
+        assert_eq!(state.selection(), Some((13, 21)));
+
+        // Typing replaces the selection
+        type_keys(&mut state, &mut value, &[key(KeyCode::Char('G'), KeyModifiers::SHIFT)]);
+        assert_eq!((value.as_str(), state.cursor, state.selection()), ("Azul: Summer G", 14, None));
+
+        // Moving without Shift drops it, and Backspace deletes it
+        type_keys(&mut state, &mut value, &[key(KeyCode::Left, KeyModifiers::SHIFT), key(KeyCode::Right, KeyModifiers::NONE)]);
+        assert_eq!(state.selection(), None);
+        type_keys(&mut state, &mut value, &[key(KeyCode::Char('a'), KeyModifiers::CONTROL), key(KeyCode::Backspace, KeyModifiers::NONE)]);
+        assert_eq!((value.as_str(), state.cursor), ("", 0));
+    }
+
+    #[test]
+    fn test_paste_and_scroll() {
+        let mut value = "Spirit ".to_string();
+        let mut state = TextInputState::at_end(&value);
+        state.paste(&mut value, "Island\r\nJagged Earth\n");
+        assert_eq!((value.as_str(), state.cursor), ("Spirit Island Jagged Earth", 26));
+
+        // Only the end fits, with a column left for the cursor after it
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+        TextInput::new(&value).focused(true).render(buf.area, &mut buf, &mut state);
+        assert_eq!(buf, Buffer::with_lines(["ged Earth "]));
+        assert_eq!(state.screen_cursor(), Some(Position::new(9, 0)));
+        state.handle_key(&mut value, key(KeyCode::Home, KeyModifiers::NONE));
+        TextInput::new(&value).focused(true).render(buf.area, &mut buf, &mut state);
+        assert_eq!(buf, Buffer::with_lines(["Spirit Isl"]));
+        assert_eq!(state.screen_cursor(), Some(Position::new(0, 0)));
+    }
+}
@@ -0,0 +1,262 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::text_input::TextInputState;
+
+/// A row of a textarea as drawn: a character range of the value, not counting its line break.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Row {
+    start: usize,
+    end: usize,
+    /// Whether the line continues on the next row rather than ending in a line break.
+    wrapped: bool,
+}
+
+/// Splits `value` into rows of at most `width` characters, breaking after spaces where it can.
+/// A width of 0 leaves lines unwrapped.
+fn wrap(value: &str, width: usize) -> Vec<Row> {
+    let chars: Vec<char> = value.chars().collect();
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    for line in value.split('\n') {
+        let line_end = line_start + line.chars().count();
+        let mut start = line_start;
+        while width > 0 && line_end - start > width {
+            let end = (start + 1..=start + width).rev().find(|&i| chars[i - 1] == ' ').unwrap_or(start + width);
+            rows.push(Row { start, end, wrapped: true });
+            start = end;
+        }
+        rows.push(Row { start, end: line_end, wrapped: false });
+        line_start = line_end + 1;
+    }
+    rows
+}
+
+/// The row the cursor is drawn on. At a wrapped row's end it is at the start of the next one.
+fn row_of(rows: &[Row], position: usize) -> usize {
+    rows.iter().rposition(|row| row.start <= position).unwrap_or(0)
+}
+
+/// Editing state of a textarea. Editing within a line works as in a `TextInputState`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextAreaState {
+    input: TextInputState,
+    /// The first row shown, moved to keep the cursor in view.
+    scroll: usize,
+    /// Characters per row as last drawn, which moving up and down depends on.
+    width: usize,
+    screen_cursor: Option<Position>,
+}
+
+impl TextAreaState {
+    pub fn at_end(value: &str) -> Self {
+        TextAreaState { input: TextInputState::at_end(value), ..Default::default() }
+    }
+
+    pub fn screen_cursor(&self) -> Option<Position> {
+        self.screen_cursor
+    }
+
+    /// Applies an editing key to `value`, returning whether the key was one. Enter starts a new
+    /// line, and Up, Down, Home and End move by rows as drawn.
+    pub fn handle_key(&mut self, value: &mut String, key: KeyEvent) -> bool {
+        self.input.cursor = self.input.cursor.min(value.chars().count());
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let rows = wrap(value, self.width);
+        let row = row_of(&rows, self.input.cursor);
+        match key.code {
+            KeyCode::Enter => self.input.insert(value, "\n"),
+            KeyCode::Up if row > 0 => self.input.move_to(self.column_in(&rows, row, row - 1), shift),
+            KeyCode::Up => self.input.move_to(0, shift),
+            KeyCode::Down if row + 1 < rows.len() => self.input.move_to(self.column_in(&rows, row, row + 1), shift),
+            KeyCode::Down => self.input.move_to(value.chars().count(), shift),
+            KeyCode::Home if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.move_to(rows[row].start, shift)
+            }
+            KeyCode::End if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.move_to(Self::last_position(rows[row]), shift)
+            }
+            // Including Ctrl+Home and Ctrl+End, which go to either end of the whole text
+            _ => return self.input.handle_key(value, key),
+        }
+        true
+    }
+
+    /// Inserts pasted text at the cursor, keeping its line breaks.
+    pub fn paste(&mut self, value: &mut String, text: &str) {
+        self.input.cursor = self.input.cursor.min(value.chars().count());
+        self.input.insert(value, &text.replace("\r\n", "\n").replace('\r', "\n"));
+    }
+
+    /// The position in row `to` in the same column as the cursor is in row `from`.
+    fn column_in(&self, rows: &[Row], from: usize, to: usize) -> usize {
+        let column = self.input.cursor - rows[from].start;
+        (rows[to].start + column).min(Self::last_position(rows[to]))
+    }
+
+    /// The last position the cursor can have on a row. On a wrapped row the end belongs to the next.
+    fn last_position(row: Row) -> usize {
+        if row.wrapped { row.end - 1 } else { row.end }
+    }
+}
+
+/// A text input for several lines, wrapping long lines and scrolling to keep the cursor in view.
+pub struct TextArea<'a> {
+    value: &'a str,
+    block: Option<Block<'a>>,
+    style: Style,
+    placeholder: Option<&'a str>,
+    focused: bool,
+}
+
+impl<'a> TextArea<'a> {
+    pub fn new(value: &'a str) -> Self {
+        TextArea { value, block: None, style: Style::default(), placeholder: None, focused: false }
+    }
+
+    pub fn block(self, block: Block<'a>) -> Self {
+        TextArea { block: Some(block), ..self }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        TextArea { style, ..self }
+    }
+
+    /// Shown dimmed while the textarea is empty.
+    pub fn placeholder(self, placeholder: Option<&'a str>) -> Self {
+        TextArea { placeholder, ..self }
+    }
+
+    /// Whether to show the cursor and selection.
+    pub fn focused(self, focused: bool) -> Self {
+        TextArea { focused, ..self }
+    }
+}
+
+impl StatefulWidget for TextArea<'_> {
+    type State = TextAreaState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TextAreaState) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        state.screen_cursor = None;
+        if inner.is_empty() {
+            return;
+        }
+
+        // One column is kept free for the cursor after a full row
+        state.width = (inner.width as usize).saturating_sub(1).max(1);
+        let chars: Vec<char> = self.value.chars().collect();
+        let rows = wrap(self.value, state.width);
+        state.input.cursor = state.input.cursor.min(chars.len());
+        let cursor_row = row_of(&rows, state.input.cursor);
+        let height = inner.height as usize;
+        if cursor_row < state.scroll {
+            state.scroll = cursor_row;
+        } else if cursor_row >= state.scroll + height {
+            state.scroll = cursor_row + 1 - height;
+        }
+
+        if chars.is_empty() {
+            if let Some(placeholder) = self.placeholder {
+                let style = self.style.fg(Color::DarkGray).add_modifier(Modifier::ITALIC);
+                buf.set_stringn(inner.x, inner.y, placeholder, inner.width as usize, style);
+            }
+        }
+        let selection = state.input.selection().filter(|_| self.focused);
+        for (y, row) in rows.iter().skip(state.scroll).take(height).enumerate() {
+            for (x, i) in (row.start..row.end).enumerate() {
+                let mut style = self.style;
+                if selection.is_some_and(|(start, end)| (start..end).contains(&i)) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                buf.set_string(inner.x + x as u16, inner.y + y as u16, chars[i].to_string(), style);
+            }
+        }
+        if self.focused {
+            let row = rows[cursor_row];
+            let position = Position::new(
+                inner.x + (state.input.cursor - row.start) as u16,
+                inner.y + (cursor_row - state.scroll) as u16,
+            );
+            state.screen_cursor = Some(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_wrap() {
+        let rows = |value: &str, width| -> Vec<(usize, usize, bool)> {
+            wrap(value, width).iter().map(|row| (row.start, row.end, row.wrapped)).collect()
+        };
+        // After a space where there is one, mid-word where there isn't
+        assert_eq!(rows("Deal cards", 6), [(0, 5, true), (5, 10, false)]);
+        assert_eq!(rows("Meeples", 3), [(0, 3, true), (3, 6, true), (6, 7, false)]);
+        assert_eq!(rows("Set up\n\nPlay", 10), [(0, 6, false), (7, 7, false), (8, 12, false)]);
+        assert_eq!(rows("Deal cards", 0), [(0, 10, false)]);
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut value = "Setup".to_string();
+        let mut state = TextAreaState::at_end(&value);
+        assert!(state.handle_key(&mut value, press(KeyCode::Enter)));
+        state.paste(&mut value, "Deal five\r\ncards each");
+        assert_eq!(value, "Setup\nDeal five\ncards each");
+
+        // Up and down keep the column, or go to the end of a shorter line
+        state.handle_key(&mut value, press(KeyCode::Up));
+        assert_eq!(state.input.cursor, 15);
+        state.handle_key(&mut value, press(KeyCode::Up));
+        assert_eq!(state.input.cursor, 5);
+        state.handle_key(&mut value, press(KeyCode::Up));
+        assert_eq!(state.input.cursor, 0);
+        state.handle_key(&mut value, press(KeyCode::Down));
+        assert_eq!(state.input.cursor, 6);
+        state.handle_key(&mut value, press(KeyCode::End));
+        assert_eq!(state.input.cursor, 15);
+
+        // Within a line, keys edit as in an input
+        state.handle_key(&mut value, press(KeyCode::Backspace));
+        state.handle_key(&mut value, press(KeyCode::Char('e')));
+        assert_eq!(value, "Setup\nDeal five\ncards each");
+        state.handle_key(&mut value, press(KeyCode::Home));
+        state.handle_key(&mut value, press(KeyCode::Backspace));
+        assert_eq!((value.as_str(), state.input.cursor), ("SetupDeal five\ncards each", 5));
+        assert!(!state.handle_key(&mut value, press(KeyCode::Esc)));
+    }
+
+    #[test]
+    fn test_render_wraps_and_scrolls() {
+        let mut value = "Take two coins or one card".to_string();
+        let mut state = TextAreaState::at_end(&value);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 11, 2));
+        TextArea::new(&value).focused(true).render(buf.area, &mut buf, &mut state);
+        // Ten characters a row, showing the last two rows where the cursor is
+        assert_eq!(buf, Buffer::with_lines(["coins or   ", "one card   "]));
+        assert_eq!(state.screen_cursor(), Some(Position::new(8, 1)));
+
+        // Moving up uses the rows as drawn
+        state.handle_key(&mut value, press(KeyCode::Up));
+        state.handle_key(&mut value, press(KeyCode::Up));
+        assert_eq!(state.input.cursor, 8);
+        TextArea::new(&value).focused(true).render(buf.area, &mut buf, &mut state);
+        assert_eq!(buf, Buffer::with_lines(["Take two   ", "coins or   "]));
+        assert_eq!(state.screen_cursor(), Some(Position::new(8, 0)));
+    }
+}
@@ -0,0 +1,56 @@
+//! Creating boardgames one at a time against `create_boardgames`. Run with `cargo bench`.
+
+use boardgame_core::db::{Boardgame, BoardgameDb, Mechanic};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tempfile::{tempdir, TempDir};
+
+const GAMES: usize = 500;
+
+fn games() -> Vec<Boardgame> {
+    (0..GAMES)
+        .map(|i| Boardgame {
+            name: format!("Game {i}"),
+            min_players: 1,
+            max_players: 4,
+            play_time_minutes: 60,
+            description: "A game for benchmarking".to_string(),
+            weight: Some(2.5),
+            mechanics: vec![Mechanic::ALL[i % Mechanic::ALL.len()]],
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn empty_db() -> (TempDir, BoardgameDb) {
+    let dir = tempdir().expect("failed to create temp directory");
+    let db = BoardgameDb::new(dir.path().join("bench.db")).expect("failed to open database");
+    (dir, db)
+}
+
+fn bench_create(c: &mut Criterion) {
+    let games = games();
+    let mut group = c.benchmark_group(format!("create {GAMES} boardgames"));
+    group.sample_size(10);
+    group.bench_function("one at a time", |b| {
+        b.iter_batched(
+            empty_db,
+            |(_dir, db)| {
+                for game in &games {
+                    db.create_boardgame(game).expect("failed to create boardgame");
+                }
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("create_boardgames", |b| {
+        b.iter_batched(
+            empty_db,
+            |(_dir, db)| db.create_boardgames(&games).expect("failed to create boardgames"),
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_create);
+criterion_main!(benches);
@@ -1,9 +1,59 @@
-use rusqlite::{Connection, params};
-use std::path::Path;
+use chrono::{Days, NaiveDateTime, Utc};
+use rusqlite::{Connection, Params, Row, params, types::Type};
+use std::{cell::Cell, path::Path, time::Duration};
 
 use crate::errors::Error;
 
-#[derive(Debug)]
+#[cfg(feature = "async")]
+mod async_db;
+mod copies;
+mod credits;
+mod custom_fields;
+mod events;
+mod filter;
+mod history;
+mod images;
+mod import_export;
+mod mechanics;
+mod migrations;
+mod polls;
+mod pool;
+mod rules;
+mod transaction;
+
+#[cfg(feature = "async")]
+pub use async_db::AsyncBoardgameDb;
+pub use copies::{Component, GameCopy};
+pub use credits::{Company, Credit, CreditRole, Person};
+pub use custom_fields::{CustomField, CustomFieldType, CustomValue, CustomValues, DATE_FORMAT};
+pub use events::{Change, ChangeKind, DbEvent};
+pub use filter::{BoardgameFilter, BoardgameSort};
+pub use history::{FieldChange, HistoryAction, HistoryEntry, UNKNOWN_ACTOR};
+pub use images::{ImageInfo, ImageKind, THUMBNAIL_SIZE};
+pub use mechanics::Mechanic;
+pub use polls::{PlayerCountMatch, PlayerCountPoll, PlayerCountTally, PlayerCountVote};
+pub use pool::{BoardgamePool, DEFAULT_MAX_READERS, PooledReader};
+pub use rules::HouseRule;
+pub use transaction::Transaction;
+
+const BOARDGAME_COLUMNS: &str = "id, name, min_players, max_players, play_time_minutes, description,
+    weight, min_age, year_published,
+    (SELECT group_concat(mechanic) FROM boardgame_mechanics m WHERE m.boardgame_id = boardgames.id),
+    deleted_at";
+
+/// Boardgames that aren't in the trash, under the table's own name so clauses can't tell the difference.
+const LIVE_BOARDGAMES: &str = "(SELECT * FROM boardgames WHERE deleted_at IS NULL) AS boardgames";
+
+/// How long trashed boardgames are kept before `purge_expired_trash` deletes them for good.
+pub const TRASH_RETENTION_DAYS: u64 = 30;
+
+/// How long a connection waits for another one to finish writing before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const MIN_WEIGHT: f64 = 1.0;
+pub const MAX_WEIGHT: f64 = 5.0;
+
+#[derive(Debug, Clone, Default)]
 pub struct Boardgame {
     pub id: Option<i64>,
     pub name: String,
@@ -11,124 +61,254 @@ pub struct Boardgame {
     pub max_players: i32,
     pub play_time_minutes: i32,
     pub description: String,
+    /// Complexity from `MIN_WEIGHT` (light) to `MAX_WEIGHT` (heavy).
+    pub weight: Option<f64>,
+    pub min_age: Option<i32>,
+    pub year_published: Option<i32>,
+    pub mechanics: Vec<Mechanic>,
+    pub custom_values: CustomValues,
+    /// When the game was moved to the trash, if it has been.
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl Boardgame {
+    fn from_row(row: &Row) -> Result<Self, rusqlite::Error> {
+        let mechanics: Option<String> = row.get(9)?;
+        let mut mechanics = mechanics
+            .iter()
+            .flat_map(|m| m.split(','))
+            .map(|m| m.parse().map_err(|e| rusqlite::Error::FromSqlConversionFailure(9, Type::Text, Box::new(e))))
+            .collect::<Result<Vec<Mechanic>, rusqlite::Error>>()?;
+        mechanics.sort_by_key(|m| Mechanic::ALL.iter().position(|other| other == m));
+
+        Ok(Boardgame {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            min_players: row.get(2)?,
+            max_players: row.get(3)?,
+            play_time_minutes: row.get(4)?,
+            description: row.get(5)?,
+            weight: row.get(6)?,
+            min_age: row.get(7)?,
+            year_published: row.get(8)?,
+            mechanics,
+            custom_values: CustomValues::new(),
+            deleted_at: row.get(10)?,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(weight) = self.weight {
+            if !(MIN_WEIGHT..=MAX_WEIGHT).contains(&weight) {
+                return Err(Error::InvalidInput(format!(
+                    "weight must be between {} and {}, got {}",
+                    MIN_WEIGHT, MAX_WEIGHT, weight
+                )));
+            }
+        }
+        if let Some(min_age) = self.min_age {
+            if min_age < 0 {
+                return Err(Error::InvalidInput(format!("minimum age cannot be negative, got {}", min_age)));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub struct BoardgameDb {
     conn: Connection,
+    /// How many `Transaction`s are open, used to name their savepoints.
+    savepoint_depth: Cell<usize>,
+    events: events::Events,
 }
 
-
 impl BoardgameDb {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let conn = Connection::open(path)?;
-        
-        // Create table if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS boardgames (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL,
-                min_players INTEGER NOT NULL,
-                max_players INTEGER NOT NULL,
-                play_time_minutes INTEGER NOT NULL,
-                description TEXT NOT NULL
-            )",
-            [],
-        )?;
+        let mut conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Foreign keys can't be switched off in a transaction, which is where migrations need them off
+        migrations::migrate(&mut conn)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        history::install_triggers(&conn)?;
+
+        Self::from_connection(conn)
+    }
 
-        Ok(BoardgameDb { conn })
+    fn from_connection(conn: Connection) -> Result<Self, Error> {
+        let events = events::Events::install(&conn)?;
+        Ok(BoardgameDb { conn, savepoint_depth: Cell::new(0), events })
     }
 
     // Create
     pub fn create_boardgame(&self, boardgame: &Boardgame) -> Result<i64, Error> {
-        self.conn.execute(
-            "INSERT INTO boardgames (name, min_players, max_players, play_time_minutes, description)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
+        Ok(self.create_boardgames(std::slice::from_ref(boardgame))?[0])
+    }
+
+    /// Creates all of the games or, if any of them is invalid, none of them. Much faster than
+    /// creating them one at a time since it is one transaction reusing the same statements.
+    pub fn create_boardgames(&self, boardgames: &[Boardgame]) -> Result<Vec<i64>, Error> {
+        for boardgame in boardgames {
+            boardgame.validate()?;
+        }
+        let fields = self.get_custom_fields()?;
+
+        let tx = self.transaction()?;
+        let mut insert = tx.conn.prepare_cached(
+            "INSERT INTO boardgames (name, min_players, max_players, play_time_minutes, description,
+                                     weight, min_age, year_published)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let mut ids = Vec::with_capacity(boardgames.len());
+        for boardgame in boardgames {
+            let id = insert.insert(params![
                 boardgame.name,
                 boardgame.min_players,
                 boardgame.max_players,
                 boardgame.play_time_minutes,
                 boardgame.description,
-            ],
-        )?;
+                boardgame.weight,
+                boardgame.min_age,
+                boardgame.year_published,
+            ])?;
+            tx.write_mechanics(id, &boardgame.mechanics)?;
+            tx.write_custom_values(id, &boardgame.custom_values, &fields)?;
+            ids.push(id);
+        }
+        drop(insert);
+        tx.commit()?;
 
-        Ok(self.conn.last_insert_rowid())
+        Ok(ids)
     }
 
     // Read
     pub fn get_all_boardgames(&self) -> Result<Vec<Boardgame>, Error> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, min_players, max_players, play_time_minutes, description 
-             FROM boardgames"
-        )?;
+        self.query_boardgames("", [])
+    }
 
-        let boardgames = stmt.query_map([], |row| {
-            Ok(Boardgame {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                min_players: row.get(2)?,
-                max_players: row.get(3)?,
-                play_time_minutes: row.get(4)?,
-                description: row.get(5)?,
-            })
-        })?;
+    pub fn get_boardgame_by_id(&self, id: i64) -> Result<Option<Boardgame>, Error> {
+        Ok(self.query_boardgames("WHERE id = ?", params![id])?.pop())
+    }
 
-        let res: Result<Vec<Boardgame>, Error> = boardgames.collect::<Result<Vec<Boardgame>, rusqlite::Error>>().map_err(|e| Error::DatabaseError(e));
-        Ok(res?)
+    /// Games in the trash, most recently deleted first.
+    pub fn get_trashed_boardgames(&self) -> Result<Vec<Boardgame>, Error> {
+        self.select_boardgames("boardgames", "WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC", [])
     }
 
-    pub fn get_boardgame_by_id(&self, id: i64) -> Result<Option<Boardgame>, Error> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, min_players, max_players, play_time_minutes, description 
-             FROM boardgames WHERE id = ?"
-        )?;
+    /// Every read of full boardgames goes through here, so `clause` is appended to a
+    /// select of `BOARDGAME_COLUMNS` and custom values are attached to the results.
+    /// Trashed games are left out.
+    fn query_boardgames<P: Params + Clone>(&self, clause: &str, params: P) -> Result<Vec<Boardgame>, Error> {
+        self.select_boardgames(LIVE_BOARDGAMES, clause, params)
+    }
+
+    fn select_boardgames<P: Params + Clone>(&self, source: &str, clause: &str, params: P) -> Result<Vec<Boardgame>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {BOARDGAME_COLUMNS} FROM {source} {clause}"
+        ))?;
 
-        let boardgame = stmt.query_row(params![id], |row| {
-            Ok(Boardgame {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                min_players: row.get(2)?,
-                max_players: row.get(3)?,
-                play_time_minutes: row.get(4)?,
-                description: row.get(5)?,
-            })
-        });
+        let mut boardgames = stmt
+            .query_map(params.clone(), Boardgame::from_row)?
+            .collect::<Result<Vec<Boardgame>, rusqlite::Error>>()?;
+        self.attach_custom_values(&mut boardgames, &format!("SELECT id FROM {source} {clause}"), params)?;
 
-        Ok(match boardgame {
-            Ok(game) => Ok(Some(game)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }?)
+        Ok(boardgames)
     }
 
     // Update
+    /// Returns how many games were updated, which is none for a game in the trash.
     pub fn update_boardgame(&self, boardgame: &Boardgame) -> Result<usize, Error> {
         let id = boardgame.id.ok_or(rusqlite::Error::InvalidParameterName("Boardgame must have an id to update".into()))?;
+        boardgame.validate()?;
 
-        Ok(self.conn.execute(
-            "UPDATE boardgames 
-             SET name = ?1, min_players = ?2, max_players = ?3, play_time_minutes = ?4, description = ?5
-             WHERE id = ?6",
+        let tx = self.transaction()?;
+        let updated = tx.conn.execute(
+            "UPDATE boardgames
+             SET name = ?1, min_players = ?2, max_players = ?3, play_time_minutes = ?4, description = ?5,
+                 weight = ?6, min_age = ?7, year_published = ?8
+             WHERE id = ?9 AND deleted_at IS NULL",
             params![
                 boardgame.name,
                 boardgame.min_players,
                 boardgame.max_players,
                 boardgame.play_time_minutes,
                 boardgame.description,
+                boardgame.weight,
+                boardgame.min_age,
+                boardgame.year_published,
                 id,
             ],
-        )?)
+        )?;
+        if updated > 0 {
+            tx.write_mechanics(id, &boardgame.mechanics)?;
+            tx.write_custom_values(id, &boardgame.custom_values, &tx.get_custom_fields()?)?;
+        }
+        tx.commit()?;
+
+        Ok(updated)
+    }
+
+    /// Only touches mechanics that were added or removed, so history shows what changed.
+    fn write_mechanics(&self, boardgame_id: i64, mechanics: &[Mechanic]) -> Result<(), Error> {
+        let mut current = self.conn.prepare_cached("SELECT mechanic FROM boardgame_mechanics WHERE boardgame_id = ?1")?;
+        let removed = current
+            .query_map(params![boardgame_id], |row| row.get::<_, String>(0))?
+            .filter(|mechanic| !matches!(mechanic, Ok(m) if mechanics.iter().any(|kept| kept.as_str() == m)))
+            .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+        let mut delete = self.conn.prepare_cached(
+            "DELETE FROM boardgame_mechanics WHERE boardgame_id = ?1 AND mechanic = ?2",
+        )?;
+        for mechanic in removed {
+            delete.execute(params![boardgame_id, mechanic])?;
+        }
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT OR IGNORE INTO boardgame_mechanics (boardgame_id, mechanic) VALUES (?1, ?2)"
+        )?;
+        for mechanic in mechanics {
+            stmt.execute(params![boardgame_id, mechanic.as_str()])?;
+        }
+        Ok(())
     }
 
     // Delete
+    /// Moves the game to the trash. It can be restored until it is purged.
     pub fn delete_boardgame(&self, id: i64) -> Result<usize, Error> {
         Ok(self.conn.execute(
-            "DELETE FROM boardgames WHERE id = ?",
+            "UPDATE boardgames SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![Utc::now().naive_utc(), id],
+        )?)
+    }
+
+    pub fn restore_boardgame(&self, id: i64) -> Result<usize, Error> {
+        Ok(self.conn.execute(
+            "UPDATE boardgames SET deleted_at = NULL WHERE id = ?1",
             params![id],
         )?)
     }
 
+    /// Permanently deletes a trashed game and everything attached to it.
+    pub fn purge_boardgame(&self, id: i64) -> Result<usize, Error> {
+        self.purge_boardgames("id = ?1", params![id])
+    }
+
+    /// Permanently deletes games that have been in the trash for more than `retention_days`.
+    pub fn purge_expired_trash(&self, retention_days: u64) -> Result<usize, Error> {
+        let cutoff = Utc::now().naive_utc() - Days::new(retention_days);
+        self.purge_boardgames("deleted_at <= ?1", params![cutoff])
+    }
+
+    fn purge_boardgames<P: Params>(&self, condition: &str, params: P) -> Result<usize, Error> {
+        let tx = self.transaction()?;
+        let deleted = tx.conn.execute(
+            &format!("DELETE FROM boardgames WHERE deleted_at IS NOT NULL AND {condition}"),
+            params,
+        )?;
+        // Images cascade with the game, but their data is shared by hash
+        tx.delete_orphaned_image_blobs()?;
+        tx.commit()?;
+
+        Ok(deleted)
+    }
 }
 
 #[cfg(test)]
@@ -150,6 +330,9 @@ mod tests {
             max_players: 4,
             play_time_minutes: 60,
             description: "Resource management and trading game".to_string(),
+            weight: Some(2.3),
+            mechanics: vec![Mechanic::Trading, Mechanic::DiceRolling],
+            ..Default::default()
         };
 
         let id = db.create_boardgame(&game)?;
@@ -158,6 +341,7 @@ mod tests {
         // Test Read
         let retrieved = db.get_boardgame_by_id(id)?.unwrap();
         assert_eq!(retrieved.name, "Catan");
+        assert_eq!(retrieved.mechanics, [Mechanic::DiceRolling, Mechanic::Trading]);
 
         // Test Update
         let mut updated_game = retrieved;
@@ -167,10 +351,29 @@ mod tests {
         let retrieved_updated = db.get_boardgame_by_id(id)?.unwrap();
         assert_eq!(retrieved_updated.name, "Settlers of Catan");
 
+        // Out of range weight is rejected
+        updated_game = retrieved_updated;
+        updated_game.weight = Some(5.5);
+        assert!(matches!(db.update_boardgame(&updated_game), Err(Error::InvalidInput(_))));
+
         // Test Delete
         db.delete_boardgame(id)?;
         assert!(db.get_boardgame_by_id(id)?.is_none());
+        assert!(db.get_all_boardgames()?.is_empty());
+        assert!(db.get_trashed_boardgames()?[0].deleted_at.is_some());
+        let renamed = Boardgame { name: "Catan".to_string(), weight: None, ..updated_game.clone() };
+        assert_eq!(db.update_boardgame(&renamed)?, 0, "trashed games can't be edited");
+        assert_eq!(db.get_trashed_boardgames()?[0].name, "Settlers of Catan");
+
+        db.restore_boardgame(id)?;
+        assert!(db.get_boardgame_by_id(id)?.is_some());
+        assert_eq!(db.purge_boardgame(id)?, 0, "only trashed games can be purged");
+
+        db.delete_boardgame(id)?;
+        assert_eq!(db.purge_expired_trash(TRASH_RETENTION_DAYS)?, 0);
+        assert_eq!(db.purge_expired_trash(0)?, 1);
+        assert!(db.get_trashed_boardgames()?.is_empty());
 
         Ok(())
     }
-} 
\ No newline at end of file
+}
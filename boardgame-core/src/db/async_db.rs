@@ -0,0 +1,258 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
+};
+
+use chrono::NaiveDateTime;
+
+use super::{
+    Boardgame, BoardgameDb, BoardgameFilter, BoardgamePool, BoardgameSort, Company, Component, Credit, CreditRole,
+    CustomField, CustomFieldType, CustomValue, DbEvent, GameCopy, HistoryEntry, HouseRule, ImageInfo, ImageKind, Person,
+    PlayerCountPoll, PlayerCountVote,
+};
+use crate::errors::Error;
+
+/// The `BoardgameDb` API for async code. Each call runs on tokio's blocking thread pool against
+/// a `BoardgamePool`, so it never blocks the executor. Methods take owned arguments where
+/// `BoardgameDb` borrows, since the work outlives the caller's stack frame.
+#[derive(Debug, Clone)]
+pub struct AsyncBoardgameDb {
+    pool: BoardgamePool,
+}
+
+impl From<BoardgamePool> for AsyncBoardgameDb {
+    fn from(pool: BoardgamePool) -> Self {
+        AsyncBoardgameDb { pool }
+    }
+}
+
+/// Generates an async method for each `BoardgameDb` method, run through `read` or `write`.
+macro_rules! async_methods {
+    ($($via:ident fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty => |$db:ident| $call:expr;)*) => {
+        $(
+            pub async fn $name(&self, $($arg: $ty),*) -> Result<$ret, Error> {
+                self.$via(move |$db| $call).await
+            }
+        )*
+    };
+}
+
+impl AsyncBoardgameDb {
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        run(move || BoardgamePool::open(path)).await.map(Self::from)
+    }
+
+    pub fn pool(&self) -> &BoardgamePool {
+        &self.pool
+    }
+
+    /// Runs `f` in a transaction on the pool's writer.
+    pub async fn write<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        run(move || pool.write(f)).await
+    }
+
+    /// Runs `f` in a transaction on the pool's writer, committed if it returns `Ok` and rolled
+    /// back otherwise, as `BoardgameDb::in_transaction` does.
+    pub async fn in_transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        run(move || pool.write(f)).await
+    }
+
+    /// Runs `f` on one of the pool's read-only connections.
+    pub async fn read<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        run(move || pool.read(f)).await
+    }
+
+    /// Events for every commit made through the pool. The receiver is a std channel, so poll it
+    /// with `try_recv` rather than blocking the executor in `recv`.
+    pub async fn subscribe(&self) -> Result<Receiver<DbEvent>, Error> {
+        // Taking the writer can wait on a write in progress
+        let pool = self.pool.clone();
+        run(move || Ok(pool.subscribe())).await
+    }
+
+    /// Writes CSV as `BoardgameDb::export_csv` does, to a writer such as a `File`. The writer is
+    /// handed back with the count, so a buffer can be read afterwards.
+    pub async fn export_csv<W: Write + Send + 'static>(&self, mut writer: W) -> Result<(usize, W), Error> {
+        self.read(move |db| Ok((db.export_csv(&mut writer)?, writer))).await
+    }
+
+    /// Creates boardgames from CSV as `BoardgameDb::import_csv` does, read from a file or a
+    /// buffer such as `io::Cursor<Vec<u8>>`.
+    pub async fn import_csv<R: Read + Send + 'static>(&self, reader: R) -> Result<Vec<i64>, Error> {
+        self.write(move |db| db.import_csv(reader)).await
+    }
+
+    async_methods! {
+        // Boardgames
+        write fn create_boardgame(boardgame: Boardgame) -> i64 => |db| db.create_boardgame(&boardgame);
+        write fn create_boardgames(boardgames: Vec<Boardgame>) -> Vec<i64> => |db| db.create_boardgames(&boardgames);
+        read fn get_all_boardgames() -> Vec<Boardgame> => |db| db.get_all_boardgames();
+        read fn get_boardgame_by_id(id: i64) -> Option<Boardgame> => |db| db.get_boardgame_by_id(id);
+        read fn get_trashed_boardgames() -> Vec<Boardgame> => |db| db.get_trashed_boardgames();
+        read fn find_boardgames(filter: BoardgameFilter) -> Vec<Boardgame> => |db| db.find_boardgames(&filter);
+        read fn find_boardgames_sorted(filter: BoardgameFilter, sort: BoardgameSort) -> Vec<Boardgame> => |db| db.find_boardgames_sorted(&filter, sort);
+        write fn update_boardgame(boardgame: Boardgame) -> usize => |db| db.update_boardgame(&boardgame);
+        write fn delete_boardgame(id: i64) -> usize => |db| db.delete_boardgame(id);
+        write fn restore_boardgame(id: i64) -> usize => |db| db.restore_boardgame(id);
+        write fn purge_boardgame(id: i64) -> usize => |db| db.purge_boardgame(id);
+        write fn purge_expired_trash(retention_days: u64) -> usize => |db| db.purge_expired_trash(retention_days);
+
+        // Copies
+        write fn create_copy(copy: GameCopy) -> i64 => |db| db.create_copy(&copy);
+        write fn update_copy(copy: GameCopy) -> usize => |db| db.update_copy(&copy);
+        write fn delete_copy(id: i64) -> usize => |db| db.delete_copy(id);
+        read fn get_copy(id: i64) -> Option<GameCopy> => |db| db.get_copy(id);
+        read fn get_copies(boardgame_id: i64) -> Vec<GameCopy> => |db| db.get_copies(boardgame_id);
+        write fn add_component(component: Component) -> i64 => |db| db.add_component(&component);
+        write fn update_component(component: Component) -> usize => |db| db.update_component(&component);
+        write fn delete_component(id: i64) -> usize => |db| db.delete_component(id);
+        read fn get_component(id: i64) -> Option<Component> => |db| db.get_component(id);
+        read fn get_components(copy_id: i64) -> Vec<Component> => |db| db.get_components(copy_id);
+        write fn complete_audit(copy_id: i64) -> Vec<Component> => |db| db.complete_audit(copy_id);
+
+        // Credits
+        write fn add_credit(boardgame_id: i64, role: CreditRole, name: String) -> i64
+            => |db| db.add_credit(boardgame_id, role, &name);
+        write fn set_credits(boardgame_id: i64, role: CreditRole, names: Vec<String>) -> ()
+            => |db| db.set_credits(boardgame_id, role, &names);
+        write fn remove_credit(boardgame_id: i64, role: CreditRole, entity_id: i64) -> usize
+            => |db| db.remove_credit(boardgame_id, role, entity_id);
+        read fn get_credits(boardgame_id: i64) -> Vec<Credit> => |db| db.get_credits(boardgame_id);
+        read fn get_people() -> Vec<Person> => |db| db.get_people();
+        read fn get_companies() -> Vec<Company> => |db| db.get_companies();
+        read fn get_boardgames_by_person(person_id: i64) -> Vec<Boardgame> => |db| db.get_boardgames_by_person(person_id);
+        read fn get_boardgames_by_company(company_id: i64) -> Vec<Boardgame>
+            => |db| db.get_boardgames_by_company(company_id);
+
+        // Custom fields
+        write fn create_custom_field(name: String, field_type: CustomFieldType) -> i64
+            => |db| db.create_custom_field(&name, &field_type);
+        write fn delete_custom_field(id: i64) -> usize => |db| db.delete_custom_field(id);
+        write fn restore_custom_field(field: CustomField, values: Vec<(i64, CustomValue)>) -> ()
+            => |db| db.restore_custom_field(&field, &values);
+        read fn get_custom_fields() -> Vec<CustomField> => |db| db.get_custom_fields();
+        read fn get_custom_field_values(field: CustomField) -> Vec<(i64, CustomValue)>
+            => |db| db.get_custom_field_values(&field);
+
+        // Events
+        write fn check_external_changes() -> bool => |db| db.check_external_changes();
+
+        // History
+        write fn set_actor(actor: String) -> () => |db| db.set_actor(&actor);
+        read fn history_for(boardgame_id: i64) -> Vec<HistoryEntry> => |db| db.history_for(boardgame_id);
+        read fn get_boardgame_as_of(id: i64, at: NaiveDateTime) -> Option<Boardgame> => |db| db.get_boardgame_as_of(id, at);
+
+        // Images
+        write fn add_image(boardgame_id: i64, kind: ImageKind, bytes: Vec<u8>) -> i64
+            => |db| db.add_image(boardgame_id, kind, &bytes);
+        write fn add_image_from_path(boardgame_id: i64, kind: ImageKind, path: PathBuf) -> i64
+            => |db| db.add_image_from_path(boardgame_id, kind, path);
+        write fn set_cover(image_id: i64) -> () => |db| db.set_cover(image_id);
+        read fn get_images(boardgame_id: i64) -> Vec<ImageInfo> => |db| db.get_images(boardgame_id);
+        read fn get_image_data(image_id: i64) -> Option<Vec<u8>> => |db| db.get_image_data(image_id);
+        read fn get_thumbnail(image_id: i64) -> Option<Vec<u8>> => |db| db.get_thumbnail(image_id);
+        write fn delete_image(image_id: i64) -> usize => |db| db.delete_image(image_id);
+
+        // Player count polls
+        write fn vote_player_count(boardgame_id: i64, player_count: i32, voter: String, vote: PlayerCountVote) -> ()
+            => |db| db.vote_player_count(boardgame_id, player_count, &voter, vote);
+        read fn get_player_count_vote(boardgame_id: i64, player_count: i32, voter: String) -> Option<PlayerCountVote>
+            => |db| db.get_player_count_vote(boardgame_id, player_count, &voter);
+        write fn remove_player_count_vote(boardgame_id: i64, player_count: i32, voter: String) -> usize
+            => |db| db.remove_player_count_vote(boardgame_id, player_count, &voter);
+        read fn get_player_count_poll(boardgame_id: i64) -> PlayerCountPoll => |db| db.get_player_count_poll(boardgame_id);
+
+        // House rules
+        write fn create_house_rule(rule: HouseRule) -> i64 => |db| db.create_house_rule(&rule);
+        write fn update_house_rule(rule: HouseRule) -> usize => |db| db.update_house_rule(&rule);
+        write fn set_house_rule_active(id: i64, active: bool) -> usize => |db| db.set_house_rule_active(id, active);
+        write fn delete_house_rule(id: i64) -> usize => |db| db.delete_house_rule(id);
+        read fn get_house_rule(id: i64) -> Option<HouseRule> => |db| db.get_house_rule(id);
+        read fn get_house_rules(boardgame_id: i64) -> Vec<HouseRule> => |db| db.get_house_rules(boardgame_id);
+        read fn search_house_rules(query: String) -> Vec<HouseRule> => |db| db.search_house_rules(&query);
+    }
+}
+
+/// Runs `f` on the blocking thread pool. A panic in `f` is passed on to the caller.
+async fn run<T, E, F>(f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_async_api() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = AsyncBoardgameDb::open(dir.path().join("test.db")).await?;
+
+        let games = ["Azul", "Brass"].map(|name| Boardgame { name: name.to_string(), ..Default::default() });
+        let ids = db.create_boardgames(games.to_vec()).await?;
+        db.add_credit(ids[0], CreditRole::Designer, "Michael Kiesling".to_string()).await?;
+
+        let events = db.subscribe().await?;
+        db.create_boardgame(Boardgame { name: "Root".to_string(), ..Default::default() }).await?;
+        assert!(events.try_recv().unwrap().touches("boardgames"));
+        assert!(!db.check_external_changes().await?);
+
+        // Calls from concurrent tasks share the pool
+        let (all, azul) = tokio::join!(db.get_all_boardgames(), db.get_boardgame_by_id(ids[0]));
+        assert_eq!(all?.len(), 3);
+        assert_eq!(azul?.unwrap().name, "Azul");
+        assert_eq!(db.get_credits(ids[0]).await?[0].name, "Michael Kiesling");
+
+        let too_heavy = Boardgame { weight: Some(9.0), ..games[0].clone() };
+        assert!(db.create_boardgame(too_heavy).await.is_err());
+        db.delete_boardgame(ids[1]).await?;
+        let names: Vec<String> = db
+            .read(|db| db.get_trashed_boardgames())
+            .await?
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names, ["Brass"]);
+
+        let (count, csv) = db.export_csv(Vec::new()).await?;
+        assert_eq!(count, 2);
+        let copies = db.import_csv(std::io::Cursor::new(csv)).await?;
+        assert_eq!(db.get_all_boardgames().await?.len(), 4);
+        let result: Result<(), Error> = db
+            .in_transaction(move |db| {
+                db.delete_boardgame(copies[0])?;
+                Err(Error::InvalidInput("changed my mind".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(db.get_all_boardgames().await?.len(), 4, "rolled back");
+
+        Ok(())
+    }
+}
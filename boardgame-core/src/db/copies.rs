@@ -0,0 +1,249 @@
+use chrono::{NaiveDateTime, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::BoardgameDb;
+use crate::errors::Error;
+
+/// A physical copy of a boardgame on our shelf.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameCopy {
+    pub id: Option<i64>,
+    pub boardgame_id: i64,
+    pub edition: String,
+    pub language: String,
+    pub publisher_year: Option<i32>,
+    /// When the components were last counted, if ever.
+    pub last_audited_at: Option<NaiveDateTime>,
+}
+
+impl GameCopy {
+    fn from_row(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(GameCopy {
+            id: Some(row.get(0)?),
+            boardgame_id: row.get(1)?,
+            edition: row.get(2)?,
+            language: row.get(3)?,
+            publisher_year: row.get(4)?,
+            last_audited_at: row.get(5)?,
+        })
+    }
+
+    /// "2nd edition (German, 2017)", leaving out whatever isn't known.
+    pub fn label(&self) -> String {
+        let edition = if self.edition.is_empty() { "Unknown edition" } else { &self.edition };
+        let details: Vec<String> = [Some(self.language.clone()).filter(|l| !l.is_empty()), self.publisher_year.map(|y| y.to_string())]
+            .into_iter()
+            .flatten()
+            .collect();
+        if details.is_empty() {
+            edition.to_string()
+        } else {
+            format!("{} ({})", edition, details.join(", "))
+        }
+    }
+}
+
+/// One line of a copy's component checklist, e.g. 30 "Wood tokens".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Component {
+    pub id: Option<i64>,
+    pub copy_id: i64,
+    pub name: String,
+    pub expected: i32,
+    pub present: i32,
+    /// What is missing or damaged and anything done about it.
+    pub notes: String,
+}
+
+impl Component {
+    fn from_row(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(Component {
+            id: Some(row.get(0)?),
+            copy_id: row.get(1)?,
+            name: row.get(2)?,
+            expected: row.get(3)?,
+            present: row.get(4)?,
+            notes: row.get(5)?,
+        })
+    }
+
+    pub fn missing(&self) -> i32 {
+        (self.expected - self.present).max(0)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing() == 0
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::InvalidInput("component name cannot be empty".into()));
+        }
+        if self.expected < 0 || self.present < 0 {
+            return Err(Error::InvalidInput(format!("'{}' can't have a negative count", self.name.trim())));
+        }
+        Ok(())
+    }
+}
+
+const COPY_COLUMNS: &str = "id, boardgame_id, edition, language, publisher_year, last_audited_at";
+const COMPONENT_COLUMNS: &str = "id, copy_id, name, expected, present, notes";
+
+impl BoardgameDb {
+    /// Creates the copy with `copy.id` if it is set, which is how a deleted copy is put back.
+    pub fn create_copy(&self, copy: &GameCopy) -> Result<i64, Error> {
+        self.conn.execute(
+            "INSERT INTO copies (id, boardgame_id, edition, language, publisher_year, last_audited_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                copy.id,
+                copy.boardgame_id,
+                copy.edition.trim(),
+                copy.language.trim(),
+                copy.publisher_year,
+                copy.last_audited_at,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_copy(&self, copy: &GameCopy) -> Result<usize, Error> {
+        let id = copy.id.ok_or_else(|| Error::InvalidInput("copy must have an id to update".into()))?;
+        Ok(self.conn.execute(
+            "UPDATE copies SET edition = ?1, language = ?2, publisher_year = ?3, last_audited_at = ?4 WHERE id = ?5",
+            params![copy.edition.trim(), copy.language.trim(), copy.publisher_year, copy.last_audited_at, id],
+        )?)
+    }
+
+    /// Deletes the copy and its component checklist.
+    pub fn delete_copy(&self, id: i64) -> Result<usize, Error> {
+        Ok(self.conn.execute("DELETE FROM copies WHERE id = ?1", params![id])?)
+    }
+
+    pub fn get_copy(&self, id: i64) -> Result<Option<GameCopy>, Error> {
+        Ok(self
+            .conn
+            .query_row(&format!("SELECT {COPY_COLUMNS} FROM copies WHERE id = ?1"), params![id], GameCopy::from_row)
+            .optional()?)
+    }
+
+    pub fn get_copies(&self, boardgame_id: i64) -> Result<Vec<GameCopy>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {COPY_COLUMNS} FROM copies WHERE boardgame_id = ?1 ORDER BY id"
+        ))?;
+        let copies = stmt.query_map(params![boardgame_id], GameCopy::from_row)?;
+        Ok(copies.collect::<Result<Vec<GameCopy>, rusqlite::Error>>()?)
+    }
+
+    /// Adds the component with `component.id` if it is set, like `create_copy`.
+    pub fn add_component(&self, component: &Component) -> Result<i64, Error> {
+        component.validate()?;
+        self.conn.execute(
+            "INSERT INTO components (id, copy_id, name, expected, present, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                component.id,
+                component.copy_id,
+                component.name.trim(),
+                component.expected,
+                component.present,
+                component.notes,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_component(&self, component: &Component) -> Result<usize, Error> {
+        let id = component.id.ok_or_else(|| Error::InvalidInput("component must have an id to update".into()))?;
+        component.validate()?;
+        Ok(self.conn.execute(
+            "UPDATE components SET name = ?1, expected = ?2, present = ?3, notes = ?4 WHERE id = ?5",
+            params![component.name.trim(), component.expected, component.present, component.notes, id],
+        )?)
+    }
+
+    pub fn delete_component(&self, id: i64) -> Result<usize, Error> {
+        Ok(self.conn.execute("DELETE FROM components WHERE id = ?1", params![id])?)
+    }
+
+    pub fn get_component(&self, id: i64) -> Result<Option<Component>, Error> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {COMPONENT_COLUMNS} FROM components WHERE id = ?1"),
+                params![id],
+                Component::from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn get_components(&self, copy_id: i64) -> Result<Vec<Component>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {COMPONENT_COLUMNS} FROM components WHERE copy_id = ?1 ORDER BY id"
+        ))?;
+        let components = stmt.query_map(params![copy_id], Component::from_row)?;
+        Ok(components.collect::<Result<Vec<Component>, rusqlite::Error>>()?)
+    }
+
+    /// Marks a box audit of the copy as finished now, returning the components still missing pieces.
+    pub fn complete_audit(&self, copy_id: i64) -> Result<Vec<Component>, Error> {
+        self.conn.execute(
+            "UPDATE copies SET last_audited_at = ?1 WHERE id = ?2",
+            params![Utc::now().naive_utc(), copy_id],
+        )?;
+        Ok(self.get_components(copy_id)?.into_iter().filter(|c| !c.is_complete()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Boardgame;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_copies_and_box_audit() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let id = db.create_boardgame(&Boardgame { name: "Agricola".to_string(), ..Default::default() })?;
+
+        let copy_id = db.create_copy(&GameCopy {
+            boardgame_id: id,
+            edition: "Revised".to_string(),
+            language: "German".to_string(),
+            publisher_year: Some(2016),
+            ..Default::default()
+        })?;
+        let copy = db.get_copy(copy_id)?.unwrap();
+        assert_eq!(copy.label(), "Revised (German, 2016)");
+        assert_eq!(copy.last_audited_at, None);
+
+        let wood = db.add_component(&Component { copy_id, name: "Wood".to_string(), expected: 30, present: 30, ..Default::default() })?;
+        db.add_component(&Component { copy_id, name: "Rules".to_string(), expected: 1, present: 1, ..Default::default() })?;
+        assert!(db.add_component(&Component { copy_id, name: " ".to_string(), ..Default::default() }).is_err());
+
+        let mut components = db.get_components(copy_id)?;
+        components[0].present = 27;
+        components[0].notes = "Lost 3 after game night".to_string();
+        db.update_component(&components[0])?;
+
+        let missing = db.complete_audit(copy_id)?;
+        assert_eq!(missing.len(), 1);
+        assert_eq!((missing[0].id, missing[0].missing()), (Some(wood), 3));
+        assert!(db.get_copy(copy_id)?.unwrap().last_audited_at.is_some());
+
+        // Deleted copies can be put back as they were
+        let copy = db.get_copy(copy_id)?.unwrap();
+        let component = db.get_component(wood)?.unwrap();
+        db.delete_copy(copy_id)?;
+        assert_eq!(db.create_copy(&copy)?, copy_id);
+        assert_eq!(db.add_component(&component)?, wood);
+        assert_eq!(db.get_copy(copy_id)?, Some(copy));
+
+        db.delete_boardgame(id)?;
+        db.purge_boardgame(id)?;
+        assert!(db.get_copies(id)?.is_empty());
+        assert!(db.get_components(copy_id)?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use std::{fmt, str::FromStr};
+
+use rusqlite::{
+    params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef},
+};
+
+use super::{Boardgame, BoardgameDb};
+use crate::errors::Error;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CreditRole {
+    Designer,
+    Artist,
+    Publisher,
+}
+
+impl CreditRole {
+    pub const ALL: [CreditRole; 3] = [CreditRole::Designer, CreditRole::Artist, CreditRole::Publisher];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditRole::Designer => "designer",
+            CreditRole::Artist => "artist",
+            CreditRole::Publisher => "publisher",
+        }
+    }
+
+    /// Publishers are companies, everyone else is a person.
+    pub fn is_company(&self) -> bool {
+        matches!(self, CreditRole::Publisher)
+    }
+}
+
+impl fmt::Display for CreditRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CreditRole {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CreditRole::ALL
+            .into_iter()
+            .find(|role| role.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::InvalidInput(format!("unknown credit role '{}'", s)))
+    }
+}
+
+impl ToSql for CreditRole {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for CreditRole {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Person {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Company {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A link between a boardgame and the person or company credited on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credit {
+    pub role: CreditRole,
+    /// Id in `people` or `companies`, depending on `role`.
+    pub entity_id: i64,
+    pub name: String,
+}
+
+/// Trims and collapses internal whitespace so "Uwe  Rosenberg " and "Uwe Rosenberg" are the same person.
+fn normalize_name(name: &str) -> Result<String, Error> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(Error::InvalidInput("credit name cannot be empty".into()));
+    }
+    Ok(name)
+}
+
+impl BoardgameDb {
+    /// Credits `name` on the boardgame, creating the person or company if it doesn't exist yet.
+    /// Returns the id of the person or company.
+    pub fn add_credit(&self, boardgame_id: i64, role: CreditRole, name: &str) -> Result<i64, Error> {
+        let name = normalize_name(name)?;
+        let (entity_table, credit_table, entity_column) = credit_tables(role);
+
+        // Names are UNIQUE COLLATE NOCASE, so this dedupes case-insensitively
+        self.conn.execute(
+            &format!("INSERT OR IGNORE INTO {entity_table} (name) VALUES (?1)"),
+            params![name],
+        )?;
+        let entity_id: i64 = self.conn.query_row(
+            &format!("SELECT id FROM {entity_table} WHERE name = ?1"),
+            params![name],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            &format!("INSERT OR IGNORE INTO {credit_table} (boardgame_id, {entity_column}, role) VALUES (?1, ?2, ?3)"),
+            params![boardgame_id, entity_id, role],
+        )?;
+
+        Ok(entity_id)
+    }
+
+    /// Replaces every credit of `role` on the boardgame with `names`, or if any name is invalid,
+    /// leaves them as they were.
+    pub fn set_credits(&self, boardgame_id: i64, role: CreditRole, names: &[String]) -> Result<(), Error> {
+        let (_, credit_table, _) = credit_tables(role);
+        self.in_transaction(|db| {
+            db.conn.execute(
+                &format!("DELETE FROM {credit_table} WHERE boardgame_id = ?1 AND role = ?2"),
+                params![boardgame_id, role],
+            )?;
+            for name in names {
+                db.add_credit(boardgame_id, role, name)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn remove_credit(&self, boardgame_id: i64, role: CreditRole, entity_id: i64) -> Result<usize, Error> {
+        let (_, credit_table, entity_column) = credit_tables(role);
+        Ok(self.conn.execute(
+            &format!("DELETE FROM {credit_table} WHERE boardgame_id = ?1 AND {entity_column} = ?2 AND role = ?3"),
+            params![boardgame_id, entity_id, role],
+        )?)
+    }
+
+    /// All credits on a boardgame, ordered by role and then name.
+    pub fn get_credits(&self, boardgame_id: i64) -> Result<Vec<Credit>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.role, p.id, p.name FROM person_credits c JOIN people p ON p.id = c.person_id
+             WHERE c.boardgame_id = ?1
+             UNION ALL
+             SELECT c.role, co.id, co.name FROM company_credits c JOIN companies co ON co.id = c.company_id
+             WHERE c.boardgame_id = ?1",
+        )?;
+
+        let mut credits = stmt
+            .query_map(params![boardgame_id], |row| {
+                Ok(Credit {
+                    role: row.get(0)?,
+                    entity_id: row.get(1)?,
+                    name: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<Credit>, rusqlite::Error>>()?;
+        credits.sort_by(|a, b| {
+            let role_order = |role| CreditRole::ALL.iter().position(|r| *r == role);
+            role_order(a.role).cmp(&role_order(b.role)).then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(credits)
+    }
+
+    pub fn get_people(&self) -> Result<Vec<Person>, Error> {
+        let mut stmt = self.conn.prepare("SELECT id, name FROM people ORDER BY name")?;
+        let people = stmt.query_map([], |row| Ok(Person { id: row.get(0)?, name: row.get(1)? }))?;
+        Ok(people.collect::<Result<Vec<Person>, rusqlite::Error>>()?)
+    }
+
+    pub fn get_companies(&self) -> Result<Vec<Company>, Error> {
+        let mut stmt = self.conn.prepare("SELECT id, name FROM companies ORDER BY name")?;
+        let companies = stmt.query_map([], |row| Ok(Company { id: row.get(0)?, name: row.get(1)? }))?;
+        Ok(companies.collect::<Result<Vec<Company>, rusqlite::Error>>()?)
+    }
+
+    /// Every boardgame the person is credited on, in any role.
+    pub fn get_boardgames_by_person(&self, person_id: i64) -> Result<Vec<Boardgame>, Error> {
+        self.get_boardgames_by_entity("person_credits", "person_id", person_id)
+    }
+
+    /// Every boardgame the company is credited on, in any role.
+    pub fn get_boardgames_by_company(&self, company_id: i64) -> Result<Vec<Boardgame>, Error> {
+        self.get_boardgames_by_entity("company_credits", "company_id", company_id)
+    }
+
+    fn get_boardgames_by_entity(&self, credit_table: &str, entity_column: &str, entity_id: i64) -> Result<Vec<Boardgame>, Error> {
+        self.query_boardgames(
+            &format!(
+                "WHERE id IN (SELECT boardgame_id FROM {credit_table} WHERE {entity_column} = ?1)
+                 ORDER BY name"
+            ),
+            params![entity_id],
+        )
+    }
+}
+
+/// (entity table, credit table, entity id column) for a role.
+fn credit_tables(role: CreditRole) -> (&'static str, &'static str, &'static str) {
+    if role.is_company() {
+        ("companies", "company_credits", "company_id")
+    } else {
+        ("people", "person_credits", "person_id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn game(name: &str) -> Boardgame {
+        Boardgame {
+            id: None,
+            name: name.to_string(),
+            min_players: 1,
+            max_players: 4,
+            play_time_minutes: 90,
+            description: String::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_credits_are_deduplicated_and_queryable() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+
+        let agricola = db.create_boardgame(&game("Agricola"))?;
+        let caverna = db.create_boardgame(&game("Caverna"))?;
+
+        let uwe = db.add_credit(agricola, CreditRole::Designer, "Uwe Rosenberg")?;
+        assert_eq!(db.add_credit(caverna, CreditRole::Designer, " uwe  rosenberg")?, uwe);
+        db.add_credit(agricola, CreditRole::Publisher, "Lookout Games")?;
+        assert_eq!(db.get_people()?.len(), 1);
+
+        let credits = db.get_credits(agricola)?;
+        assert_eq!(credits.len(), 2);
+        assert_eq!(credits[0].role, CreditRole::Designer);
+        assert_eq!(credits[1].name, "Lookout Games");
+
+        let names: Vec<String> = db.get_boardgames_by_person(uwe)?.into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["Agricola", "Caverna"]);
+
+        let filter = crate::db::BoardgameFilter { credit: Some("lookout".into()), ..Default::default() };
+        assert_eq!(db.find_boardgames(&filter)?.len(), 1);
+
+        // A bad name anywhere keeps the credits as they were
+        let names = ["Klemens Franz".to_string(), " ".to_string()];
+        assert!(db.set_credits(agricola, CreditRole::Designer, &names).is_err());
+        assert_eq!(db.get_credits(agricola)?, credits);
+
+        db.delete_boardgame(caverna)?;
+        assert_eq!(db.get_boardgames_by_person(uwe)?.len(), 1);
+
+        Ok(())
+    }
+}
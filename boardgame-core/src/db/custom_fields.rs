@@ -0,0 +1,386 @@
+use std::{collections::{BTreeMap, HashMap}, fmt, str::FromStr};
+
+use chrono::NaiveDate;
+use rusqlite::{
+    params,
+    types::{ToSql, ToSqlOutput, Value},
+    Params,
+};
+
+use super::{Boardgame, BoardgameDb};
+use crate::{errors::Error, strings::{BG_CREDIT_FIELDS, BG_FIELDS, BG_METADATA_FIELDS, FILTER_FIELDS}};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The kind of value a custom field holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    Bool,
+    Date,
+    /// One of a fixed list of options.
+    Enum(Vec<String>),
+}
+
+impl CustomFieldType {
+    pub const KINDS: [&'static str; 5] = ["text", "number", "bool", "date", "enum"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CustomFieldType::Text => "text",
+            CustomFieldType::Number => "number",
+            CustomFieldType::Bool => "bool",
+            CustomFieldType::Date => "date",
+            CustomFieldType::Enum(_) => "enum",
+        }
+    }
+
+    /// Builds a type from its kind name, taking `options` only for enums.
+    pub fn from_kind(kind: &str, options: Vec<String>) -> Result<Self, Error> {
+        let field_type = match kind.trim().to_lowercase().as_str() {
+            "text" => CustomFieldType::Text,
+            "number" => CustomFieldType::Number,
+            "bool" => CustomFieldType::Bool,
+            "date" => CustomFieldType::Date,
+            "enum" => CustomFieldType::Enum(options.clone()),
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "unknown field type '{}', expected one of {}",
+                    kind.trim(),
+                    CustomFieldType::KINDS.join(", ")
+                )))
+            }
+        };
+        match &field_type {
+            CustomFieldType::Enum(options) if options.is_empty() => {
+                Err(Error::InvalidInput("enum fields need at least one option".into()))
+            }
+            CustomFieldType::Enum(_) => Ok(field_type),
+            _ if !options.is_empty() => Err(Error::InvalidInput(format!("{} fields don't take options", kind.trim()))),
+            _ => Ok(field_type),
+        }
+    }
+
+    fn options(&self) -> &[String] {
+        match self {
+            CustomFieldType::Enum(options) => options,
+            _ => &[],
+        }
+    }
+
+    /// Converts a stored SQLite value back into a `CustomValue` of this type.
+    pub(super) fn read_sql(&self, value: Value) -> Result<CustomValue, Error> {
+        let mismatch = |value: &Value| Error::InvalidInput(format!("stored value {:?} is not a {}", value, self.kind()));
+        Ok(match (self, value) {
+            (CustomFieldType::Text, Value::Text(text)) => CustomValue::Text(text),
+            (CustomFieldType::Number, Value::Real(number)) => CustomValue::Number(number),
+            (CustomFieldType::Number, Value::Integer(number)) => CustomValue::Number(number as f64),
+            (CustomFieldType::Bool, Value::Integer(flag)) => CustomValue::Bool(flag != 0),
+            (CustomFieldType::Date, Value::Text(date)) => CustomValue::Date(
+                NaiveDate::parse_from_str(&date, DATE_FORMAT).map_err(|_| mismatch(&Value::Text(date)))?,
+            ),
+            (CustomFieldType::Enum(_), Value::Text(option)) => CustomValue::Enum(option),
+            (_, value) => return Err(mismatch(&value)),
+        })
+    }
+}
+
+/// A field defined at runtime, such as "Sleeved?" or "Language edition".
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomField {
+    pub id: i64,
+    pub name: String,
+    pub field_type: CustomFieldType,
+}
+
+impl CustomField {
+    /// Parses user input into a value of this field's type.
+    pub fn parse_value(&self, input: &str) -> Result<CustomValue, Error> {
+        let input = input.trim();
+        let invalid = |expected: &str| Error::InvalidInput(format!("'{}' needs {}, got '{}'", self.name, expected, input));
+        Ok(match &self.field_type {
+            CustomFieldType::Text => CustomValue::Text(input.to_string()),
+            CustomFieldType::Number => CustomValue::Number(input.parse().map_err(|_| invalid("a number"))?),
+            CustomFieldType::Bool => CustomValue::Bool(match input.to_lowercase().as_str() {
+                "y" | "yes" | "true" | "1" => true,
+                "n" | "no" | "false" | "0" => false,
+                _ => return Err(invalid("yes or no")),
+            }),
+            CustomFieldType::Date => CustomValue::Date(
+                NaiveDate::parse_from_str(input, DATE_FORMAT).map_err(|_| invalid("a YYYY-MM-DD date"))?,
+            ),
+            CustomFieldType::Enum(options) => CustomValue::Enum(
+                options
+                    .iter()
+                    .find(|option| option.eq_ignore_ascii_case(input))
+                    .cloned()
+                    .ok_or_else(|| invalid(&format!("one of {}", options.join(", "))))?,
+            ),
+        })
+    }
+
+    /// Checks that `value` is the right type for this field.
+    pub fn check_value(&self, value: &CustomValue) -> Result<(), Error> {
+        let matches = match (&self.field_type, value) {
+            (CustomFieldType::Text, CustomValue::Text(_))
+            | (CustomFieldType::Number, CustomValue::Number(_))
+            | (CustomFieldType::Bool, CustomValue::Bool(_))
+            | (CustomFieldType::Date, CustomValue::Date(_)) => true,
+            (CustomFieldType::Enum(options), CustomValue::Enum(option)) => options.contains(option),
+            _ => false,
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::InvalidInput(format!("{} is not a valid value for '{}'", value, self.name)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    Enum(String),
+}
+
+impl fmt::Display for CustomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomValue::Text(text) | CustomValue::Enum(text) => f.write_str(text),
+            CustomValue::Number(number) => write!(f, "{}", number),
+            CustomValue::Bool(flag) => f.write_str(if *flag { "yes" } else { "no" }),
+            CustomValue::Date(date) => write!(f, "{}", date.format(DATE_FORMAT)),
+        }
+    }
+}
+
+impl ToSql for CustomValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            CustomValue::Text(text) | CustomValue::Enum(text) => text.as_str().into(),
+            CustomValue::Number(number) => (*number).into(),
+            CustomValue::Bool(flag) => (*flag).into(),
+            CustomValue::Date(date) => date.format(DATE_FORMAT).to_string().into(),
+        })
+    }
+}
+
+impl FromStr for CustomFieldType {
+    type Err = Error;
+
+    /// Parses "text", "bool", ... or "enum: English, German" with comma-separated options.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, options) = s.split_once(':').unwrap_or((s, ""));
+        let options = options
+            .split(',')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .map(str::to_string)
+            .collect();
+        CustomFieldType::from_kind(kind, options)
+    }
+}
+
+/// Custom field values on a boardgame, keyed by `CustomField::id`.
+pub type CustomValues = BTreeMap<i64, CustomValue>;
+
+impl BoardgameDb {
+    pub fn create_custom_field(&self, name: &str, field_type: &CustomFieldType) -> Result<i64, Error> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::InvalidInput("field name cannot be empty".into()));
+        }
+        // Custom fields are inputs on the boardgame and filter forms, keyed by name alongside these
+        let mut reserved = BG_FIELDS.iter().chain(&BG_METADATA_FIELDS).chain(&BG_CREDIT_FIELDS).chain(&FILTER_FIELDS);
+        if reserved.any(|field| field.eq_ignore_ascii_case(name)) {
+            return Err(Error::InvalidInput(format!("'{}' is a built in field", name)));
+        }
+
+        self.conn.execute(
+            "INSERT INTO custom_fields (name, kind, options) VALUES (?1, ?2, ?3)",
+            params![name, field_type.kind(), field_type.options().join("\n")],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Deletes the field along with every value stored for it.
+    pub fn delete_custom_field(&self, id: i64) -> Result<usize, Error> {
+        Ok(self.conn.execute("DELETE FROM custom_fields WHERE id = ?1", params![id])?)
+    }
+
+    /// Puts a deleted field back with its id and the values games had for it.
+    pub fn restore_custom_field(&self, field: &CustomField, values: &[(i64, CustomValue)]) -> Result<(), Error> {
+        let tx = self.transaction()?;
+        tx.conn.execute(
+            "INSERT INTO custom_fields (id, name, kind, options) VALUES (?1, ?2, ?3, ?4)",
+            params![field.id, field.name, field.field_type.kind(), field.field_type.options().join("\n")],
+        )?;
+        let mut stmt = tx.conn.prepare("INSERT INTO custom_field_values (boardgame_id, field_id, value) VALUES (?1, ?2, ?3)")?;
+        for (boardgame_id, value) in values {
+            field.check_value(value)?;
+            stmt.execute(params![boardgame_id, field.id, value])?;
+        }
+        drop(stmt);
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_custom_fields(&self) -> Result<Vec<CustomField>, Error> {
+        let mut stmt = self.conn.prepare("SELECT id, name, kind, options FROM custom_fields ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?))
+        })?;
+
+        let mut fields = Vec::new();
+        for row in rows {
+            let (id, name, kind, options) = row?;
+            let options = options.lines().map(str::to_string).collect();
+            fields.push(CustomField { id, name, field_type: CustomFieldType::from_kind(&kind, options)? });
+        }
+        Ok(fields)
+    }
+
+    /// Every game's value for the field, trashed games included, as (boardgame id, value).
+    pub fn get_custom_field_values(&self, field: &CustomField) -> Result<Vec<(i64, CustomValue)>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT boardgame_id, value FROM custom_field_values WHERE field_id = ?1 ORDER BY boardgame_id",
+        )?;
+        let rows = stmt.query_map(params![field.id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Value>(1)?)))?;
+        let mut values = Vec::new();
+        for row in rows {
+            let (boardgame_id, value) = row?;
+            values.push((boardgame_id, field.field_type.read_sql(value)?));
+        }
+        Ok(values)
+    }
+
+    /// Replaces every custom value on the boardgame, checking each against its field's type.
+    /// `fields` are the current custom fields, passed in so bulk writes only load them once.
+    pub(super) fn write_custom_values(&self, boardgame_id: i64, values: &CustomValues, fields: &[CustomField]) -> Result<(), Error> {
+        for (field_id, value) in values {
+            let field = fields
+                .iter()
+                .find(|field| field.id == *field_id)
+                .ok_or_else(|| Error::InvalidInput(format!("no custom field with id {}", field_id)))?;
+            field.check_value(value)?;
+        }
+
+        // Values are upserted rather than rewritten, so history shows what changed
+        let mut current = self.conn.prepare_cached("SELECT field_id FROM custom_field_values WHERE boardgame_id = ?1")?;
+        let removed = current
+            .query_map(params![boardgame_id], |row| row.get::<_, i64>(0))?
+            .filter(|field_id| !matches!(field_id, Ok(id) if values.contains_key(id)))
+            .collect::<Result<Vec<i64>, rusqlite::Error>>()?;
+        let mut delete = self.conn.prepare_cached(
+            "DELETE FROM custom_field_values WHERE boardgame_id = ?1 AND field_id = ?2",
+        )?;
+        for field_id in removed {
+            delete.execute(params![boardgame_id, field_id])?;
+        }
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO custom_field_values (boardgame_id, field_id, value) VALUES (?1, ?2, ?3)
+             ON CONFLICT (boardgame_id, field_id) DO UPDATE SET value = excluded.value",
+        )?;
+        for (field_id, value) in values {
+            stmt.execute(params![boardgame_id, field_id, value])?;
+        }
+        Ok(())
+    }
+
+    /// Loads custom values onto already fetched boardgames with a single query. `ids` selects
+    /// the ids of the same boardgames with `params`, so any number of them can be loaded.
+    pub(super) fn attach_custom_values<P: Params>(&self, boardgames: &mut [Boardgame], ids: &str, params: P) -> Result<(), Error> {
+        if boardgames.is_empty() {
+            return Ok(());
+        }
+        let fields: HashMap<i64, CustomField> = self.get_custom_fields()?.into_iter().map(|field| (field.id, field)).collect();
+        let mut by_id: HashMap<i64, &mut Boardgame> =
+            boardgames.iter_mut().filter_map(|boardgame| Some((boardgame.id?, boardgame))).collect();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT boardgame_id, field_id, value FROM custom_field_values WHERE boardgame_id IN ({ids})"
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Value>(2)?))
+        })?;
+
+        for row in rows {
+            let (boardgame_id, field_id, value) = row?;
+            let (Some(field), Some(boardgame)) = (fields.get(&field_id), by_id.get_mut(&boardgame_id)) else {
+                continue;
+            };
+            boardgame.custom_values.insert(field_id, field.field_type.read_sql(value)?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::BoardgameFilter;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_custom_fields_round_trip_and_filter() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+
+        let sleeved = db.create_custom_field("Sleeved?", &"bool".parse()?)?;
+        let language = db.create_custom_field("Language", &"enum: English, German".parse()?)?;
+        assert!(db.create_custom_field("Name", &CustomFieldType::Text).is_err());
+        assert!(db.create_custom_field("min weight", &CustomFieldType::Number).is_err(), "a filter field's name too");
+
+        let fields = db.get_custom_fields()?;
+        let mut game = Boardgame { name: "Brass".to_string(), ..Default::default() };
+        game.custom_values.insert(sleeved, fields[0].parse_value("yes")?);
+        game.custom_values.insert(language, fields[1].parse_value("german")?);
+        let id = db.create_boardgame(&game)?;
+
+        let stored = db.get_boardgame_by_id(id)?.unwrap();
+        assert_eq!(stored.custom_values[&sleeved], CustomValue::Bool(true));
+        assert_eq!(stored.custom_values[&language], CustomValue::Enum("German".into()));
+        assert!(fields[1].parse_value("French").is_err());
+
+        let filter = BoardgameFilter {
+            custom_values: vec![(sleeved, CustomValue::Bool(false))],
+            ..Default::default()
+        };
+        assert!(db.find_boardgames(&filter)?.is_empty());
+
+        // Mistyped values are rejected
+        game.custom_values.insert(sleeved, CustomValue::Text("yes".into()));
+        assert!(db.create_boardgame(&game).is_err());
+
+        // Deleted fields can be put back with their values
+        let values = db.get_custom_field_values(&fields[0])?;
+        db.delete_custom_field(sleeved)?;
+        db.restore_custom_field(&fields[0], &values)?;
+        assert_eq!(db.get_boardgame_by_id(id)?.unwrap().custom_values[&sleeved], CustomValue::Bool(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_load_for_more_games_than_sqlite_binds() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let sleeved = db.create_custom_field("Sleeved", &CustomFieldType::Bool)?;
+
+        // More than SQLite's default limit of 32766 parameters to a statement
+        let game = Boardgame {
+            name: "Azul".to_string(),
+            custom_values: CustomValues::from([(sleeved, CustomValue::Bool(true))]),
+            ..Default::default()
+        };
+        db.create_boardgames(&vec![game; 33_000])?;
+        let boardgames = db.get_all_boardgames()?;
+        assert_eq!(boardgames.len(), 33_000);
+        assert!(boardgames.iter().all(|boardgame| boardgame.custom_values[&sleeved] == CustomValue::Bool(true)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::Cell,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use rusqlite::{hooks::Action, Connection};
+
+use super::BoardgameDb;
+use crate::errors::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A row written through this connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub table: String,
+    pub row_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbEvent {
+    /// The rows a transaction on this connection changed, sent when it commits.
+    Committed(Vec<Change>),
+    /// Another connection, possibly in another process, committed to the same file. Which rows
+    /// changed isn't known, so anything read before should be read again.
+    External,
+}
+
+impl DbEvent {
+    /// Whether the event could have changed rows in `table`.
+    pub fn touches(&self, table: &str) -> bool {
+        match self {
+            DbEvent::Committed(changes) => changes.iter().any(|change| change.table == table),
+            DbEvent::External => true,
+        }
+    }
+}
+
+/// Collects row changes from SQLite's hooks and hands them to subscribers on commit.
+#[derive(Debug)]
+pub(super) struct Events {
+    shared: Arc<Mutex<Shared>>,
+    /// `PRAGMA data_version` as last seen, it changes when other connections commit.
+    data_version: Cell<i64>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    pending: Vec<Change>,
+    subscribers: Vec<Sender<DbEvent>>,
+}
+
+impl Events {
+    pub(super) fn install(conn: &Connection) -> Result<Self, Error> {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let hook_shared = Arc::clone(&shared);
+        conn.update_hook(Some(move |action, db: &str, table: &str, row_id| {
+            let kind = match action {
+                Action::SQLITE_INSERT => ChangeKind::Insert,
+                Action::SQLITE_UPDATE => ChangeKind::Update,
+                Action::SQLITE_DELETE => ChangeKind::Delete,
+                _ => return,
+            };
+            // The temp schema only holds per-connection bookkeeping such as the history actor
+            if db == "main" {
+                lock(&hook_shared).pending.push(Change { kind, table: table.to_string(), row_id });
+            }
+        }));
+        let hook_shared = Arc::clone(&shared);
+        conn.commit_hook(Some(move || {
+            let mut shared = lock(&hook_shared);
+            if !shared.pending.is_empty() {
+                let event = DbEvent::Committed(std::mem::take(&mut shared.pending));
+                shared.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+            }
+            false
+        }));
+        let hook_shared = Arc::clone(&shared);
+        conn.rollback_hook(Some(move || lock(&hook_shared).pending.clear()));
+
+        let data_version = conn.pragma_query_value(None, "data_version", |row| row.get(0))?;
+        Ok(Events { shared, data_version: Cell::new(data_version) })
+    }
+
+    /// How many changes are waiting for a commit, to pass to `discard_since` on rollback.
+    pub(super) fn mark(&self) -> usize {
+        lock(&self.shared).pending.len()
+    }
+
+    /// Forgets changes undone by rolling back to a savepoint, which doesn't call the rollback hook.
+    pub(super) fn discard_since(&self, mark: usize) {
+        lock(&self.shared).pending.truncate(mark);
+    }
+}
+
+/// The hooks never panic while holding the lock, but a subscriber's thread might.
+fn lock(shared: &Mutex<Shared>) -> std::sync::MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl BoardgameDb {
+    /// Receives an event for every transaction committed on this connection, and for changes
+    /// from elsewhere when `check_external_changes` notices them.
+    pub fn subscribe(&self) -> Receiver<DbEvent> {
+        let (sender, receiver) = mpsc::channel();
+        lock(&self.events.shared).subscribers.push(sender);
+        receiver
+    }
+
+    /// Sends `DbEvent::External` to subscribers if another connection has committed since the
+    /// last check. SQLite can't push these, so call it periodically.
+    pub fn check_external_changes(&self) -> Result<bool, Error> {
+        let data_version: i64 = self.conn.pragma_query_value(None, "data_version", |row| row.get(0))?;
+        if data_version == self.events.data_version.replace(data_version) {
+            return Ok(false);
+        }
+        lock(&self.events.shared).subscribers.retain(|subscriber| subscriber.send(DbEvent::External).is_ok());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Boardgame;
+    use tempfile::tempdir;
+
+    fn game(name: &str) -> Boardgame {
+        Boardgame { name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_events() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let events = db.subscribe();
+
+        let id = db.create_boardgame(&game("Azul"))?;
+        let event = events.try_recv().expect("create was committed");
+        assert!(event.touches("boardgames") && event.touches("history"));
+        let DbEvent::Committed(changes) = event else { panic!("expected a commit, got {:?}", event) };
+        assert!(changes.contains(&Change { kind: ChangeKind::Insert, table: "boardgames".into(), row_id: id }));
+
+        db.delete_boardgame(id)?;
+        let DbEvent::Committed(changes) = events.try_recv().expect("delete was committed") else { panic!() };
+        assert!(changes.contains(&Change { kind: ChangeKind::Update, table: "boardgames".into(), row_id: id }));
+
+        // Nothing is sent for work that was rolled back, even inside a transaction that commits
+        let tx = db.transaction()?;
+        let inner = tx.transaction()?;
+        inner.create_boardgame(&game("Brass"))?;
+        inner.rollback()?;
+        tx.commit()?;
+        assert!(events.try_recv().is_err());
+        assert!(db.create_boardgame(&Boardgame { weight: Some(9.0), ..game("Catan") }).is_err());
+        assert!(events.try_recv().is_err());
+
+        // Other connections' commits are only seen by checking
+        let other = BoardgameDb::new(dir.path().join("test.db"))?;
+        assert!(!db.check_external_changes()?);
+        other.restore_boardgame(id)?;
+        assert!(events.try_recv().is_err());
+        assert!(db.check_external_changes()?);
+        assert_eq!(events.try_recv(), Ok(DbEvent::External));
+        assert!(!db.check_external_changes()?);
+
+        Ok(())
+    }
+}
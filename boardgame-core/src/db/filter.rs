@@ -0,0 +1,229 @@
+use std::{fmt, str::FromStr};
+
+use rusqlite::{params_from_iter, types::Value};
+
+use super::{
+    polls::{BEST_HAVING, RECOMMENDED_HAVING},
+    Boardgame, BoardgameDb, CustomValue, Mechanic, PlayerCountMatch,
+};
+use crate::errors::Error;
+
+/// Criteria for `BoardgameDb::find_boardgames`. Unset fields don't restrict the results.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoardgameFilter {
+    /// Case-insensitive substring of the boardgame name.
+    pub name: Option<String>,
+    /// Case-insensitive substring of any credited designer, artist or publisher.
+    pub credit: Option<String>,
+    pub min_weight: Option<f64>,
+    pub max_weight: Option<f64>,
+    /// Only games suitable for a player of this age. Games without a minimum age always match.
+    pub age: Option<i32>,
+    /// Games must have every one of these mechanics.
+    pub mechanics: Vec<Mechanic>,
+    /// Only games that play well with this many players, judged by `player_count_match`.
+    pub players: Option<i32>,
+    pub player_count_match: PlayerCountMatch,
+    /// Games whose custom field (by `CustomField::id`) has exactly this value.
+    pub custom_values: Vec<(i64, CustomValue)>,
+}
+
+impl BoardgameFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == BoardgameFilter::default()
+    }
+
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        // Every condition refers to its value by number so a value can be used more than once
+        let mut push = |condition: &str, value: Value| {
+            values.push(value);
+            conditions.push(condition.replace("?#", &format!("?{}", values.len())));
+        };
+
+        if let Some(name) = &self.name {
+            push("name LIKE ?# ESCAPE '\\'", Value::Text(like_pattern(name)));
+        }
+        if let Some(credit) = &self.credit {
+            push(
+                "id IN (SELECT c.boardgame_id FROM person_credits c JOIN people p ON p.id = c.person_id
+                        WHERE p.name LIKE ?# ESCAPE '\\'
+                        UNION
+                        SELECT c.boardgame_id FROM company_credits c JOIN companies co ON co.id = c.company_id
+                        WHERE co.name LIKE ?# ESCAPE '\\')",
+                Value::Text(like_pattern(credit)),
+            );
+        }
+        if let Some(min_weight) = self.min_weight {
+            push("weight >= ?#", Value::Real(min_weight));
+        }
+        if let Some(max_weight) = self.max_weight {
+            push("weight <= ?#", Value::Real(max_weight));
+        }
+        if let Some(age) = self.age {
+            push("(min_age IS NULL OR min_age <= ?#)", Value::Integer(age.into()));
+        }
+        for mechanic in &self.mechanics {
+            push(
+                "id IN (SELECT boardgame_id FROM boardgame_mechanics WHERE mechanic = ?#)",
+                Value::Text(mechanic.as_str().to_string()),
+            );
+        }
+        if let Some(players) = self.players {
+            let condition = match self.player_count_match {
+                PlayerCountMatch::Supported => "(min_players <= ?# AND max_players >= ?#)".to_string(),
+                PlayerCountMatch::Recommended => voted_condition(RECOMMENDED_HAVING),
+                PlayerCountMatch::Best => voted_condition(BEST_HAVING),
+            };
+            push(&condition, Value::Integer(players.into()));
+        }
+        for (field_id, value) in &self.custom_values {
+            push(
+                &format!("id IN (SELECT boardgame_id FROM custom_field_values WHERE field_id = {field_id} AND value = ?#)"),
+                sql_value(value),
+            );
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+}
+
+/// The order `BoardgameDb::find_boardgames_sorted` returns boardgames in. Ties and games
+/// without the value sorted on go last, by name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoardgameSort {
+    #[default]
+    Name,
+    /// Lightest first.
+    Weight,
+    /// Shortest first.
+    PlayTime,
+    /// Most recently published first.
+    Year,
+    /// Most recently added first.
+    Added,
+}
+
+impl BoardgameSort {
+    pub const ALL: [BoardgameSort; 5] = [
+        BoardgameSort::Name,
+        BoardgameSort::Weight,
+        BoardgameSort::PlayTime,
+        BoardgameSort::Year,
+        BoardgameSort::Added,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardgameSort::Name => "name",
+            BoardgameSort::Weight => "weight",
+            BoardgameSort::PlayTime => "play-time",
+            BoardgameSort::Year => "year",
+            BoardgameSort::Added => "added",
+        }
+    }
+
+    fn order_by(&self) -> &'static str {
+        match self {
+            BoardgameSort::Name => "name",
+            BoardgameSort::Weight => "weight IS NULL, weight, name",
+            BoardgameSort::PlayTime => "play_time_minutes, name",
+            BoardgameSort::Year => "year_published IS NULL, year_published DESC, name",
+            BoardgameSort::Added => "id DESC",
+        }
+    }
+}
+
+impl fmt::Display for BoardgameSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BoardgameSort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardgameSort::ALL
+            .into_iter()
+            .find(|sort| sort.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::InvalidInput(format!("unknown sort order '{}'", s.trim())))
+    }
+}
+
+fn voted_condition(having: &str) -> String {
+    format!(
+        "id IN (SELECT boardgame_id FROM player_count_votes WHERE player_count = ?#
+                GROUP BY boardgame_id HAVING {having})"
+    )
+}
+
+fn sql_value(value: &CustomValue) -> Value {
+    match value {
+        CustomValue::Text(text) | CustomValue::Enum(text) => Value::Text(text.clone()),
+        CustomValue::Number(number) => Value::Real(*number),
+        CustomValue::Bool(flag) => Value::Integer((*flag).into()),
+        CustomValue::Date(_) => Value::Text(value.to_string()),
+    }
+}
+
+/// `%text%` with LIKE wildcards in `text` escaped, for use with `ESCAPE '\'`.
+pub(super) fn like_pattern(text: &str) -> String {
+    let escaped = text.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+impl BoardgameDb {
+    /// Boardgames matching every criterion set on `filter`, ordered by name.
+    pub fn find_boardgames(&self, filter: &BoardgameFilter) -> Result<Vec<Boardgame>, Error> {
+        self.find_boardgames_sorted(filter, BoardgameSort::Name)
+    }
+
+    /// Boardgames matching every criterion set on `filter`, in the order `sort` gives.
+    pub fn find_boardgames_sorted(&self, filter: &BoardgameFilter, sort: BoardgameSort) -> Result<Vec<Boardgame>, Error> {
+        let (where_clause, values) = filter.where_clause();
+        self.query_boardgames(&format!("{where_clause} ORDER BY {}", sort.order_by()), params_from_iter(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sort() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let game = |name: &str, weight, play_time_minutes, year_published| Boardgame {
+            name: name.to_string(),
+            weight,
+            play_time_minutes,
+            year_published,
+            ..Default::default()
+        };
+        db.create_boardgame(&game("Brass", Some(3.9), 120, Some(2018)))?;
+        db.create_boardgame(&game("Azul", Some(1.8), 45, Some(2017)))?;
+        db.create_boardgame(&game("Carcassonne", None, 45, None))?;
+
+        let names = |sort: BoardgameSort| -> Result<Vec<String>, Error> {
+            let boardgames = db.find_boardgames_sorted(&BoardgameFilter::default(), sort)?;
+            Ok(boardgames.into_iter().map(|boardgame| boardgame.name).collect())
+        };
+        assert_eq!(names(BoardgameSort::Name)?, ["Azul", "Brass", "Carcassonne"]);
+        assert_eq!(names(BoardgameSort::Weight)?, ["Azul", "Brass", "Carcassonne"]);
+        assert_eq!(names(BoardgameSort::PlayTime)?, ["Azul", "Carcassonne", "Brass"]);
+        assert_eq!(names(BoardgameSort::Year)?, ["Brass", "Azul", "Carcassonne"]);
+        assert_eq!(names(BoardgameSort::Added)?, ["Carcassonne", "Azul", "Brass"]);
+
+        assert_eq!("Play-Time".parse::<BoardgameSort>()?, BoardgameSort::PlayTime);
+        assert!(matches!("rating".parse::<BoardgameSort>(), Err(Error::InvalidInput(_))));
+        Ok(())
+    }
+}
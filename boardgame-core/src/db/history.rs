@@ -0,0 +1,363 @@
+use std::{fmt, str::FromStr};
+
+use chrono::NaiveDateTime;
+use rusqlite::{
+    params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef},
+    Connection, Row,
+};
+
+use super::{Boardgame, BoardgameDb};
+use crate::errors::Error;
+
+/// Recorded as the actor until `BoardgameDb::set_actor` is called.
+pub const UNKNOWN_ACTOR: &str = "unknown";
+
+/// Every table whose changes are recorded, with an expression for the boardgame a row
+/// belongs to. `{row}` stands for the row, `NEW` or `OLD` in the triggers.
+const TRACKED_TABLES: &[(&str, &str)] = &[
+    ("boardgames", "{row}.id"),
+    ("boardgame_mechanics", "{row}.boardgame_id"),
+    ("people", "NULL"),
+    ("companies", "NULL"),
+    ("person_credits", "{row}.boardgame_id"),
+    ("company_credits", "{row}.boardgame_id"),
+    ("player_count_votes", "{row}.boardgame_id"),
+    ("custom_fields", "NULL"),
+    ("custom_field_values", "{row}.boardgame_id"),
+    // Image data is left out, the hash says which file it was
+    ("images", "{row}.boardgame_id"),
+    ("copies", "{row}.boardgame_id"),
+    ("components", "(SELECT boardgame_id FROM copies WHERE id = {row}.copy_id)"),
+    ("house_rules", "{row}.boardgame_id"),
+];
+
+/// The latest recorded state, as of `?2`, of every row belonging to boardgame `?1`.
+const ROWS_AS_OF: &str = "WITH as_of AS (
+    SELECT * FROM history WHERE action != 'delete' AND id IN (
+        SELECT max(id) FROM history
+        WHERE changed_at <= ?2 AND (boardgame_id = ?1 OR (table_name = 'boardgames' AND row_id = ?1))
+        GROUP BY table_name, row_id
+    )
+)";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistoryAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl HistoryAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryAction::Create => "create",
+            HistoryAction::Update => "update",
+            HistoryAction::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for HistoryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HistoryAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [HistoryAction::Create, HistoryAction::Update, HistoryAction::Delete]
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| Error::InvalidInput(format!("unknown history action '{}'", s)))
+    }
+}
+
+impl ToSql for HistoryAction {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for HistoryAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// One column that differs between a history entry's before and after snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// A single recorded create, update or delete of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// The table the row is in, e.g. `boardgames` or `house_rules`.
+    pub table: String,
+    pub row_id: i64,
+    /// The boardgame the row belongs to, if it belongs to one.
+    pub boardgame_id: Option<i64>,
+    pub action: HistoryAction,
+    pub actor: String,
+    pub changed_at: NaiveDateTime,
+    /// The row as JSON before the change, for updates and deletes.
+    pub before: Option<String>,
+    /// The row as JSON after the change, for creates and updates.
+    pub after: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+impl HistoryEntry {
+    fn from_row(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            table: row.get(1)?,
+            row_id: row.get(2)?,
+            boardgame_id: row.get(3)?,
+            action: row.get(4)?,
+            actor: row.get(5)?,
+            changed_at: row.get(6)?,
+            before: row.get(7)?,
+            after: row.get(8)?,
+            changes: Vec::new(),
+        })
+    }
+}
+
+fn display_value(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Integer(number) => Some(number.to_string()),
+        Value::Real(number) => Some(number.to_string()),
+        Value::Text(text) => Some(text),
+        Value::Blob(bytes) => Some(format!("{} bytes", bytes.len())),
+    }
+}
+
+/// Creates the triggers that record changes into `history`. They are temporary so they
+/// only exist on connections opened by `BoardgameDb` and always match the current columns.
+/// Rows written before history existed are recorded as created now.
+pub(super) fn install_triggers(conn: &Connection) -> Result<(), Error> {
+    conn.execute_batch(&format!(
+        "CREATE TEMP TABLE IF NOT EXISTS history_actor (name TEXT NOT NULL);
+         INSERT INTO history_actor SELECT '{UNKNOWN_ACTOR}' WHERE NOT EXISTS (SELECT 1 FROM history_actor);"
+    ))?;
+
+    for (table, boardgame_id) in TRACKED_TABLES {
+        let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{table}')"))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+        let snapshot = |row: &str| {
+            let pairs: Vec<String> = columns.iter().map(|column| format!("'{column}', {row}.{column}")).collect();
+            format!("json_object({})", pairs.join(", "))
+        };
+        let boardgame_id = |row: &str| boardgame_id.replace("{row}", row);
+        let (new, old, existing) = (snapshot("NEW"), snapshot("OLD"), snapshot("t"));
+        let (new_id, old_id, existing_id) = (boardgame_id("NEW"), boardgame_id("OLD"), boardgame_id("t"));
+        let insert = "INSERT INTO history (table_name, row_id, boardgame_id, action, actor, before, after)";
+        let actor = "(SELECT name FROM history_actor)";
+
+        conn.execute_batch(&format!(
+            "CREATE TEMP TRIGGER IF NOT EXISTS history_{table}_insert AFTER INSERT ON main.{table} BEGIN
+                {insert} VALUES ('{table}', NEW.rowid, {new_id}, 'create', {actor}, NULL, {new});
+             END;
+             CREATE TEMP TRIGGER IF NOT EXISTS history_{table}_update AFTER UPDATE ON main.{table}
+             WHEN {old} IS NOT {new} BEGIN
+                {insert} VALUES ('{table}', NEW.rowid, {new_id}, 'update', {actor}, {old}, {new});
+             END;
+             CREATE TEMP TRIGGER IF NOT EXISTS history_{table}_delete AFTER DELETE ON main.{table} BEGIN
+                {insert} VALUES ('{table}', OLD.rowid, {old_id}, 'delete', {actor}, {old}, NULL);
+             END;
+             {insert} SELECT '{table}', t.rowid, {existing_id}, 'create', {actor}, NULL, {existing}
+             FROM main.{table} AS t
+             WHERE NOT EXISTS (SELECT 1 FROM history h WHERE h.table_name = '{table}' AND h.row_id = t.rowid);"
+        ))?;
+    }
+    Ok(())
+}
+
+impl BoardgameDb {
+    /// Names who the changes made from now on are recorded as made by.
+    pub fn set_actor(&self, actor: &str) -> Result<(), Error> {
+        self.conn.execute("UPDATE temp.history_actor SET name = ?1", params![actor.trim()])?;
+        Ok(())
+    }
+
+    /// Every recorded change to the boardgame and the rows that belong to it, newest first.
+    pub fn history_for(&self, boardgame_id: i64) -> Result<Vec<HistoryEntry>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, table_name, row_id, boardgame_id, action, actor, changed_at, before, after
+             FROM history WHERE boardgame_id = ?1 OR (table_name = 'boardgames' AND row_id = ?1)
+             ORDER BY id DESC",
+        )?;
+        let mut entries = stmt
+            .query_map(params![boardgame_id], HistoryEntry::from_row)?
+            .collect::<Result<Vec<HistoryEntry>, rusqlite::Error>>()?;
+
+        let mut changes = self.conn.prepare(
+            "SELECT coalesce(a.key, b.key), b.value, a.value
+             FROM json_each(?1) AS a FULL JOIN json_each(?2) AS b ON a.key = b.key
+             WHERE a.value IS NOT b.value
+             ORDER BY coalesce(a.id, b.id)",
+        )?;
+        for entry in &mut entries {
+            entry.changes = changes
+                .query_map(params![entry.after, entry.before], |row| {
+                    Ok(FieldChange {
+                        field: row.get(0)?,
+                        before: display_value(row.get(1)?),
+                        after: display_value(row.get(2)?),
+                    })
+                })?
+                .collect::<Result<Vec<FieldChange>, rusqlite::Error>>()?;
+        }
+        Ok(entries)
+    }
+
+    /// The boardgame as it was recorded at `at`, or `None` if it didn't exist then.
+    /// Trashed games are returned with `deleted_at` set.
+    pub fn get_boardgame_as_of(&self, id: i64, at: NaiveDateTime) -> Result<Option<Boardgame>, Error> {
+        // The same columns as BOARDGAME_COLUMNS, so Boardgame::from_row can read them
+        let columns = [
+            "id", "name", "min_players", "max_players", "play_time_minutes", "description",
+            "weight", "min_age", "year_published",
+        ]
+        .map(|column| format!("json_extract(after, '$.{column}')"))
+        .join(", ");
+        let mut boardgame = self
+            .conn
+            .prepare(&format!(
+                "{ROWS_AS_OF}
+                 SELECT {columns},
+                     (SELECT group_concat(json_extract(after, '$.mechanic')) FROM as_of
+                      WHERE table_name = 'boardgame_mechanics'),
+                     json_extract(after, '$.deleted_at')
+                 FROM as_of WHERE table_name = 'boardgames'"
+            ))?
+            .query_map(params![id, at], Boardgame::from_row)?
+            .next()
+            .transpose()?;
+
+        if let Some(boardgame) = &mut boardgame {
+            let fields = self.get_custom_fields()?;
+            let mut stmt = self.conn.prepare(&format!(
+                "{ROWS_AS_OF}
+                 SELECT json_extract(after, '$.field_id'), json_extract(after, '$.value') FROM as_of
+                 WHERE table_name = 'custom_field_values'"
+            ))?;
+            let values = stmt.query_map(params![id, at], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Value>(1)?)))?;
+            for value in values {
+                let (field_id, value) = value?;
+                if let Some(field) = fields.iter().find(|field| field.id == field_id) {
+                    boardgame.custom_values.insert(field_id, field.field_type.read_sql(value)?);
+                }
+            }
+        }
+        Ok(boardgame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{HouseRule, Mechanic};
+    use chrono::Utc;
+    use std::{thread, time::Duration};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_history_and_as_of_reads() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        db.set_actor("Sam")?;
+
+        let mut game = Boardgame {
+            name: "Catan".to_string(),
+            mechanics: vec![Mechanic::Trading],
+            ..Default::default()
+        };
+        let id = db.create_boardgame(&game)?;
+        thread::sleep(Duration::from_millis(5));
+        let created = Utc::now().naive_utc();
+        thread::sleep(Duration::from_millis(5));
+
+        db.set_actor("Alex")?;
+        game.id = Some(id);
+        game.name = "Settlers of Catan".to_string();
+        game.mechanics.push(Mechanic::DiceRolling);
+        db.update_boardgame(&game)?;
+        // Saving without changes records nothing
+        db.update_boardgame(&game)?;
+        db.create_house_rule(&HouseRule { boardgame_id: id, title: "Friendly robber".to_string(), ..Default::default() })?;
+
+        let history = db.history_for(id)?;
+        let tables: Vec<(&str, HistoryAction, &str)> =
+            history.iter().map(|e| (e.table.as_str(), e.action, e.actor.as_str())).collect();
+        assert_eq!(
+            tables,
+            [
+                ("house_rules", HistoryAction::Create, "Alex"),
+                ("boardgame_mechanics", HistoryAction::Create, "Alex"),
+                ("boardgames", HistoryAction::Update, "Alex"),
+                ("boardgame_mechanics", HistoryAction::Create, "Sam"),
+                ("boardgames", HistoryAction::Create, "Sam"),
+            ]
+        );
+        assert_eq!(
+            history[2].changes,
+            [FieldChange {
+                field: "name".to_string(),
+                before: Some("Catan".to_string()),
+                after: Some("Settlers of Catan".to_string()),
+            }]
+        );
+        assert!(db.conn.execute("DELETE FROM history", []).is_err(), "history is append-only");
+
+        let then = db.get_boardgame_as_of(id, created)?.unwrap();
+        assert_eq!((then.name.as_str(), then.mechanics.as_slice()), ("Catan", [Mechanic::Trading].as_slice()));
+        let before_creation = created - chrono::Duration::days(1);
+        assert!(db.get_boardgame_as_of(id, before_creation)?.is_none());
+
+        db.delete_boardgame(id)?;
+        db.purge_boardgame(id)?;
+        assert!(db.get_boardgame_as_of(id, Utc::now().naive_utc())?.is_none());
+        assert_eq!(db.get_boardgame_as_of(id, created)?.unwrap().name, "Catan");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_purged_ids_are_not_reused() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let game = |name: &str| Boardgame { name: name.to_string(), ..Default::default() };
+
+        db.create_boardgame(&game("Azul"))?;
+        let brass = db.create_boardgame(&game("Brass"))?;
+        let short_game =
+            db.create_house_rule(&HouseRule { boardgame_id: brass, title: "Short game".to_string(), ..Default::default() })?;
+        db.delete_boardgame(brass)?;
+        db.purge_boardgame(brass)?;
+
+        // Brass had the highest id, which a new game would otherwise be given
+        let catan = db.create_boardgame(&game("Catan"))?;
+        assert_ne!(catan, brass);
+        let history = db.history_for(catan)?;
+        let entries: Vec<(&str, HistoryAction)> = history.iter().map(|e| (e.table.as_str(), e.action)).collect();
+        assert_eq!(entries, [("boardgames", HistoryAction::Create)]);
+        // Nor are the ids of rows that went with it
+        let robber =
+            db.create_house_rule(&HouseRule { boardgame_id: catan, title: "Friendly robber".to_string(), ..Default::default() })?;
+        assert_ne!(robber, short_game);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::{fmt, fs, io::Cursor, path::Path, str::FromStr};
+
+use chrono::NaiveDateTime;
+use image::{ImageFormat, ImageReader};
+use rusqlite::{
+    params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef},
+    OptionalExtension,
+};
+use sha2::{Digest, Sha256};
+
+use super::BoardgameDb;
+use crate::errors::Error;
+
+/// Longest side of generated thumbnails, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ImageKind {
+    /// Box art. A game has at most one.
+    Cover,
+    /// A photo of our copy.
+    Photo,
+}
+
+impl ImageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageKind::Cover => "cover",
+            ImageKind::Photo => "photo",
+        }
+    }
+}
+
+impl fmt::Display for ImageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImageKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [ImageKind::Cover, ImageKind::Photo]
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::InvalidInput(format!("unknown image kind '{}'", s.trim())))
+    }
+}
+
+impl ToSql for ImageKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ImageKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// An image attached to a boardgame, without its pixel data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInfo {
+    pub id: i64,
+    pub boardgame_id: i64,
+    pub kind: ImageKind,
+    /// SHA-256 of the original file, used to store identical files once.
+    pub hash: String,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub added_at: NaiveDateTime,
+}
+
+const IMAGE_COLUMNS: &str = "i.id, i.boardgame_id, i.kind, i.hash, b.mime_type, b.width, b.height, i.added_at";
+
+fn image_info_from_row(row: &rusqlite::Row) -> Result<ImageInfo, rusqlite::Error> {
+    Ok(ImageInfo {
+        id: row.get(0)?,
+        boardgame_id: row.get(1)?,
+        kind: row.get(2)?,
+        hash: row.get(3)?,
+        mime_type: row.get(4)?,
+        width: row.get(5)?,
+        height: row.get(6)?,
+        added_at: row.get(7)?,
+    })
+}
+
+impl BoardgameDb {
+    /// Attaches an image file to the boardgame. The file is decoded to check it is an image
+    /// we can read and to generate a PNG thumbnail. Attaching the same file twice to one game
+    /// returns the existing image. Adding a cover turns any previous cover into a photo.
+    pub fn add_image(&self, boardgame_id: i64, kind: ImageKind, bytes: &[u8]) -> Result<i64, Error> {
+        let hash = format!("{:x}", Sha256::digest(bytes));
+        let reader = ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| Error::InvalidInput(format!("couldn't read image: {}", e)))?;
+        let format = reader
+            .format()
+            .ok_or_else(|| Error::InvalidInput("unrecognised image format".into()))?;
+        let decoded = reader.decode()?;
+        let mut thumbnail = Vec::new();
+        decoded
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .write_to(&mut Cursor::new(&mut thumbnail), ImageFormat::Png)?;
+
+        let tx = self.transaction()?;
+        tx.conn.execute(
+            "INSERT OR IGNORE INTO image_blobs (hash, mime_type, width, height, data, thumbnail)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![hash, format.to_mime_type(), decoded.width(), decoded.height(), bytes, thumbnail],
+        )?;
+        let existing: Option<i64> = tx
+            .conn
+            .query_row(
+                "SELECT id FROM images WHERE boardgame_id = ?1 AND hash = ?2",
+                params![boardgame_id, hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                tx.conn.execute(
+                    "INSERT INTO images (boardgame_id, kind, hash) VALUES (?1, 'photo', ?2)",
+                    params![boardgame_id, hash],
+                )?;
+                tx.conn.last_insert_rowid()
+            }
+        };
+        if kind == ImageKind::Cover {
+            tx.set_cover(id)?;
+        }
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    pub fn add_image_from_path<P: AsRef<Path>>(&self, boardgame_id: i64, kind: ImageKind, path: P) -> Result<i64, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .map_err(|e| Error::InvalidInput(format!("couldn't read {}: {}", path.display(), e)))?;
+        self.add_image(boardgame_id, kind, &bytes)
+    }
+
+    /// Makes the image its game's cover, turning any previous cover into a photo.
+    pub fn set_cover(&self, image_id: i64) -> Result<(), Error> {
+        // Together, so a game is never left without its cover
+        let tx = self.transaction()?;
+        tx.conn.execute(
+            "UPDATE images SET kind = 'photo'
+             WHERE kind = 'cover' AND boardgame_id = (SELECT boardgame_id FROM images WHERE id = ?1)",
+            params![image_id],
+        )?;
+        tx.conn.execute("UPDATE images SET kind = 'cover' WHERE id = ?1", params![image_id])?;
+        tx.commit()
+    }
+
+    /// Images on a boardgame, cover first and then in the order they were added.
+    pub fn get_images(&self, boardgame_id: i64) -> Result<Vec<ImageInfo>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {IMAGE_COLUMNS} FROM images i JOIN image_blobs b ON b.hash = i.hash
+             WHERE i.boardgame_id = ?1 ORDER BY i.kind = 'cover' DESC, i.id"
+        ))?;
+        let images = stmt.query_map(params![boardgame_id], image_info_from_row)?;
+        Ok(images.collect::<Result<Vec<ImageInfo>, rusqlite::Error>>()?)
+    }
+
+    /// The original file, as it was added.
+    pub fn get_image_data(&self, image_id: i64) -> Result<Option<Vec<u8>>, Error> {
+        self.get_image_blob(image_id, "data")
+    }
+
+    /// A PNG no larger than `THUMBNAIL_SIZE` on either side.
+    pub fn get_thumbnail(&self, image_id: i64) -> Result<Option<Vec<u8>>, Error> {
+        self.get_image_blob(image_id, "thumbnail")
+    }
+
+    fn get_image_blob(&self, image_id: i64, column: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT b.{column} FROM images i JOIN image_blobs b ON b.hash = i.hash WHERE i.id = ?1"),
+                params![image_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Removes the image from its game, and its data once no game uses it.
+    pub fn delete_image(&self, image_id: i64) -> Result<usize, Error> {
+        let tx = self.transaction()?;
+        let deleted = tx.conn.execute("DELETE FROM images WHERE id = ?1", params![image_id])?;
+        tx.delete_orphaned_image_blobs()?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    pub(super) fn delete_orphaned_image_blobs(&self) -> Result<usize, Error> {
+        Ok(self.conn.execute("DELETE FROM image_blobs WHERE hash NOT IN (SELECT hash FROM images)", [])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Boardgame;
+    use image::{DynamicImage, RgbImage};
+    use tempfile::tempdir;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .expect("failed to encode png");
+        bytes
+    }
+
+    #[test]
+    fn test_images_are_deduplicated_and_thumbnailed() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let id = db.create_boardgame(&Boardgame { name: "Root".to_string(), ..Default::default() })?;
+
+        let photo = db.add_image(id, ImageKind::Photo, &png(1024, 512))?;
+        assert_eq!(db.add_image(id, ImageKind::Photo, &png(1024, 512))?, photo);
+        let cover = db.add_image(id, ImageKind::Cover, &png(10, 10))?;
+        assert!(db.add_image(id, ImageKind::Photo, b"not an image").is_err());
+
+        let images = db.get_images(id)?;
+        assert_eq!(images.iter().map(|i| (i.id, i.kind)).collect::<Vec<_>>(), [(cover, ImageKind::Cover), (photo, ImageKind::Photo)]);
+        assert_eq!((images[1].width, images[1].height), (1024, 512));
+
+        let thumbnail = image::load_from_memory(&db.get_thumbnail(photo)?.unwrap()).expect("thumbnail is a png");
+        assert_eq!((thumbnail.width(), thumbnail.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+
+        db.delete_image(photo)?;
+        assert_eq!(db.get_image_data(photo)?, None);
+
+        Ok(())
+    }
+}
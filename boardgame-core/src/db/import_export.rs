@@ -0,0 +1,205 @@
+use std::io::{Read, Write};
+
+use super::{Boardgame, BoardgameDb, CustomField, Mechanic};
+use crate::errors::Error;
+
+/// The columns every export has, in order. Custom fields follow, one column each by name.
+const COLUMNS: [&str; 9] = [
+    "name",
+    "min_players",
+    "max_players",
+    "play_time_minutes",
+    "description",
+    "weight",
+    "min_age",
+    "year_published",
+    "mechanics",
+];
+
+/// Separates the mechanics in their column.
+const MECHANIC_SEPARATOR: char = ';';
+
+impl BoardgameDb {
+    /// Writes every boardgame not in the trash as CSV, with a column for each custom field.
+    /// Empty cells are values that aren't set. Credits, copies, images and rules aren't included.
+    pub fn export_csv<W: Write>(&self, writer: W) -> Result<usize, Error> {
+        let fields = self.get_custom_fields()?;
+        let boardgames = self.get_all_boardgames()?;
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(COLUMNS.iter().copied().chain(fields.iter().map(|field| field.name.as_str())))?;
+        for boardgame in &boardgames {
+            let optional = |value: Option<String>| value.unwrap_or_default();
+            let mechanics: Vec<&str> = boardgame.mechanics.iter().map(Mechanic::as_str).collect();
+            let mut record = vec![
+                boardgame.name.clone(),
+                boardgame.min_players.to_string(),
+                boardgame.max_players.to_string(),
+                boardgame.play_time_minutes.to_string(),
+                boardgame.description.clone(),
+                optional(boardgame.weight.map(|weight| weight.to_string())),
+                optional(boardgame.min_age.map(|age| age.to_string())),
+                optional(boardgame.year_published.map(|year| year.to_string())),
+                mechanics.join(&MECHANIC_SEPARATOR.to_string()),
+            ];
+            record.extend(fields.iter().map(|field| optional(boardgame.custom_values.get(&field.id).map(ToString::to_string))));
+            csv.write_record(&record)?;
+        }
+        csv.flush().map_err(csv::Error::from)?;
+        Ok(boardgames.len())
+    }
+
+    /// Creates a boardgame for each row of CSV as `export_csv` writes it, all of them or, if any
+    /// row is invalid, none. Only `name` is required, and columns may be in any order. Columns
+    /// after the fixed ones must name existing custom fields.
+    pub fn import_csv<R: Read>(&self, reader: R) -> Result<Vec<i64>, Error> {
+        let fields = self.get_custom_fields()?;
+        let mut csv = csv::Reader::from_reader(reader);
+        let headers = csv.headers()?.clone();
+        let columns = headers
+            .iter()
+            .map(|header| Column::for_header(header.trim(), &fields))
+            .collect::<Result<Vec<Column>, Error>>()?;
+        if !columns.iter().any(|column| matches!(column, Column::Fixed("name"))) {
+            return Err(Error::InvalidInput("the CSV has no name column".into()));
+        }
+
+        let mut boardgames = Vec::new();
+        for (i, record) in csv.records().enumerate() {
+            // The header is line 1
+            let line = i + 2;
+            let record = record?;
+            let mut boardgame = Boardgame::default();
+            for (column, cell) in columns.iter().zip(record.iter()) {
+                column
+                    .read(cell, &mut boardgame)
+                    .map_err(|e| Error::InvalidInput(format!("line {}: {}", line, invalid_input_message(e))))?;
+            }
+            boardgames.push(boardgame);
+        }
+        self.create_boardgames(&boardgames)
+    }
+}
+
+/// What a CSV column holds.
+enum Column<'a> {
+    Fixed(&'static str),
+    Custom(&'a CustomField),
+}
+
+impl<'a> Column<'a> {
+    fn for_header(header: &str, fields: &'a [CustomField]) -> Result<Self, Error> {
+        if let Some(column) = COLUMNS.iter().find(|column| column.eq_ignore_ascii_case(header)) {
+            return Ok(Column::Fixed(column));
+        }
+        fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(header))
+            .map(Column::Custom)
+            .ok_or_else(|| Error::InvalidInput(format!("unknown column '{}', add a custom field for it first", header)))
+    }
+
+    /// Sets what the cell holds on `boardgame`, leaving it unset for an empty cell.
+    fn read(&self, cell: &str, boardgame: &mut Boardgame) -> Result<(), Error> {
+        let cell = cell.trim();
+        let number = |name: &str| {
+            cell.parse::<i32>().map_err(|_| Error::InvalidInput(format!("{} needs a whole number, got '{}'", name, cell)))
+        };
+        match self {
+            Column::Fixed("name") => boardgame.name = cell.to_string(),
+            Column::Fixed("description") => boardgame.description = cell.to_string(),
+            _ if cell.is_empty() => {}
+            Column::Fixed(name @ "min_players") => boardgame.min_players = number(name)?,
+            Column::Fixed(name @ "max_players") => boardgame.max_players = number(name)?,
+            Column::Fixed(name @ "play_time_minutes") => boardgame.play_time_minutes = number(name)?,
+            Column::Fixed(name @ "min_age") => boardgame.min_age = Some(number(name)?),
+            Column::Fixed(name @ "year_published") => boardgame.year_published = Some(number(name)?),
+            Column::Fixed("weight") => {
+                boardgame.weight = Some(
+                    cell.parse().map_err(|_| Error::InvalidInput(format!("weight needs a number, got '{}'", cell)))?,
+                )
+            }
+            Column::Fixed("mechanics") => {
+                boardgame.mechanics = cell
+                    .split(MECHANIC_SEPARATOR)
+                    .map(|mechanic| mechanic.trim().parse())
+                    .collect::<Result<Vec<Mechanic>, Error>>()?
+            }
+            Column::Fixed(column) => unreachable!("no reader for the {} column", column),
+            Column::Custom(field) => {
+                boardgame.custom_values.insert(field.id, field.parse_value(cell)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The message of an input error without the "Invalid input" its display starts with.
+fn invalid_input_message(error: Error) -> String {
+    match error {
+        Error::InvalidInput(message) => message,
+        error => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CustomFieldType, CustomValue};
+    use chrono::NaiveDate;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_and_import() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let sleeved = db.create_custom_field("Sleeved?", &CustomFieldType::Bool)?;
+        let bought = db.create_custom_field("Bought", &CustomFieldType::Date)?;
+        db.create_custom_field("Language", &"enum: English, German".parse()?)?;
+
+        let mut brass = Boardgame {
+            name: "Brass".to_string(),
+            min_players: 2,
+            max_players: 4,
+            play_time_minutes: 120,
+            description: "Canals, \"rails\"\nand beer".to_string(),
+            weight: Some(3.9),
+            mechanics: vec![Mechanic::HandManagement, Mechanic::NetworkBuilding],
+            ..Default::default()
+        };
+        brass.custom_values.insert(sleeved, CustomValue::Bool(true));
+        brass.custom_values.insert(bought, CustomValue::Date(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()));
+        let azul = Boardgame { name: "Azul".to_string(), min_age: Some(8), ..Default::default() };
+        db.create_boardgames(&[brass, azul])?;
+
+        let mut exported = Vec::new();
+        assert_eq!(db.export_csv(&mut exported)?, 2);
+        let text = String::from_utf8(exported.clone()).unwrap();
+        assert!(text.starts_with("name,min_players,max_players,play_time_minutes,description,weight,min_age,year_published,mechanics,Sleeved?,Bought,Language\n"));
+        assert!(text.contains("\"Canals, \"\"rails\"\"\nand beer\",3.9,,,hand_management;network_building,yes,2024-03-01,\n"));
+
+        // Into another database, where the same fields have other ids, everything comes back
+        let other = BoardgameDb::new(dir.path().join("other.db"))?;
+        other.create_custom_field("Language", &"enum: English, German".parse()?)?;
+        let other_bought = other.create_custom_field("Bought", &CustomFieldType::Date)?;
+        other.create_custom_field("Sleeved?", &CustomFieldType::Bool)?;
+        assert_eq!(other.import_csv(exported.as_slice())?.len(), 2);
+        let imported = other.get_all_boardgames()?;
+        let brass = imported.iter().find(|game| game.name == "Brass").unwrap();
+        assert_eq!(brass.description, "Canals, \"rails\"\nand beer");
+        assert_eq!(brass.weight, Some(3.9));
+        assert_eq!(brass.mechanics, [Mechanic::HandManagement, Mechanic::NetworkBuilding]);
+        assert_eq!(brass.custom_values.len(), 2);
+        assert_eq!(brass.custom_values[&other_bought], CustomValue::Date(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()));
+        assert_eq!(imported.iter().find(|game| game.name == "Azul").unwrap().min_age, Some(8));
+
+        // A bad row stops the whole import
+        let csv = "Name,Language\nCatan,English\nRoot,French\n";
+        let error = db.import_csv(csv.as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid input: line 3: 'Language' needs one of English, German, got 'French'");
+        assert_eq!(db.get_all_boardgames()?.len(), 2);
+        assert!(db.import_csv("name,colour\nCatan,red\n".as_bytes()).is_err());
+        assert!(db.import_csv("title\nCatan\n".as_bytes()).is_err());
+
+        Ok(())
+    }
+}
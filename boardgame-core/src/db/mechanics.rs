@@ -0,0 +1,108 @@
+use std::{fmt, str::FromStr};
+
+use crate::errors::Error;
+
+/// The controlled vocabulary of game mechanics.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Mechanic {
+    AreaControl,
+    Auction,
+    Cooperative,
+    DeckBuilding,
+    DiceRolling,
+    Drafting,
+    EngineBuilding,
+    HandManagement,
+    HiddenRoles,
+    NetworkBuilding,
+    PushYourLuck,
+    RollAndWrite,
+    SetCollection,
+    TileLaying,
+    Trading,
+    WorkerPlacement,
+}
+
+impl Mechanic {
+    pub const ALL: [Mechanic; 16] = [
+        Mechanic::AreaControl,
+        Mechanic::Auction,
+        Mechanic::Cooperative,
+        Mechanic::DeckBuilding,
+        Mechanic::DiceRolling,
+        Mechanic::Drafting,
+        Mechanic::EngineBuilding,
+        Mechanic::HandManagement,
+        Mechanic::HiddenRoles,
+        Mechanic::NetworkBuilding,
+        Mechanic::PushYourLuck,
+        Mechanic::RollAndWrite,
+        Mechanic::SetCollection,
+        Mechanic::TileLaying,
+        Mechanic::Trading,
+        Mechanic::WorkerPlacement,
+    ];
+
+    /// Key stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mechanic::AreaControl => "area_control",
+            Mechanic::Auction => "auction",
+            Mechanic::Cooperative => "cooperative",
+            Mechanic::DeckBuilding => "deck_building",
+            Mechanic::DiceRolling => "dice_rolling",
+            Mechanic::Drafting => "drafting",
+            Mechanic::EngineBuilding => "engine_building",
+            Mechanic::HandManagement => "hand_management",
+            Mechanic::HiddenRoles => "hidden_roles",
+            Mechanic::NetworkBuilding => "network_building",
+            Mechanic::PushYourLuck => "push_your_luck",
+            Mechanic::RollAndWrite => "roll_and_write",
+            Mechanic::SetCollection => "set_collection",
+            Mechanic::TileLaying => "tile_laying",
+            Mechanic::Trading => "trading",
+            Mechanic::WorkerPlacement => "worker_placement",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mechanic::AreaControl => "Area control",
+            Mechanic::Auction => "Auction",
+            Mechanic::Cooperative => "Cooperative",
+            Mechanic::DeckBuilding => "Deck building",
+            Mechanic::DiceRolling => "Dice rolling",
+            Mechanic::Drafting => "Drafting",
+            Mechanic::EngineBuilding => "Engine building",
+            Mechanic::HandManagement => "Hand management",
+            Mechanic::HiddenRoles => "Hidden roles",
+            Mechanic::NetworkBuilding => "Network building",
+            Mechanic::PushYourLuck => "Push your luck",
+            Mechanic::RollAndWrite => "Roll and write",
+            Mechanic::SetCollection => "Set collection",
+            Mechanic::TileLaying => "Tile laying",
+            Mechanic::Trading => "Trading",
+            Mechanic::WorkerPlacement => "Worker placement",
+        }
+    }
+}
+
+impl fmt::Display for Mechanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Accepts either the database key or the label, ignoring case, so "Worker placement",
+/// "worker_placement" and "worker-placement" are all the same mechanic.
+impl FromStr for Mechanic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_lowercase().replace([' ', '-'], "_");
+        Mechanic::ALL
+            .into_iter()
+            .find(|mechanic| mechanic.as_str() == key)
+            .ok_or_else(|| Error::InvalidInput(format!("unknown mechanic '{}'", s.trim())))
+    }
+}
@@ -0,0 +1,256 @@
+use rusqlite::Connection;
+
+use crate::errors::Error;
+
+/// Schema changes, applied in order. `PRAGMA user_version` records how many have run,
+/// so append new migrations to the end and never edit one that has shipped.
+const MIGRATIONS: &[&str] = &[
+    // Databases created before migrations existed already have some of these tables,
+    // hence IF NOT EXISTS
+    "CREATE TABLE IF NOT EXISTS boardgames (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        min_players INTEGER NOT NULL,
+        max_players INTEGER NOT NULL,
+        play_time_minutes INTEGER NOT NULL,
+        description TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS people (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE
+    );
+    CREATE TABLE IF NOT EXISTS companies (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE
+    );
+    CREATE TABLE IF NOT EXISTS person_credits (
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        person_id INTEGER NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+        role TEXT NOT NULL,
+        PRIMARY KEY (boardgame_id, person_id, role)
+    );
+    CREATE TABLE IF NOT EXISTS company_credits (
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        company_id INTEGER NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+        role TEXT NOT NULL,
+        PRIMARY KEY (boardgame_id, company_id, role)
+    );",
+    "ALTER TABLE boardgames ADD COLUMN weight REAL;
+    ALTER TABLE boardgames ADD COLUMN min_age INTEGER;
+    ALTER TABLE boardgames ADD COLUMN year_published INTEGER;
+    CREATE TABLE boardgame_mechanics (
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        mechanic TEXT NOT NULL,
+        PRIMARY KEY (boardgame_id, mechanic)
+    );",
+    "CREATE TABLE player_count_votes (
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        player_count INTEGER NOT NULL,
+        voter TEXT NOT NULL,
+        vote TEXT NOT NULL,
+        PRIMARY KEY (boardgame_id, player_count, voter)
+    );",
+    "CREATE TABLE custom_fields (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        kind TEXT NOT NULL,
+        options TEXT NOT NULL
+    );
+    CREATE TABLE custom_field_values (
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        field_id INTEGER NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+        value,
+        PRIMARY KEY (boardgame_id, field_id)
+    );",
+    "CREATE TABLE image_blobs (
+        hash TEXT PRIMARY KEY,
+        mime_type TEXT NOT NULL,
+        width INTEGER NOT NULL,
+        height INTEGER NOT NULL,
+        data BLOB NOT NULL,
+        thumbnail BLOB NOT NULL
+    );
+    CREATE TABLE images (
+        id INTEGER PRIMARY KEY,
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        hash TEXT NOT NULL REFERENCES image_blobs(hash),
+        added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (boardgame_id, hash)
+    );",
+    "CREATE TABLE copies (
+        id INTEGER PRIMARY KEY,
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        edition TEXT NOT NULL,
+        language TEXT NOT NULL,
+        publisher_year INTEGER,
+        last_audited_at TEXT
+    );
+    CREATE TABLE components (
+        id INTEGER PRIMARY KEY,
+        copy_id INTEGER NOT NULL REFERENCES copies(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        expected INTEGER NOT NULL,
+        present INTEGER NOT NULL,
+        notes TEXT NOT NULL
+    );",
+    "CREATE TABLE house_rules (
+        id INTEGER PRIMARY KEY,
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        author TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        active INTEGER NOT NULL
+    );",
+    "ALTER TABLE boardgames ADD COLUMN deleted_at TEXT;",
+    // Written by the triggers in history.rs. No foreign keys, entries outlive what they describe
+    "CREATE TABLE history (
+        id INTEGER PRIMARY KEY,
+        table_name TEXT NOT NULL,
+        row_id INTEGER NOT NULL,
+        boardgame_id INTEGER,
+        action TEXT NOT NULL,
+        actor TEXT NOT NULL,
+        changed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+        before TEXT,
+        after TEXT
+    );
+    CREATE INDEX history_rows ON history (table_name, row_id);
+    CREATE INDEX history_boardgames ON history (boardgame_id);
+    CREATE TRIGGER history_no_update BEFORE UPDATE ON history BEGIN
+        SELECT RAISE(ABORT, 'history is append-only');
+    END;
+    CREATE TRIGGER history_no_delete BEFORE DELETE ON history BEGIN
+        SELECT RAISE(ABORT, 'history is append-only');
+    END;",
+    // History follows rows by id, so ids must never be handed out again once their row is
+    // deleted. The sequences start past every id history has seen, even ones already reused.
+    "CREATE TABLE new_boardgames (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        min_players INTEGER NOT NULL,
+        max_players INTEGER NOT NULL,
+        play_time_minutes INTEGER NOT NULL,
+        description TEXT NOT NULL,
+        weight REAL,
+        min_age INTEGER,
+        year_published INTEGER,
+        deleted_at TEXT
+    );
+    INSERT INTO new_boardgames
+    SELECT id, name, min_players, max_players, play_time_minutes, description, weight, min_age, year_published, deleted_at
+    FROM boardgames;
+    DROP TABLE boardgames;
+    ALTER TABLE new_boardgames RENAME TO boardgames;
+    CREATE TABLE new_copies (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        edition TEXT NOT NULL,
+        language TEXT NOT NULL,
+        publisher_year INTEGER,
+        last_audited_at TEXT
+    );
+    INSERT INTO new_copies SELECT id, boardgame_id, edition, language, publisher_year, last_audited_at FROM copies;
+    DROP TABLE copies;
+    ALTER TABLE new_copies RENAME TO copies;
+    CREATE TABLE new_components (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        copy_id INTEGER NOT NULL REFERENCES copies(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        expected INTEGER NOT NULL,
+        present INTEGER NOT NULL,
+        notes TEXT NOT NULL
+    );
+    INSERT INTO new_components SELECT id, copy_id, name, expected, present, notes FROM components;
+    DROP TABLE components;
+    ALTER TABLE new_components RENAME TO components;
+    CREATE TABLE new_house_rules (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        boardgame_id INTEGER NOT NULL REFERENCES boardgames(id) ON DELETE CASCADE,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        author TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        active INTEGER NOT NULL
+    );
+    INSERT INTO new_house_rules SELECT id, boardgame_id, title, body, author, created_at, active FROM house_rules;
+    DROP TABLE house_rules;
+    ALTER TABLE new_house_rules RENAME TO house_rules;
+    DELETE FROM sqlite_sequence;
+    INSERT INTO sqlite_sequence (name, seq)
+    SELECT name, max(coalesce((SELECT max(row_id) FROM history WHERE table_name = name), 0), coalesce(seq, 0))
+    FROM (
+        SELECT 'boardgames' AS name, (SELECT max(id) FROM boardgames) AS seq
+        UNION ALL SELECT 'copies', (SELECT max(id) FROM copies)
+        UNION ALL SELECT 'components', (SELECT max(id) FROM components)
+        UNION ALL SELECT 'house_rules', (SELECT max(id) FROM house_rules)
+    );",
+];
+
+/// Brings the schema up to date, running each pending migration in its own transaction.
+///
+/// Foreign keys must be off, since rebuilding a table drops the old one, which would cascade
+/// into the rows referencing it. Each migration checks that it left them intact instead.
+pub(super) fn migrate(conn: &mut Connection) -> Result<(), Error> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > MIGRATIONS.len() {
+        return Err(Error::InvalidInput(format!(
+            "database schema version {} is newer than this build supports ({})",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        let broken: i64 = tx.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))?;
+        if broken > 0 {
+            return Err(Error::InvalidInput(format!("migration {} left {} rows with broken references", i + 1, broken)));
+        }
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrates_database_created_before_migrations() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db_path = dir.path().join("old.db");
+
+        let old = Connection::open(&db_path)?;
+        old.execute_batch(
+            "CREATE TABLE boardgames (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                min_players INTEGER NOT NULL,
+                max_players INTEGER NOT NULL,
+                play_time_minutes INTEGER NOT NULL,
+                description TEXT NOT NULL
+            );
+            INSERT INTO boardgames (name, min_players, max_players, play_time_minutes, description)
+            VALUES ('Catan', 3, 4, 60, '');",
+        )?;
+        drop(old);
+
+        let db = crate::db::BoardgameDb::new(&db_path)?;
+        let catan = db.get_all_boardgames()?.pop().expect("existing row survives");
+        assert_eq!(catan.name, "Catan");
+        assert_eq!(catan.weight, None);
+        assert!(catan.mechanics.is_empty());
+
+        // Reopening is a no-op
+        drop(db);
+        crate::db::BoardgameDb::new(&db_path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use std::{fmt, str::FromStr};
+
+use rusqlite::{
+    params,
+    OptionalExtension,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef},
+};
+
+use super::BoardgameDb;
+use crate::errors::Error;
+
+/// How well a game plays at a particular player count.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PlayerCountVote {
+    Best,
+    Recommended,
+    NotRecommended,
+}
+
+impl PlayerCountVote {
+    pub const ALL: [PlayerCountVote; 3] = [
+        PlayerCountVote::Best,
+        PlayerCountVote::Recommended,
+        PlayerCountVote::NotRecommended,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerCountVote::Best => "best",
+            PlayerCountVote::Recommended => "recommended",
+            PlayerCountVote::NotRecommended => "not_recommended",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayerCountVote::Best => "Best",
+            PlayerCountVote::Recommended => "Recommended",
+            PlayerCountVote::NotRecommended => "Not recommended",
+        }
+    }
+}
+
+impl fmt::Display for PlayerCountVote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for PlayerCountVote {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_lowercase().replace([' ', '-'], "_");
+        PlayerCountVote::ALL
+            .into_iter()
+            .find(|vote| vote.as_str() == key)
+            .ok_or_else(|| Error::InvalidInput(format!("unknown player count vote '{}'", s.trim())))
+    }
+}
+
+impl ToSql for PlayerCountVote {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for PlayerCountVote {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// Which player counts `BoardgameFilter::players` matches against.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PlayerCountMatch {
+    /// Between `min_players` and `max_players`.
+    #[default]
+    Supported,
+    /// In the game's `PlayerCountPoll::recommended_at` set.
+    Recommended,
+    /// In the game's `PlayerCountPoll::best_at` set.
+    Best,
+}
+
+impl PlayerCountMatch {
+    pub const ALL: [PlayerCountMatch; 3] = [
+        PlayerCountMatch::Supported,
+        PlayerCountMatch::Recommended,
+        PlayerCountMatch::Best,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerCountMatch::Supported => "supported",
+            PlayerCountMatch::Recommended => "recommended",
+            PlayerCountMatch::Best => "best",
+        }
+    }
+}
+
+impl fmt::Display for PlayerCountMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PlayerCountMatch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PlayerCountMatch::ALL
+            .into_iter()
+            .find(|m| m.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::InvalidInput(format!("unknown player count match '{}'", s.trim())))
+    }
+}
+
+/// SQL condition on `player_count_votes` grouped by boardgame and player count that holds
+/// when the count is recommended. Kept next to `PlayerCountTally::is_recommended` so they agree.
+pub(super) const RECOMMENDED_HAVING: &str =
+    "SUM(vote = 'best') + SUM(vote = 'recommended') > SUM(vote = 'not_recommended')";
+/// As `RECOMMENDED_HAVING`, for `PlayerCountTally::is_best`.
+pub(super) const BEST_HAVING: &str =
+    "SUM(vote = 'best') > SUM(vote = 'recommended') AND SUM(vote = 'best') > SUM(vote = 'not_recommended')";
+
+/// Vote totals for one player count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerCountTally {
+    pub player_count: i32,
+    pub best: u32,
+    pub recommended: u32,
+    pub not_recommended: u32,
+}
+
+impl PlayerCountTally {
+    /// More votes for best or recommended than against.
+    pub fn is_recommended(&self) -> bool {
+        self.best + self.recommended > self.not_recommended
+    }
+
+    /// Best has strictly more votes than either other option.
+    pub fn is_best(&self) -> bool {
+        self.best > self.recommended && self.best > self.not_recommended
+    }
+}
+
+/// Every vote cast on a game's player counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerCountPoll {
+    /// One tally per voted player count, in ascending player count order.
+    pub tallies: Vec<PlayerCountTally>,
+}
+
+impl PlayerCountPoll {
+    pub fn best_at(&self) -> Vec<i32> {
+        self.tallies.iter().filter(|t| t.is_best()).map(|t| t.player_count).collect()
+    }
+
+    pub fn recommended_at(&self) -> Vec<i32> {
+        self.tallies.iter().filter(|t| t.is_recommended()).map(|t| t.player_count).collect()
+    }
+}
+
+impl BoardgameDb {
+    /// Records `voter`'s opinion of the game at `player_count`, replacing any earlier vote they
+    /// cast for that count. The count must be within the game's supported range.
+    pub fn vote_player_count(&self, boardgame_id: i64, player_count: i32, voter: &str, vote: PlayerCountVote) -> Result<(), Error> {
+        let voter = voter.trim();
+        if voter.is_empty() {
+            return Err(Error::InvalidInput("voter name cannot be empty".into()));
+        }
+        let boardgame = self.get_boardgame_by_id(boardgame_id)?
+            .ok_or_else(|| Error::InvalidInput(format!("no boardgame with id {}", boardgame_id)))?;
+        if !(boardgame.min_players..=boardgame.max_players).contains(&player_count) {
+            return Err(Error::InvalidInput(format!(
+                "{} supports {}-{} players, can't vote on {}",
+                boardgame.name, boardgame.min_players, boardgame.max_players, player_count
+            )));
+        }
+
+        self.conn.execute(
+            "INSERT INTO player_count_votes (boardgame_id, player_count, voter, vote) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (boardgame_id, player_count, voter) DO UPDATE SET vote = excluded.vote",
+            params![boardgame_id, player_count, voter, vote],
+        )?;
+        Ok(())
+    }
+
+    /// The voter's vote on the player count, if they have voted on it.
+    pub fn get_player_count_vote(&self, boardgame_id: i64, player_count: i32, voter: &str) -> Result<Option<PlayerCountVote>, Error> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT vote FROM player_count_votes WHERE boardgame_id = ?1 AND player_count = ?2 AND voter = ?3",
+                params![boardgame_id, player_count, voter.trim()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn remove_player_count_vote(&self, boardgame_id: i64, player_count: i32, voter: &str) -> Result<usize, Error> {
+        Ok(self.conn.execute(
+            "DELETE FROM player_count_votes WHERE boardgame_id = ?1 AND player_count = ?2 AND voter = ?3",
+            params![boardgame_id, player_count, voter.trim()],
+        )?)
+    }
+
+    pub fn get_player_count_poll(&self, boardgame_id: i64) -> Result<PlayerCountPoll, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT player_count, SUM(vote = 'best'), SUM(vote = 'recommended'), SUM(vote = 'not_recommended')
+             FROM player_count_votes WHERE boardgame_id = ?1
+             GROUP BY player_count ORDER BY player_count",
+        )?;
+        let tallies = stmt.query_map(params![boardgame_id], |row| {
+            Ok(PlayerCountTally {
+                player_count: row.get(0)?,
+                best: row.get(1)?,
+                recommended: row.get(2)?,
+                not_recommended: row.get(3)?,
+            })
+        })?;
+        Ok(PlayerCountPoll {
+            tallies: tallies.collect::<Result<Vec<PlayerCountTally>, rusqlite::Error>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Boardgame, BoardgameFilter};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_player_count_poll_and_filtering() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let id = db.create_boardgame(&Boardgame {
+            name: "Terraforming Mars".to_string(),
+            min_players: 1,
+            max_players: 5,
+            ..Default::default()
+        })?;
+
+        db.vote_player_count(id, 3, "ann", PlayerCountVote::Best)?;
+        db.vote_player_count(id, 3, "bob", PlayerCountVote::Best)?;
+        db.vote_player_count(id, 4, "ann", PlayerCountVote::NotRecommended)?;
+        db.vote_player_count(id, 2, "ann", PlayerCountVote::Best)?;
+        // A second vote from the same person replaces the first
+        db.vote_player_count(id, 2, "ann", PlayerCountVote::Recommended)?;
+        assert!(db.vote_player_count(id, 6, "ann", PlayerCountVote::Best).is_err());
+
+        let poll = db.get_player_count_poll(id)?;
+        assert_eq!(poll.best_at(), [3]);
+        assert_eq!(poll.recommended_at(), [2, 3]);
+
+        let filter = |players, player_count_match| BoardgameFilter {
+            players: Some(players),
+            player_count_match,
+            ..Default::default()
+        };
+        assert_eq!(db.find_boardgames(&filter(4, PlayerCountMatch::Supported))?.len(), 1);
+        assert_eq!(db.find_boardgames(&filter(4, PlayerCountMatch::Recommended))?.len(), 0);
+        assert_eq!(db.find_boardgames(&filter(2, PlayerCountMatch::Recommended))?.len(), 1);
+        assert_eq!(db.find_boardgames(&filter(2, PlayerCountMatch::Best))?.len(), 0);
+        assert_eq!(db.find_boardgames(&filter(3, PlayerCountMatch::Best))?.len(), 1);
+
+        Ok(())
+    }
+}
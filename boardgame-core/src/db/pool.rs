@@ -0,0 +1,206 @@
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{mpsc::Receiver, Arc, Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+use rusqlite::{Connection, OpenFlags};
+
+use super::{BoardgameDb, DbEvent, BUSY_TIMEOUT};
+use crate::errors::Error;
+
+/// How many read-only connections `BoardgamePool::open` allows at once.
+pub const DEFAULT_MAX_READERS: usize = 4;
+
+/// A handle to a database that can be cloned and shared between threads.
+///
+/// Writes go through a single writer connection, one at a time. Reads use a pool of read-only
+/// connections, and since the database is put in WAL mode they don't wait for writes: a read
+/// sees the database as of the last commit before it started.
+#[derive(Debug, Clone)]
+pub struct BoardgamePool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    path: PathBuf,
+    writer: Mutex<BoardgameDb>,
+    readers: Mutex<Readers>,
+    reader_returned: Condvar,
+    max_readers: usize,
+}
+
+#[derive(Debug, Default)]
+struct Readers {
+    idle: Vec<BoardgameDb>,
+    open: usize,
+}
+
+/// A read-only connection borrowed from a `BoardgamePool`, returned to it when dropped.
+#[derive(Debug)]
+pub struct PooledReader<'pool> {
+    pool: &'pool PoolInner,
+    db: Option<BoardgameDb>,
+}
+
+impl Deref for PooledReader<'_> {
+    type Target = BoardgameDb;
+
+    fn deref(&self) -> &BoardgameDb {
+        self.db.as_ref().expect("reader is only taken when dropped")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let Some(db) = self.db.take() {
+            lock(&self.pool.readers).idle.push(db);
+            self.pool.reader_returned.notify_one();
+        }
+    }
+}
+
+/// A panic while a connection was in use rolls back its transaction, so it is still usable.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl BoardgamePool {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::with_max_readers(path, DEFAULT_MAX_READERS)
+    }
+
+    pub fn with_max_readers<P: AsRef<Path>>(path: P, max_readers: usize) -> Result<Self, Error> {
+        if max_readers == 0 {
+            return Err(Error::InvalidInput("a pool needs at least one reader".into()));
+        }
+        let writer = BoardgameDb::new(&path)?;
+        writer.conn.pragma_update(None, "journal_mode", "WAL")?;
+        // Switching modes moves the data version, which isn't a change anyone else made
+        writer.check_external_changes()?;
+
+        Ok(BoardgamePool {
+            inner: Arc::new(PoolInner {
+                path: path.as_ref().to_path_buf(),
+                writer: Mutex::new(writer),
+                readers: Mutex::new(Readers::default()),
+                reader_returned: Condvar::new(),
+                max_readers,
+            }),
+        })
+    }
+
+    /// Runs `f` in a transaction on the writer, waiting for any other write to finish first.
+    pub fn write<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, E>,
+        E: From<Error>,
+    {
+        self.writer().in_transaction(f)
+    }
+
+    /// Runs `f` on a read-only connection. Everything it reads is from the same snapshot.
+    pub fn read<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, E>,
+        E: From<Error>,
+    {
+        self.reader()?.in_transaction(f)
+    }
+
+    /// Events for every commit made through the pool, since they all go through the writer.
+    pub fn subscribe(&self) -> Receiver<DbEvent> {
+        self.writer().subscribe()
+    }
+
+    /// The writer connection, held until the guard is dropped.
+    pub fn writer(&self) -> MutexGuard<'_, BoardgameDb> {
+        lock(&self.inner.writer)
+    }
+
+    /// A read-only connection, opening one if there is room or waiting for one to be returned.
+    pub fn reader(&self) -> Result<PooledReader<'_>, Error> {
+        let mut readers = lock(&self.inner.readers);
+        loop {
+            if let Some(db) = readers.idle.pop() {
+                return Ok(PooledReader { pool: &self.inner, db: Some(db) });
+            }
+            if readers.open < self.inner.max_readers {
+                readers.open += 1;
+                drop(readers);
+                return match open_reader(&self.inner.path) {
+                    Ok(db) => Ok(PooledReader { pool: &self.inner, db: Some(db) }),
+                    Err(e) => {
+                        lock(&self.inner.readers).open -= 1;
+                        Err(e)
+                    }
+                };
+            }
+            readers = self.inner.reader_returned.wait(readers).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+/// Read-only connections skip migrations and history triggers, the writer has already set them up.
+fn open_reader(path: &Path) -> Result<BoardgameDb, Error> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    BoardgameDb::from_connection(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Boardgame;
+    use std::{sync::mpsc, thread};
+    use tempfile::tempdir;
+
+    fn game(name: &str) -> Boardgame {
+        Boardgame { name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_readers_run_while_a_writer_commits() -> Result<(), Error> {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BoardgamePool>();
+
+        let dir = tempdir().expect("failed to create temp directory");
+        let pool = BoardgamePool::with_max_readers(dir.path().join("test.db"), 3)?;
+        pool.write(|db| db.create_boardgame(&game("Azul")))?;
+
+        let (written, wait_for_write) = mpsc::channel();
+        let (release, wait_for_release) = mpsc::channel::<()>();
+        thread::scope(|scope| -> Result<(), Error> {
+            let pool = &pool;
+            let writer = scope.spawn(move || {
+                pool.write(|db| {
+                    db.create_boardgame(&game("Brass"))?;
+                    written.send(()).expect("test is waiting");
+                    wait_for_release.recv().expect("test releases the writer");
+                    Ok::<_, Error>(())
+                })
+            });
+            wait_for_write.recv().expect("writer started");
+
+            // More readers than connections, none of them blocked by the open write
+            let readers: Vec<_> = (0..6)
+                .map(|_| scope.spawn(move || pool.read(|db| db.get_all_boardgames())))
+                .collect();
+            for reader in readers {
+                let names: Vec<String> = reader.join().expect("reader panicked")?.into_iter().map(|b| b.name).collect();
+                assert_eq!(names, ["Azul"], "uncommitted writes aren't visible");
+            }
+
+            release.send(()).expect("writer is waiting");
+            writer.join().expect("writer panicked")
+        })?;
+
+        assert_eq!(pool.read(|db| db.get_all_boardgames())?.len(), 2);
+        assert!(pool.reader()?.create_boardgame(&game("Catan")).is_err(), "readers are read-only");
+        Ok(())
+    }
+}
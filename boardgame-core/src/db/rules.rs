@@ -0,0 +1,158 @@
+use chrono::NaiveDateTime;
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{filter::like_pattern, BoardgameDb};
+use crate::errors::Error;
+
+/// A house rule or variant our group plays a game with. `body` is markdown.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HouseRule {
+    pub id: Option<i64>,
+    pub boardgame_id: i64,
+    pub title: String,
+    pub body: String,
+    pub author: String,
+    /// Set by the database when the rule is created.
+    pub created_at: Option<NaiveDateTime>,
+    /// Whether we currently play with this rule.
+    pub active: bool,
+}
+
+impl HouseRule {
+    fn from_row(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(HouseRule {
+            id: Some(row.get(0)?),
+            boardgame_id: row.get(1)?,
+            title: row.get(2)?,
+            body: row.get(3)?,
+            author: row.get(4)?,
+            created_at: row.get(5)?,
+            active: row.get(6)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.title.trim().is_empty() {
+            return Err(Error::InvalidInput("house rule title cannot be empty".into()));
+        }
+        Ok(())
+    }
+}
+
+const RULE_COLUMNS: &str = "id, boardgame_id, title, body, author, created_at, active";
+
+impl BoardgameDb {
+    /// Creates the rule with `rule.id` and `rule.created_at` if they are set, which is how
+    /// a deleted rule is put back.
+    pub fn create_house_rule(&self, rule: &HouseRule) -> Result<i64, Error> {
+        rule.validate()?;
+        self.conn.execute(
+            "INSERT INTO house_rules (id, boardgame_id, title, body, author, active, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, coalesce(?7, CURRENT_TIMESTAMP))",
+            params![rule.id, rule.boardgame_id, rule.title.trim(), rule.body, rule.author.trim(), rule.active, rule.created_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_house_rule(&self, rule: &HouseRule) -> Result<usize, Error> {
+        let id = rule.id.ok_or_else(|| Error::InvalidInput("house rule must have an id to update".into()))?;
+        rule.validate()?;
+        Ok(self.conn.execute(
+            "UPDATE house_rules SET title = ?1, body = ?2, author = ?3, active = ?4 WHERE id = ?5",
+            params![rule.title.trim(), rule.body, rule.author.trim(), rule.active, id],
+        )?)
+    }
+
+    pub fn set_house_rule_active(&self, id: i64, active: bool) -> Result<usize, Error> {
+        Ok(self.conn.execute("UPDATE house_rules SET active = ?1 WHERE id = ?2", params![active, id])?)
+    }
+
+    pub fn delete_house_rule(&self, id: i64) -> Result<usize, Error> {
+        Ok(self.conn.execute("DELETE FROM house_rules WHERE id = ?1", params![id])?)
+    }
+
+    pub fn get_house_rule(&self, id: i64) -> Result<Option<HouseRule>, Error> {
+        Ok(self
+            .conn
+            .query_row(&format!("SELECT {RULE_COLUMNS} FROM house_rules WHERE id = ?1"), params![id], HouseRule::from_row)
+            .optional()?)
+    }
+
+    /// A game's rules, active ones first and then oldest first.
+    pub fn get_house_rules(&self, boardgame_id: i64) -> Result<Vec<HouseRule>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RULE_COLUMNS} FROM house_rules WHERE boardgame_id = ?1 ORDER BY active DESC, id"
+        ))?;
+        let rules = stmt.query_map(params![boardgame_id], HouseRule::from_row)?;
+        Ok(rules.collect::<Result<Vec<HouseRule>, rusqlite::Error>>()?)
+    }
+
+    /// Rules on any game not in the trash whose title, body or author contains every word of `query`, ignoring case.
+    pub fn search_house_rules(&self, query: &str) -> Result<Vec<HouseRule>, Error> {
+        let words: Vec<String> = query.split_whitespace().map(like_pattern).collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conditions = (1..=words.len())
+            .map(|i| format!("(title || ' ' || body || ' ' || author) LIKE ?{i} ESCAPE '\\'"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RULE_COLUMNS} FROM house_rules
+             WHERE {conditions} AND boardgame_id IN (SELECT id FROM boardgames WHERE deleted_at IS NULL)
+             ORDER BY boardgame_id, active DESC, id"
+        ))?;
+        let rules = stmt.query_map(rusqlite::params_from_iter(words), HouseRule::from_row)?;
+        Ok(rules.collect::<Result<Vec<HouseRule>, rusqlite::Error>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Boardgame;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_house_rules_and_search() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+        let catan = db.create_boardgame(&Boardgame { name: "Catan".to_string(), ..Default::default() })?;
+        let azul = db.create_boardgame(&Boardgame { name: "Azul".to_string(), ..Default::default() })?;
+
+        let friendly = db.create_house_rule(&HouseRule {
+            boardgame_id: catan,
+            title: "Friendly robber".to_string(),
+            body: "The robber can't target players with **2 points** or fewer.".to_string(),
+            author: "Sam".to_string(),
+            active: true,
+            ..Default::default()
+        })?;
+        db.create_house_rule(&HouseRule {
+            boardgame_id: azul,
+            title: "No floor penalty".to_string(),
+            body: "First round only.".to_string(),
+            author: "Alex".to_string(),
+            ..Default::default()
+        })?;
+        assert!(db.create_house_rule(&HouseRule { boardgame_id: azul, ..Default::default() }).is_err());
+
+        let rules = db.get_house_rules(catan)?;
+        assert_eq!(rules.len(), 1);
+        assert!(rules[0].created_at.is_some());
+
+        assert_eq!(db.search_house_rules("ROBBER points")?.len(), 1);
+        assert_eq!(db.search_house_rules("robber round")?.len(), 0);
+        assert_eq!(db.search_house_rules("alex")?[0].boardgame_id, azul);
+
+        db.set_house_rule_active(friendly, false)?;
+        assert!(!db.get_house_rules(catan)?[0].active);
+
+        let rule = db.get_house_rule(friendly)?.unwrap();
+        db.delete_house_rule(friendly)?;
+        assert_eq!(db.create_house_rule(&rule)?, friendly);
+        assert_eq!(db.get_house_rule(friendly)?, Some(rule));
+
+        Ok(())
+    }
+}
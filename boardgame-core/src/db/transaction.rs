@@ -0,0 +1,159 @@
+use std::ops::Deref;
+
+use super::BoardgameDb;
+use crate::errors::Error;
+
+/// An open transaction. Use the database through it, since it derefs to `BoardgameDb`, then
+/// `commit`. Dropping it without committing rolls everything back.
+///
+/// Transactions nest: every method that writes more than one row opens its own, and opening
+/// one inside another makes a savepoint that only commits into the outer transaction.
+#[derive(Debug)]
+pub struct Transaction<'db> {
+    db: &'db BoardgameDb,
+    name: String,
+    /// How many transactions this one is inside, which is 0 for the outermost.
+    depth: usize,
+    finished: bool,
+    /// Where this transaction's changes start in the pending change notifications.
+    changes_before: usize,
+}
+
+impl Transaction<'_> {
+    pub fn commit(mut self) -> Result<(), Error> {
+        self.finish(&format!("RELEASE {}", self.name))
+    }
+
+    pub fn rollback(mut self) -> Result<(), Error> {
+        self.db.events.discard_since(self.changes_before);
+        self.finish(&format!("ROLLBACK TO {0}; RELEASE {0}", self.name))
+    }
+
+    /// Runs the SQL that ends the transaction, which is only ended if it succeeds. Otherwise
+    /// dropping it rolls it back.
+    fn finish(&mut self, sql: &str) -> Result<(), Error> {
+        self.db.conn.execute_batch(sql)?;
+        self.finished = true;
+        self.db.savepoint_depth.set(self.depth);
+        Ok(())
+    }
+}
+
+impl Deref for Transaction<'_> {
+    type Target = BoardgameDb;
+
+    fn deref(&self) -> &BoardgameDb {
+        self.db
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.db.events.discard_since(self.changes_before);
+            // Releasing the outermost savepoint commits, which may be what just failed, so it
+            // is ended with a plain rollback
+            let sql = match self.depth {
+                0 => "ROLLBACK".to_string(),
+                _ => format!("ROLLBACK TO {0}; RELEASE {0}", self.name),
+            };
+            // Nothing to report the error to, and the connection rolls back when closed anyway
+            let _ = self.db.conn.execute_batch(&sql);
+            self.db.savepoint_depth.set(self.depth);
+        }
+    }
+}
+
+impl BoardgameDb {
+    /// Starts a transaction, or a savepoint if one is already open.
+    pub fn transaction(&self) -> Result<Transaction<'_>, Error> {
+        let depth = self.savepoint_depth.get();
+        let name = format!("boardgame_db_{depth}");
+        self.conn.execute_batch(&format!("SAVEPOINT {name}"))?;
+        self.savepoint_depth.set(depth + 1);
+        Ok(Transaction { db: self, name, depth, finished: false, changes_before: self.events.mark() })
+    }
+
+    /// Runs `f` in a transaction that is committed if it returns `Ok` and rolled back otherwise.
+    pub fn in_transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&BoardgameDb) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let tx = self.transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::db::Boardgame;
+    use rusqlite::Connection;
+    use tempfile::tempdir;
+
+    fn game(name: &str) -> Boardgame {
+        Boardgame { name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_transactions_nest_and_roll_back() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let db = BoardgameDb::new(dir.path().join("test.db"))?;
+
+        let tx = db.transaction()?;
+        tx.create_boardgame(&game("Azul"))?;
+        let inner = tx.transaction()?;
+        inner.create_boardgame(&game("Brass"))?;
+        drop(inner);
+        tx.create_boardgame(&game("Catan"))?;
+        tx.commit()?;
+        let names: Vec<String> = db.get_all_boardgames()?.into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["Azul", "Catan"]);
+
+        let result = db.in_transaction(|db| {
+            db.create_boardgame(&game("Dune"))?;
+            db.create_boardgame(&Boardgame { weight: Some(9.0), ..game("Everdell") })
+        });
+        assert!(result.is_err());
+        assert_eq!(db.get_all_boardgames()?.len(), 2);
+
+        // A bad game anywhere in a bulk create means none are created
+        assert!(db.create_boardgames(&[game("Fog"), Boardgame { weight: Some(0.0), ..game("Gloom") }]).is_err());
+        let ids = db.create_boardgames(&[game("Fog"), game("Gloom")])?;
+        assert_eq!(ids.len(), 2);
+        assert_eq!(db.get_boardgame_by_id(ids[1])?.unwrap().name, "Gloom");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_commit_rolls_back() -> Result<(), Error> {
+        let dir = tempdir().expect("failed to create temp directory");
+        let path = dir.path().join("test.db");
+        let db = BoardgameDb::new(&path)?;
+        db.conn.busy_timeout(Duration::ZERO)?;
+
+        // A reader in the middle of a read keeps the writer from committing
+        let reader = Connection::open(&path)?;
+        reader.execute_batch("BEGIN")?;
+        reader.query_row("SELECT COUNT(*) FROM boardgames", [], |row| row.get::<_, i64>(0))?;
+        let tx = db.transaction()?;
+        tx.create_boardgame(&game("Azul"))?;
+        assert!(tx.commit().is_err());
+        reader.execute_batch("COMMIT")?;
+
+        // The failed transaction was rolled back rather than left open under the next one
+        assert!(db.get_all_boardgames()?.is_empty());
+        db.in_transaction(|db| db.create_boardgame(&game("Brass")))?;
+        let other = BoardgameDb::new(&path)?;
+        let names: Vec<String> = other.get_all_boardgames()?.into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["Brass"]);
+
+        Ok(())
+    }
+}
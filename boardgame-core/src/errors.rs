@@ -4,5 +4,10 @@ use thiserror::Error;
 pub enum Error {
     #[error("Database error: {0}")]
     DatabaseError(#[from]rusqlite::Error),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Image error: {0}")]
+    ImageError(#[from]image::ImageError),
+    #[error("CSV error: {0}")]
+    CsvError(#[from]csv::Error),
 }
-
@@ -5,3 +5,69 @@ pub static BG_MAX_PLAYERS: &str = "Max players";
 pub static BG_PLAY_TIME: &str = "Play time (minutes)";
 pub static BG_DESCRIPTION: &str = "Description";
 pub static BG_FIELDS: [&str; 5] = [BG_NAME, BG_MIN_PLAYERS, BG_MAX_PLAYERS, BG_PLAY_TIME, BG_DESCRIPTION];
+
+pub static BG_DESIGNERS: &str = "Designers";
+pub static BG_ARTISTS: &str = "Artists";
+pub static BG_PUBLISHERS: &str = "Publishers";
+pub static BG_CREDIT_FIELDS: [&str; 3] = [BG_DESIGNERS, BG_ARTISTS, BG_PUBLISHERS];
+
+pub static BG_WEIGHT: &str = "Weight (1-5)";
+pub static BG_MIN_AGE: &str = "Min age";
+pub static BG_YEAR_PUBLISHED: &str = "Year published";
+pub static BG_MECHANICS: &str = "Mechanics";
+pub static BG_METADATA_FIELDS: [&str; 4] = [BG_WEIGHT, BG_MIN_AGE, BG_YEAR_PUBLISHED, BG_MECHANICS];
+
+// FILTER
+pub static FILTER_NAME: &str = "Name contains";
+pub static FILTER_CREDIT: &str = "Designer, artist or publisher";
+pub static FILTER_MIN_WEIGHT: &str = "Min weight";
+pub static FILTER_MAX_WEIGHT: &str = "Max weight";
+pub static FILTER_AGE: &str = "Suitable for age";
+pub static FILTER_MECHANICS: &str = "Has mechanics";
+pub static FILTER_PLAYERS: &str = "Players";
+pub static FILTER_PLAYER_COUNT_MATCH: &str = "Players match";
+pub static FILTER_FIELDS: [&str; 8] = [
+    FILTER_NAME, FILTER_CREDIT, FILTER_MIN_WEIGHT, FILTER_MAX_WEIGHT, FILTER_AGE, FILTER_MECHANICS,
+    FILTER_PLAYERS, FILTER_PLAYER_COUNT_MATCH,
+];
+
+// PLAYER COUNT VOTES
+pub static VOTE_VOTER: &str = "Your name";
+pub static VOTE_PLAYER_COUNT: &str = "Player count";
+
+// CUSTOM FIELDS
+pub static CUSTOM_FIELD_NAME: &str = "Field name";
+pub static CUSTOM_FIELD_TYPE: &str = "Type (text, number, bool, date or enum: a, b, c)";
+
+// IMAGES
+pub static IMAGE_PATH: &str = "Image file (PNG or JPEG)";
+
+// COPIES
+pub static COPY_EDITION: &str = "Edition";
+pub static COPY_LANGUAGE: &str = "Language";
+pub static COPY_PUBLISHER_YEAR: &str = "Publisher year";
+
+// COMPONENTS
+pub static COMPONENT_NAME: &str = "Component";
+pub static COMPONENT_EXPECTED: &str = "Expected count";
+pub static COMPONENT_NOTES: &str = "Missing-piece notes";
+
+// HOUSE RULES
+pub static RULE_TITLE: &str = "Title";
+pub static RULE_AUTHOR: &str = "Author";
+pub static RULE_BODY: &str = "Rule (markdown)";
+pub static RULE_SEARCH: &str = "Search house rules";
+
+// PLACEHOLDERS
+/// Hints shown in empty inputs, by input label.
+pub static PLACEHOLDERS: [(&str, &str); 9] = [
+    (BG_DESIGNERS, "Uwe Rosenberg, Klemens Franz"),
+    (BG_ARTISTS, "Comma-separated names"),
+    (BG_PUBLISHERS, "Comma-separated names"),
+    (BG_MECHANICS, "Worker placement, Drafting"),
+    (FILTER_CREDIT, "Any part of a name"),
+    (FILTER_MECHANICS, "Worker placement, Drafting"),
+    (VOTE_VOTER, "Who is voting"),
+    (IMAGE_PATH, "path/to/cover.png"),
+    (RULE_SEARCH, "Words to look for"),
+];